@@ -2,14 +2,34 @@
 ///
 /// A board is where DKG participants publish their data for the corresponding DKG
 /// phase.
-use super::primitives::types::{BundledJustification, BundledResponses, BundledShares};
+use super::primitives::{
+    auth::{Bundle, Signed},
+    group::Group,
+    types::{conflicting, BundledJustification, BundledResponses, BundledShares, Equivocation},
+};
 use async_trait::async_trait;
 use bincode::serialize_into;
-use std::io::Write;
+use rand::thread_rng;
+use serde::de::DeserializeOwned;
+use std::{collections::HashMap, fmt::Debug, io::Write, mem, time::Duration};
+use thiserror::Error;
 use threshold_bls::group::Curve;
 
+/// The phases of the DKG during which participants publish to the board
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BoardPhase {
+    /// Dealers publish their encrypted shares
+    Shares,
+    /// Share holders publish their responses
+    Responses,
+    /// Dealers publish their justifications
+    Justifications,
+}
+
 /// Trait which must be implemented for writing to the board. This trait assumes
-/// an authenticated channel.
+/// an authenticated channel, see [`AuthenticatedBoard`] otherwise.
+///
+/// [`AuthenticatedBoard`]: struct.AuthenticatedBoard.html
 #[async_trait(?Send)]
 pub trait BoardPublisher<C>
 where
@@ -37,6 +57,48 @@ where
         C: 'async_trait;
 }
 
+/// Trait which must be implemented for reading from the board. Each read
+/// returns all the bundles published so far for the corresponding phase. This
+/// trait assumes an authenticated channel, see [`AuthenticatedBoard`]
+/// otherwise.
+///
+/// [`AuthenticatedBoard`]: struct.AuthenticatedBoard.html
+#[async_trait(?Send)]
+pub trait BoardReader<C>
+where
+    C: Curve,
+{
+    /// Error raised when trying to read data from the board
+    type Error;
+
+    /// Reads the shares published on the board
+    async fn read_shares(&mut self) -> Result<Vec<BundledShares<C>>, Self::Error>
+    where
+        C: 'async_trait;
+
+    /// Reads the responses published on the board
+    async fn read_responses(&mut self) -> Result<Vec<BundledResponses>, Self::Error>
+    where
+        C: 'async_trait;
+
+    /// Reads the justifications published on the board
+    async fn read_justifications(&mut self) -> Result<Vec<BundledJustification<C>>, Self::Error>
+    where
+        C: 'async_trait;
+
+    /// Resolves once the deadline of the provided phase has passed, i.e. once
+    /// no more bundles are accepted for it. `timeout` is the duration of the
+    /// phase as configured by the caller; boards which have their own notion
+    /// of deadlines (e.g. block heights) may ignore it.
+    async fn wait_for_deadline(
+        &mut self,
+        phase: BoardPhase,
+        timeout: Duration,
+    ) -> Result<(), Self::Error>
+    where
+        C: 'async_trait;
+}
+
 // Board implementation for all `Write` implementers, leveraging serde/bincode
 #[async_trait(?Send)]
 impl<C, W> BoardPublisher<C> for W
@@ -71,3 +133,271 @@ where
         serialize_into(self, &justifications)
     }
 }
+
+/// Trait which must be implemented by transports which relay opaque messages
+/// between participants without authenticating them, e.g. a gossip network.
+/// Wrap it in an [`AuthenticatedBoard`] to run a DKG over it.
+///
+/// [`AuthenticatedBoard`]: struct.AuthenticatedBoard.html
+#[async_trait(?Send)]
+pub trait BoardTransport {
+    /// Error raised when trying to send or receive messages
+    type Error;
+
+    /// Broadcasts the message to all participants for the provided phase
+    async fn broadcast(&mut self, phase: BoardPhase, msg: Vec<u8>) -> Result<(), Self::Error>;
+
+    /// Returns all the messages received so far for the provided phase
+    async fn receive(&mut self, phase: BoardPhase) -> Result<Vec<Vec<u8>>, Self::Error>;
+
+    /// Resolves once the deadline of the provided phase has passed
+    async fn wait_for_deadline(
+        &mut self,
+        phase: BoardPhase,
+        timeout: Duration,
+    ) -> Result<(), Self::Error>;
+}
+
+/// Errors which may occur while publishing to or reading from an
+/// [`AuthenticatedBoard`]
+///
+/// [`AuthenticatedBoard`]: struct.AuthenticatedBoard.html
+#[derive(Debug, Error)]
+pub enum AuthenticatedBoardError<E: Debug> {
+    /// TransportError is raised when the underlying transport fails
+    #[error("transport error: {0:?}")]
+    TransportError(E),
+
+    /// BincodeError is raised when de(serialization) by bincode fails
+    #[error("de(serialization failed: {0})")]
+    BincodeError(#[from] bincode::Error),
+
+    /// MissingKey is raised when publishing a bundle for a role (dealer or
+    /// share holder) for which no signing key was provided
+    #[error("no key to sign the bundle with")]
+    MissingKey,
+}
+
+/// A board which signs every published bundle with the long-term key of the
+/// participant, and drops every bundle which is not signed by the key
+/// registered in the group for its `dealer_idx` or `share_idx`. This makes
+/// it possible to run the DKG over transports which do not authenticate their
+/// messages.
+///
+/// The board also keeps the signed bundles of the participants which
+/// published two conflicting bundles during a phase, as an [`Equivocation`]
+/// which can be verified by anyone knowing the group.
+///
+/// [`Equivocation`]: ../primitives/enum.Equivocation.html
+pub struct AuthenticatedBoard<C: Curve, T> {
+    transport: T,
+    dealer_key: Option<C::Scalar>,
+    share_holder_key: Option<C::Scalar>,
+    dealers: Group<C>,
+    share_holders: Group<C>,
+    equivocations: Vec<Equivocation<C>>,
+}
+
+impl<C: Curve, T: BoardTransport> AuthenticatedBoard<C, T> {
+    /// Creates a board for a DKG where the dealers are also the share holders.
+    /// `private_key` must correspond to the key of the participant in `group`.
+    pub fn new(transport: T, private_key: C::Scalar, group: Group<C>) -> Self {
+        Self {
+            transport,
+            dealer_key: Some(private_key.clone()),
+            share_holder_key: Some(private_key),
+            dealers: group.clone(),
+            share_holders: group,
+            equivocations: Vec::new(),
+        }
+    }
+
+    /// Creates a board for a resharing. A participant which is only a dealer
+    /// (resp. share holder) does not need to provide a share holder (resp.
+    /// dealer) key.
+    ///
+    /// The dealers of a resharing are indexed by share index, so `dealers`
+    /// must be the expanded previous group, i.e. `qual.expand()`. Each share
+    /// index is registered with the key of the node owning it, which signs
+    /// the bundles of all its shares.
+    pub fn new_resharing(
+        transport: T,
+        dealer_key: Option<C::Scalar>,
+        share_holder_key: Option<C::Scalar>,
+        dealers: Group<C>,
+        share_holders: Group<C>,
+    ) -> Self {
+        Self {
+            transport,
+            dealer_key,
+            share_holder_key,
+            dealers,
+            share_holders,
+            equivocations: Vec::new(),
+        }
+    }
+
+    /// Returns a reference to the underlying transport
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    /// Returns the proofs against the participants which signed conflicting
+    /// bundles, among the bundles read so far
+    pub fn equivocations(&self) -> &[Equivocation<C>] {
+        &self.equivocations
+    }
+
+    /// Consumes the board and returns the underlying transport
+    pub fn into_transport(self) -> T {
+        self.transport
+    }
+
+    async fn publish<B: Bundle>(
+        &mut self,
+        phase: BoardPhase,
+        bundle: B,
+        key: Option<C::Scalar>,
+    ) -> Result<(), AuthenticatedBoardError<T::Error>>
+    where
+        T::Error: Debug,
+    {
+        let key = key.ok_or(AuthenticatedBoardError::MissingKey)?;
+        let signed = Signed::<C, B>::sign(bundle, &key, &mut thread_rng());
+        self.transport
+            .broadcast(phase, bincode::serialize(&signed)?)
+            .await
+            .map_err(AuthenticatedBoardError::TransportError)
+    }
+
+    async fn read<B: Bundle + Clone + DeserializeOwned>(
+        &mut self,
+        phase: BoardPhase,
+        equivocation: impl Fn(Signed<C, B>, Signed<C, B>) -> Equivocation<C>,
+    ) -> Result<Vec<B>, AuthenticatedBoardError<T::Error>>
+    where
+        T::Error: Debug,
+    {
+        let msgs = self
+            .transport
+            .receive(phase)
+            .await
+            .map_err(AuthenticatedBoardError::TransportError)?;
+
+        // messages which cannot be decoded are dropped like the ones with an
+        // invalid signature, since anyone can write to the transport
+        let group = match phase {
+            BoardPhase::Responses => &self.share_holders,
+            BoardPhase::Shares | BoardPhase::Justifications => &self.dealers,
+        };
+        let signed = msgs
+            .iter()
+            .filter_map(|msg| bincode::deserialize::<Signed<C, B>>(msg).ok())
+            .filter(|signed| signed.verify(group))
+            .collect::<Vec<_>>();
+
+        // the conflicting bundles are all returned, so that the DKG
+        // disqualifies their sender, but a proof is kept for each of them
+        let mut firsts = HashMap::new();
+        for signed in &signed {
+            let first = firsts.entry(signed.bundle.sender()).or_insert(signed);
+            if conflicting(signed.bundle.session(), &first.bundle, &signed.bundle) {
+                let proof = equivocation((*first).clone(), signed.clone());
+                let known = self.equivocations.iter().any(|e| {
+                    e.culprit() == proof.culprit()
+                        && mem::discriminant(e) == mem::discriminant(&proof)
+                });
+                if !known {
+                    self.equivocations.push(proof);
+                }
+            }
+        }
+
+        Ok(signed.into_iter().map(|signed| signed.bundle).collect())
+    }
+}
+
+#[async_trait(?Send)]
+impl<C, T> BoardPublisher<C> for AuthenticatedBoard<C, T>
+where
+    C: Curve,
+    T: BoardTransport,
+    T::Error: Debug,
+{
+    type Error = AuthenticatedBoardError<T::Error>;
+
+    async fn publish_shares(&mut self, shares: BundledShares<C>) -> Result<(), Self::Error>
+    where
+        C: 'async_trait,
+    {
+        let key = self.dealer_key.clone();
+        self.publish(BoardPhase::Shares, shares, key).await
+    }
+
+    async fn publish_responses(&mut self, responses: BundledResponses) -> Result<(), Self::Error>
+    where
+        C: 'async_trait,
+    {
+        let key = self.share_holder_key.clone();
+        self.publish(BoardPhase::Responses, responses, key).await
+    }
+
+    async fn publish_justifications(
+        &mut self,
+        justifications: BundledJustification<C>,
+    ) -> Result<(), Self::Error>
+    where
+        C: 'async_trait,
+    {
+        let key = self.dealer_key.clone();
+        self.publish(BoardPhase::Justifications, justifications, key)
+            .await
+    }
+}
+
+#[async_trait(?Send)]
+impl<C, T> BoardReader<C> for AuthenticatedBoard<C, T>
+where
+    C: Curve,
+    T: BoardTransport,
+    T::Error: Debug,
+{
+    type Error = AuthenticatedBoardError<T::Error>;
+
+    async fn read_shares(&mut self) -> Result<Vec<BundledShares<C>>, Self::Error>
+    where
+        C: 'async_trait,
+    {
+        self.read(BoardPhase::Shares, Equivocation::Shares).await
+    }
+
+    async fn read_responses(&mut self) -> Result<Vec<BundledResponses>, Self::Error>
+    where
+        C: 'async_trait,
+    {
+        self.read(BoardPhase::Responses, Equivocation::Responses)
+            .await
+    }
+
+    async fn read_justifications(&mut self) -> Result<Vec<BundledJustification<C>>, Self::Error>
+    where
+        C: 'async_trait,
+    {
+        self.read(BoardPhase::Justifications, Equivocation::Justifications)
+            .await
+    }
+
+    async fn wait_for_deadline(
+        &mut self,
+        phase: BoardPhase,
+        timeout: Duration,
+    ) -> Result<(), Self::Error>
+    where
+        C: 'async_trait,
+    {
+        self.transport
+            .wait_for_deadline(phase, timeout)
+            .await
+            .map_err(AuthenticatedBoardError::TransportError)
+    }
+}
@@ -0,0 +1,353 @@
+//! # Checkpoints
+//!
+//! The state of a DKG participant contains its long-term private key and its
+//! secret polynomial, so it is encrypted before being written to disk. A
+//! checkpoint is laid out as follows:
+//!
+//! `magic (4) | version (1) | key kind (1) | salt (16) | nonce (12) | ciphertext`
+//!
+//! The header is authenticated as associated data of the AEAD, and the
+//! encryption key is derived from the salt and either a passphrase (PBKDF2) or
+//! a raw 32 bytes key (HKDF).
+use crate::{primitives::phases::Phase0, runner::DKGState};
+
+use chacha20poly1305::{
+    aead::{Aead, NewAead, Payload},
+    ChaCha20Poly1305,
+};
+use hkdf::Hkdf;
+use hmac::Hmac;
+use rand::{thread_rng, RngCore};
+use sha2::Sha256;
+use std::{
+    fs,
+    io::{self, ErrorKind, Write},
+    path::{Path, PathBuf},
+};
+use thiserror::Error;
+use threshold_bls::group::Curve;
+
+/// Magic bytes identifying a checkpoint
+const MAGIC: [u8; 4] = *b"DKGC";
+
+/// The current version of the checkpoint format
+pub const CHECKPOINT_VERSION: u8 = 1;
+
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const KEY_LEN: usize = 32;
+const HEADER_LEN: usize = MAGIC.len() + 2 + SALT_LEN + NONCE_LEN;
+
+/// Number of PBKDF2 iterations when deriving the key from a passphrase
+const PBKDF2_ROUNDS: usize = 100_000;
+
+/// A domain separator
+const DOMAIN: &[u8] = b"dkg-core checkpoint";
+
+/// Errors which may occur while sealing, opening or storing a checkpoint
+#[derive(Debug, Error)]
+pub enum CheckpointError {
+    /// InvalidHeader is raised when the checkpoint is too short or does not
+    /// start with the expected magic bytes.
+    #[error("invalid checkpoint header")]
+    InvalidHeader,
+
+    /// UnsupportedVersion is raised when the checkpoint was written with a
+    /// different version of the format.
+    #[error("unsupported checkpoint version {0}, expected {1}")]
+    UnsupportedVersion(u8, u8),
+
+    /// KeyMismatch is raised when the checkpoint was sealed with a different
+    /// kind of key (passphrase or raw key) than the one provided.
+    #[error("checkpoint was not sealed with this kind of key")]
+    KeyMismatch,
+
+    /// DecryptionError is raised when the key is wrong or the checkpoint has
+    /// been tampered with.
+    #[error("could not decrypt checkpoint")]
+    DecryptionError,
+
+    /// BincodeError is raised when de(serialization) by bincode fails
+    #[error("de(serialization failed: {0})")]
+    BincodeError(#[from] bincode::Error),
+
+    /// IoError is raised when the checkpoint cannot be read from or written
+    /// to its storage.
+    #[error("could not access checkpoint: {0}")]
+    IoError(#[from] io::Error),
+}
+
+/// The secret from which the encryption key of a checkpoint is derived
+#[derive(Clone)]
+pub enum CheckpointKey {
+    /// A human chosen passphrase, stretched with PBKDF2
+    Passphrase(Vec<u8>),
+    /// A uniformly random key, e.g. coming from a KMS
+    Key([u8; KEY_LEN]),
+}
+
+impl CheckpointKey {
+    fn kind(&self) -> u8 {
+        match self {
+            CheckpointKey::Passphrase(_) => 0,
+            CheckpointKey::Key(_) => 1,
+        }
+    }
+
+    fn derive(&self, salt: &[u8]) -> [u8; KEY_LEN] {
+        let mut key = [0u8; KEY_LEN];
+        match self {
+            CheckpointKey::Passphrase(passphrase) => {
+                pbkdf2::pbkdf2::<Hmac<Sha256>>(passphrase, salt, PBKDF2_ROUNDS, &mut key);
+            }
+            CheckpointKey::Key(ikm) => {
+                Hkdf::<Sha256>::new(Some(salt), ikm)
+                    .expand(DOMAIN, &mut key)
+                    .expect("hkdf should not fail");
+            }
+        }
+        key
+    }
+}
+
+/// Serializes the provided state and encrypts it under the key. The output can
+/// be stored anywhere and restored with [`open`].
+///
+/// [`open`]: fn.open.html
+pub fn seal<C, P, R>(
+    state: &DKGState<C, P>,
+    key: &CheckpointKey,
+    rng: &mut R,
+) -> Result<Vec<u8>, CheckpointError>
+where
+    C: Curve,
+    P: Phase0<C>,
+    R: RngCore,
+{
+    let mut salt = [0u8; SALT_LEN];
+    rng.fill_bytes(&mut salt);
+    let mut nonce = [0u8; NONCE_LEN];
+    rng.fill_bytes(&mut nonce);
+
+    let mut header = Vec::with_capacity(HEADER_LEN);
+    header.extend_from_slice(&MAGIC);
+    header.push(CHECKPOINT_VERSION);
+    header.push(key.kind());
+    header.extend_from_slice(&salt);
+    header.extend_from_slice(&nonce);
+
+    let plaintext = bincode::serialize(state)?;
+    let aead = ChaCha20Poly1305::new(&key.derive(&salt).into());
+    let ciphertext = aead
+        .encrypt(
+            &nonce.into(),
+            Payload {
+                msg: &plaintext,
+                aad: &header,
+            },
+        )
+        .expect("aead should not fail");
+
+    header.extend_from_slice(&ciphertext);
+    Ok(header)
+}
+
+/// Decrypts and deserializes a state which was produced by [`seal`].
+///
+/// [`seal`]: fn.seal.html
+pub fn open<C, P>(checkpoint: &[u8], key: &CheckpointKey) -> Result<DKGState<C, P>, CheckpointError>
+where
+    C: Curve,
+    P: Phase0<C>,
+{
+    if checkpoint.len() < HEADER_LEN || checkpoint[..MAGIC.len()] != MAGIC {
+        return Err(CheckpointError::InvalidHeader);
+    }
+    let (header, ciphertext) = checkpoint.split_at(HEADER_LEN);
+
+    let version = header[MAGIC.len()];
+    if version != CHECKPOINT_VERSION {
+        return Err(CheckpointError::UnsupportedVersion(
+            version,
+            CHECKPOINT_VERSION,
+        ));
+    }
+    if header[MAGIC.len() + 1] != key.kind() {
+        return Err(CheckpointError::KeyMismatch);
+    }
+
+    let salt = &header[MAGIC.len() + 2..MAGIC.len() + 2 + SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    nonce.copy_from_slice(&header[HEADER_LEN - NONCE_LEN..]);
+
+    let aead = ChaCha20Poly1305::new(&key.derive(salt).into());
+    let plaintext = aead
+        .decrypt(
+            &nonce.into(),
+            Payload {
+                msg: ciphertext,
+                aad: header,
+            },
+        )
+        .map_err(|_| CheckpointError::DecryptionError)?;
+
+    Ok(bincode::deserialize(&plaintext)?)
+}
+
+/// A CheckpointStore keeps the latest sealed state of a DKG participant in a
+/// file, so that the participant can resume the protocol after a restart.
+pub struct CheckpointStore {
+    path: PathBuf,
+    key: CheckpointKey,
+}
+
+impl CheckpointStore {
+    /// Creates a store which saves checkpoints at `path`, encrypted under `key`
+    pub fn new<T: Into<PathBuf>>(path: T, key: CheckpointKey) -> Self {
+        Self {
+            path: path.into(),
+            key,
+        }
+    }
+
+    /// Seals the state and atomically replaces the previous checkpoint
+    pub fn save<C, P>(&self, state: &DKGState<C, P>) -> Result<(), CheckpointError>
+    where
+        C: Curve,
+        P: Phase0<C>,
+    {
+        let sealed = seal(state, &self.key, &mut thread_rng())?;
+
+        // write to a temporary file first so that a crash while writing does
+        // not corrupt the previous checkpoint, and sync both the file and the
+        // renaming before returning
+        let tmp = self.path.with_extension("tmp");
+        let mut file = fs::File::create(&tmp)?;
+        file.write_all(&sealed)?;
+        file.sync_all()?;
+        fs::rename(&tmp, &self.path)?;
+        sync_dir(&self.path)?;
+        Ok(())
+    }
+
+    /// Loads the latest checkpoint, if there is one
+    pub fn load<C, P>(&self) -> Result<Option<DKGState<C, P>>, CheckpointError>
+    where
+        C: Curve,
+        P: Phase0<C>,
+    {
+        match fs::read(&self.path) {
+            Ok(sealed) => Ok(Some(open(&sealed, &self.key)?)),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Removes the checkpoint, if there is one
+    pub fn clear(&self) -> Result<(), CheckpointError> {
+        match fs::remove_file(&self.path) {
+            Err(err) if err.kind() != ErrorKind::NotFound => Err(err.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+// syncs the directory containing the path, so that the entries it contains
+// are durable. Directories cannot be opened as files on Windows.
+#[cfg(unix)]
+fn sync_dir(path: &Path) -> io::Result<()> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    fs::File::open(dir)?.sync_all()
+}
+
+#[cfg(not(unix))]
+fn sync_dir(_: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives::{group::Group, joint_feldman::DKG, types::SessionId};
+    use threshold_bls::{
+        curve::bls12377::{G1Curve as BCurve, PairingCurve},
+        sig::{G1Scheme, Scheme},
+    };
+
+    fn state() -> DKGState<BCurve, DKG<BCurve>> {
+        let keypairs = (0..4)
+            .map(|_| G1Scheme::<PairingCurve>::keypair(&mut thread_rng()))
+            .collect::<Vec<_>>();
+        let group = Group::from(keypairs.iter().map(|k| k.1.clone()).collect::<Vec<_>>());
+        let session = SessionId::new(&group, 0, &[]);
+        let dkg = DKG::new(keypairs[0].0, group, session).unwrap();
+        DKGState::Phase0(dkg)
+    }
+
+    fn roundtrip(key: CheckpointKey) {
+        let state = state();
+        let sealed = seal(&state, &key, &mut thread_rng()).unwrap();
+        let opened: DKGState<BCurve, DKG<BCurve>> = open(&sealed, &key).unwrap();
+        assert_eq!(
+            bincode::serialize(&state).unwrap(),
+            bincode::serialize(&opened).unwrap()
+        );
+    }
+
+    #[test]
+    fn seal_open() {
+        roundtrip(CheckpointKey::Passphrase(b"correct horse".to_vec()));
+        roundtrip(CheckpointKey::Key([7; KEY_LEN]));
+    }
+
+    #[test]
+    fn invalid_checkpoints() {
+        let key = CheckpointKey::Key([7; KEY_LEN]);
+        let sealed = seal(&state(), &key, &mut thread_rng()).unwrap();
+
+        // wrong key
+        let res = open::<BCurve, DKG<BCurve>>(&sealed, &CheckpointKey::Key([8; KEY_LEN]));
+        assert!(matches!(res, Err(CheckpointError::DecryptionError)));
+
+        // wrong kind of key
+        let res = open::<BCurve, DKG<BCurve>>(&sealed, &CheckpointKey::Passphrase(vec![7]));
+        assert!(matches!(res, Err(CheckpointError::KeyMismatch)));
+
+        // unknown version
+        let mut other = sealed.clone();
+        other[MAGIC.len()] = CHECKPOINT_VERSION + 1;
+        let res = open::<BCurve, DKG<BCurve>>(&other, &key);
+        assert!(matches!(
+            res,
+            Err(CheckpointError::UnsupportedVersion(_, _))
+        ));
+
+        // tampered header
+        let mut other = sealed.clone();
+        other[MAGIC.len() + 2] ^= 1;
+        let res = open::<BCurve, DKG<BCurve>>(&other, &key);
+        assert!(matches!(res, Err(CheckpointError::DecryptionError)));
+
+        // truncated
+        let res = open::<BCurve, DKG<BCurve>>(&sealed[..HEADER_LEN - 1], &key);
+        assert!(matches!(res, Err(CheckpointError::InvalidHeader)));
+    }
+
+    #[test]
+    fn store() {
+        let path = std::env::temp_dir().join(format!("dkg-checkpoint-{}", thread_rng().next_u64()));
+        let store = CheckpointStore::new(&path, CheckpointKey::Key([1; KEY_LEN]));
+        assert!(store.load::<BCurve, DKG<BCurve>>().unwrap().is_none());
+
+        store.save(&state()).unwrap();
+        let loaded = store.load::<BCurve, DKG<BCurve>>().unwrap();
+        assert!(matches!(loaded, Some(DKGState::Phase0(_))));
+
+        store.clear().unwrap();
+        assert!(!path.exists());
+        store.clear().unwrap();
+    }
+}
@@ -9,12 +9,23 @@
 
 /// Board trait and implementations for publishing data from each DKG phase
 mod board;
-pub use board::BoardPublisher;
+pub use board::{
+    AuthenticatedBoard, AuthenticatedBoardError, BoardPhase, BoardPublisher, BoardReader,
+    BoardTransport,
+};
 
 /// Higher level objects for running a JF-DKG
 mod node;
 pub use node::{DKGPhase, NodeError, Phase2Result};
 
+/// Driver which runs all the phases of a DKG against a board
+mod runner;
+pub use runner::{DKGRunner, DKGState, PhaseTimeouts};
+
+/// Encrypted persistence of the DKG state between phases
+pub mod checkpoint;
+pub use checkpoint::{CheckpointError, CheckpointKey, CheckpointStore};
+
 /// Low level primitives and datatypes for implementing DKGs
 pub mod primitives;
 
@@ -1,5 +1,6 @@
 use super::{
     board::BoardPublisher,
+    checkpoint::CheckpointError,
     primitives::{
         phases::{Phase0, Phase1, Phase2, Phase3},
         types::{BundledJustification, BundledResponses, BundledShares, DKGOutput},
@@ -18,9 +19,15 @@ pub enum NodeError {
     /// Node could not publish to the board
     #[error("Could not publish to board")]
     PublisherError,
+    /// Node could not read from the board
+    #[error("Could not read from board")]
+    ReaderError,
     /// There was an internal error in the DKG
     #[error("DKG Error: {0}")]
     DKGError(#[from] DKGError),
+    /// The state of the node could not be checkpointed
+    #[error("Checkpoint Error: {0}")]
+    CheckpointError(#[from] CheckpointError),
 }
 
 /// Phase2 can either be successful or require going to Phase 3.
@@ -32,7 +39,7 @@ pub enum Phase2Result<C: Curve, P: Phase3<C>> {
     GoToPhase3(P),
 }
 
-type NodeResult<T> = std::result::Result<T, NodeError>;
+pub(crate) type NodeResult<T> = std::result::Result<T, NodeError>;
 
 /// A DKG Phase.
 #[async_trait(?Send)]
@@ -63,7 +70,7 @@ where
         C: 'async_trait,
     {
         let (next, shares) = self.encrypt_shares(rng)?;
-        if let Some(sh) = shares {
+        for sh in shares {
             board
                 .publish_shares(sh)
                 .await
@@ -130,7 +137,7 @@ where
                         // Nodes may just see that justifications are needed but they
                         // don't have to create any, since no  complaint have been filed
                         // against their deal.
-                        if let Some(justifications) = justifications {
+                        for justifications in justifications {
                             board
                                 .publish_justifications(justifications)
                                 .await
@@ -174,6 +181,7 @@ mod tests {
         primitives::{
             group::{Group, Node},
             joint_feldman, resharing,
+            types::SessionId,
         },
         test_helpers::InMemoryBoard,
     };
@@ -282,15 +290,20 @@ mod tests {
             .map(|(i, (_, public))| Node::<C>::new(i as Idx, public.clone()))
             .collect::<Vec<_>>();
         let new_group = Group::new(nodes, new_t).unwrap();
+        let session = SessionId::new(&new_group, 1, &[]);
 
         let mut phase0s = Vec::new();
 
         if removed_nodes_participate {
             for output in removed_outputs.into_iter() {
                 let keypair = S::keypair(rng);
-                let phase0 =
-                    resharing::RDKG::new_from_share(keypair.0, output.clone(), new_group.clone())
-                        .unwrap();
+                let phase0 = resharing::RDKG::new_from_share(
+                    keypair.0,
+                    output.clone(),
+                    new_group.clone(),
+                    session,
+                )
+                .unwrap();
                 phase0s.push(phase0);
             }
         }
@@ -301,6 +314,7 @@ mod tests {
                     keypairs[i].0.clone(),
                     dkg_outputs[i].clone(),
                     new_group.clone(),
+                    session,
                 )
                 .unwrap() // infallible
             } else {
@@ -309,6 +323,7 @@ mod tests {
                     old_group.clone(),   // people who have registered before
                     public_poly.clone(), // the previous public key
                     new_group.clone(),   // the new group
+                    session,
                 )
                 .unwrap()
             };
@@ -352,7 +367,7 @@ mod tests {
         // generates a partial sig with each share from the dkg
         let partial_sigs = outputs
             .iter()
-            .map(|output| S::sign_blind_partial(&output.share, &blinded_msg[..]).unwrap())
+            .map(|output| S::sign_blind_partial(&output.shares[0], &blinded_msg[..]).unwrap())
             .collect::<Vec<_>>();
 
         // aggregates them
@@ -460,7 +475,7 @@ mod tests {
         // will get `honest`
         for err in &errs[..bad] {
             match err {
-                DKGError::NotEnoughValidShares(got, required) => {
+                DKGError::NotEnoughValidShares(got, required, _) => {
                     assert_eq!(*got, honest);
                     assert_eq!(*required, t);
                 }
@@ -472,7 +487,7 @@ mod tests {
         // (which were not enough)
         for err in &errs[bad..] {
             match err {
-                DKGError::NotEnoughValidShares(got, required) => {
+                DKGError::NotEnoughValidShares(got, required, _) => {
                     assert_eq!(*got, honest - 1);
                     assert_eq!(*required, t);
                 }
@@ -585,11 +600,14 @@ mod tests {
         // This is setup phase during which publickeys and indexes must be exchanged
         // across participants
         let group = Group::new(nodes, t).unwrap();
+        let session = SessionId::new(&group, 0, &[]);
 
         // Create the Phase 0 for each participant
         let phase0s = keypairs
             .iter()
-            .map(|(private, _)| joint_feldman::DKG::new(private.clone(), group.clone()).unwrap())
+            .map(|(private, _)| {
+                joint_feldman::DKG::new(private.clone(), group.clone(), session).unwrap()
+            })
             .collect::<Vec<_>>();
 
         // Create the board
@@ -0,0 +1,245 @@
+use crate::primitives::{group::Group, types::*};
+
+use rand_core::RngCore;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use threshold_bls::{
+    group::{Curve, Element, Scalar},
+    poly::Idx,
+};
+
+/// A domain separator
+const DOMAIN: &[u8] = b"dkg-core schnorr";
+
+/// Bundles which are published to the board by a single participant
+pub trait Bundle: Serialize {
+    /// Domain separator for signatures over this kind of bundle, so that a
+    /// signature cannot be replayed over another kind of bundle.
+    const DOMAIN: &'static [u8];
+
+    /// Returns the index of the participant which created the bundle
+    fn sender(&self) -> Idx;
+
+    /// Returns the session in which the bundle was created
+    fn session(&self) -> &SessionId;
+}
+
+impl<C: Curve> Bundle for BundledShares<C> {
+    const DOMAIN: &'static [u8] = b"shares";
+
+    fn sender(&self) -> Idx {
+        self.dealer_idx
+    }
+
+    fn session(&self) -> &SessionId {
+        &self.session
+    }
+}
+
+impl Bundle for BundledResponses {
+    const DOMAIN: &'static [u8] = b"responses";
+
+    fn sender(&self) -> Idx {
+        self.share_idx
+    }
+
+    fn session(&self) -> &SessionId {
+        &self.session
+    }
+}
+
+impl<C: Curve> Bundle for BundledJustification<C> {
+    const DOMAIN: &'static [u8] = b"justifications";
+
+    fn sender(&self) -> Idx {
+        self.dealer_idx
+    }
+
+    fn session(&self) -> &SessionId {
+        &self.session
+    }
+}
+
+/// A Schnorr signature produced with the long-term key of a `Node`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(bound = "C::Scalar: DeserializeOwned")]
+pub struct Signature<C: Curve> {
+    /// The commitment to the signing nonce
+    commitment: C::Point,
+    /// The response to the challenge
+    response: C::Scalar,
+}
+
+impl<C: Curve> Signature<C> {
+    /// Signs the message with the provided private key
+    pub fn sign<R: RngCore>(private: &C::Scalar, msg: &[u8], rng: &mut R) -> Self {
+        let mut public = C::point();
+        public.mul(private);
+
+        let nonce = C::Scalar::rand(rng);
+        let mut commitment = C::point();
+        commitment.mul(&nonce);
+
+        // s = k + e * x
+        let mut response = challenge::<C>(&commitment, &public, msg);
+        response.mul(private);
+        response.add(&nonce);
+
+        Self {
+            commitment,
+            response,
+        }
+    }
+
+    /// Verifies the signature on the message against the provided public key
+    pub fn verify(&self, public: &C::Point, msg: &[u8]) -> bool {
+        // g * s == R + P * e
+        let mut left = C::point();
+        left.mul(&self.response);
+
+        let mut right = public.clone();
+        right.mul(&challenge::<C>(&self.commitment, public, msg));
+        right.add(&self.commitment);
+
+        left == right
+    }
+}
+
+// hashes the commitment, public key and message to a scalar
+fn challenge<C: Curve>(commitment: &C::Point, public: &C::Point, msg: &[u8]) -> C::Scalar {
+    let commitment = bincode::serialize(commitment).expect("could not serialize element");
+    let public = bincode::serialize(public).expect("could not serialize element");
+    // try and increment until the hash maps to a scalar
+    (0u32..)
+        .find_map(|counter| {
+            let hash = Sha256::new()
+                .chain(DOMAIN)
+                .chain(counter.to_le_bytes())
+                .chain(&commitment)
+                .chain(&public)
+                .chain(msg)
+                .result();
+            C::Scalar::from_random_bytes(&hash)
+        })
+        .expect("a scalar should be found")
+}
+
+/// Signed is an envelope binding a bundle to the long-term key of its sender,
+/// so that bundles can be relayed over transports which do not authenticate
+/// their messages.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "T: Serialize",
+    deserialize = "T: DeserializeOwned, C::Scalar: DeserializeOwned"
+))]
+pub struct Signed<C: Curve, T> {
+    /// The signed bundle
+    pub bundle: T,
+    /// The signature of the bundle's sender
+    pub signature: Signature<C>,
+}
+
+impl<C: Curve, T: Bundle> Signed<C, T> {
+    /// Signs the bundle with the private key of its sender
+    pub fn sign<R: RngCore>(bundle: T, private: &C::Scalar, rng: &mut R) -> Self {
+        let signature = Signature::sign(private, &signed_message(&bundle), rng);
+        Self { bundle, signature }
+    }
+
+    /// Returns true if the bundle was signed by the key which is registered
+    /// in the group for the index of the bundle's sender.
+    pub fn verify(&self, group: &Group<C>) -> bool {
+        group
+            .node(self.bundle.sender())
+            .map(|node| {
+                self.signature
+                    .verify(node.key(), &signed_message(&self.bundle))
+            })
+            .unwrap_or(false)
+    }
+}
+
+fn signed_message<T: Bundle>(bundle: &T) -> Vec<u8> {
+    let mut msg = T::DOMAIN.to_vec();
+    bincode::serialize_into(&mut msg, bundle).expect("could not serialize bundle");
+    msg
+}
+
+/// Returns the bundles which are correctly signed by their sender in the
+/// group, dropping all the others.
+pub fn verify_bundles<C: Curve, T: Bundle>(group: &Group<C>, bundles: Vec<Signed<C, T>>) -> Vec<T> {
+    bundles
+        .into_iter()
+        .filter(|signed| signed.verify(group))
+        .map(|signed| signed.bundle)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives::{group::Node, status::Status};
+    use rand::thread_rng;
+    use threshold_bls::curve::bls12377::G1Curve as BCurve;
+
+    fn keypair() -> (<BCurve as Curve>::Scalar, <BCurve as Curve>::Point) {
+        let private = <BCurve as Curve>::Scalar::rand(&mut thread_rng());
+        let mut public = BCurve::point();
+        public.mul(&private);
+        (private, public)
+    }
+
+    #[test]
+    fn schnorr() {
+        let (private, public) = keypair();
+        let (_, other) = keypair();
+        let msg = vec![1, 9, 6, 9];
+        let sig = Signature::<BCurve>::sign(&private, &msg, &mut thread_rng());
+        assert!(sig.verify(&public, &msg));
+        assert!(!sig.verify(&other, &msg));
+        assert!(!sig.verify(&public, &[1, 9, 6, 8]));
+    }
+
+    #[test]
+    fn signed_bundles() {
+        let keypairs = (0..3).map(|_| keypair()).collect::<Vec<_>>();
+        let group = Group::<BCurve>::new(
+            keypairs
+                .iter()
+                .enumerate()
+                .map(|(i, (_, public))| Node::new(i as Idx, public.clone()))
+                .collect(),
+            2,
+        )
+        .unwrap();
+        let session = SessionId::new(&group, 0, &[]);
+        let bundle = |share_idx| BundledResponses {
+            session,
+            share_idx,
+            responses: vec![Response {
+                dealer_idx: 0,
+                status: Status::Complaint,
+            }],
+        };
+
+        let valid = Signed::sign(bundle(1), &keypairs[1].0, &mut thread_rng());
+        assert!(valid.verify(&group));
+
+        // signed with the key of another node
+        let forged = Signed::sign(bundle(1), &keypairs[2].0, &mut thread_rng());
+        assert!(!forged.verify(&group));
+
+        // sender not in the group
+        let unknown = Signed::sign(bundle(5), &keypairs[2].0, &mut thread_rng());
+        assert!(!unknown.verify(&group));
+
+        // modified after signing
+        let mut tampered = valid.clone();
+        tampered.bundle.responses[0].status = Status::Success;
+        assert!(!tampered.verify(&group));
+
+        let bundles = verify_bundles(&group, vec![valid, forged, unknown, tampered]);
+        assert_eq!(bundles.len(), 1);
+        assert_eq!(bundles[0].share_idx, 1);
+    }
+}
@@ -0,0 +1,382 @@
+//! Certifies that the members of a group finished a DKG, a resharing or a
+//! refresh with the same output.
+//!
+//! Each member hashes its qualified group and distributed public polynomial
+//! into a `Fingerprint`, and signs it with its new shares in an
+//! `Attestation`. Once a threshold of shares attested to the same fingerprint,
+//! their partial signatures are aggregated into a `CompletionCertificate`,
+//! which anyone can verify under the new distributed public key. It proves
+//! both that the members agree on the output and that their shares work, so
+//! a deployment should only switch to the new key once it holds a certificate.
+use crate::primitives::{
+    errors::CertificateError,
+    group::{Group, Node},
+    types::DKGOutput,
+    DKGResult,
+};
+
+use bincode::Options;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use threshold_bls::{
+    group::Curve,
+    poly::{Eval, Idx, PublicPoly},
+    sig::{Partial, SignatureScheme, ThresholdScheme},
+};
+
+const FINGERPRINT_DOMAIN: &[u8] = b"dkg-core fingerprint";
+const CERTIFICATE_DOMAIN: &[u8] = b"dkg-core certificate";
+
+/// Fingerprint is the canonical hash of the output of a DKG, i.e. of its
+/// qualified group and of its distributed public polynomial. It does not
+/// depend on the order of the nodes in the group.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Fingerprint([u8; 32]);
+
+impl Fingerprint {
+    /// Hashes the group (the index, share indices and public key of each node
+    /// sorted by index, and the threshold) and the public polynomial.
+    pub fn new<C: Curve>(group: &Group<C>, public: &PublicPoly<C>) -> Self {
+        let mut nodes = group.nodes.iter().collect::<Vec<_>>();
+        nodes.sort_by_key(|n| n.id());
+
+        let mut hasher = Sha256::new()
+            .chain(FINGERPRINT_DOMAIN)
+            .chain((nodes.len() as u64).to_le_bytes());
+        for node in nodes {
+            let key = bincode::serialize(node.key()).expect("could not serialize key");
+            hasher = hasher
+                .chain(node.id().to_le_bytes())
+                .chain(node.shares().start.to_le_bytes())
+                .chain(node.shares().end.to_le_bytes())
+                .chain(&key);
+        }
+        let public = bincode::serialize(public).expect("could not serialize polynomial");
+        let hash = hasher
+            .chain((group.threshold as u64).to_le_bytes())
+            .chain(&public)
+            .result();
+
+        let mut fingerprint = [0u8; 32];
+        fingerprint.copy_from_slice(&hash);
+        Self(fingerprint)
+    }
+
+    /// Returns the bytes of the fingerprint
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    // the message signed by the members, which is domain separated so that
+    // the group key can not be tricked into certifying another message
+    fn message(&self) -> Vec<u8> {
+        [CERTIFICATE_DOMAIN, &self.0].concat()
+    }
+}
+
+/// Attestation is published by a member at the end of the protocol. It
+/// contains the fingerprint of the member's output, signed with each of its
+/// shares.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Attestation {
+    /// The index of the member in the qualified group
+    pub signer_idx: Idx,
+    /// The fingerprint of the member's output
+    pub fingerprint: Fingerprint,
+    /// One partial signature of the fingerprint for each share of the member
+    pub partials: Vec<Partial>,
+}
+
+impl<C: Curve> DKGOutput<C> {
+    /// Returns the fingerprint of the qualified group and public polynomial
+    pub fn fingerprint(&self) -> Fingerprint {
+        Fingerprint::new(&self.qual, &self.public)
+    }
+
+    /// Signs the fingerprint of the output with each of the shares
+    pub fn attest<S>(&self) -> DKGResult<Attestation>
+    where
+        S: ThresholdScheme<Private = C::Scalar>,
+    {
+        let indices = self.shares.iter().map(|s| s.index);
+        let signer = self
+            .qual
+            .nodes
+            .iter()
+            .find(|n| n.weight() > 0 && n.shares().eq(indices.clone()))
+            .ok_or(CertificateError::NotQualified)?;
+
+        let fingerprint = self.fingerprint();
+        let msg = fingerprint.message();
+        let partials = self
+            .shares
+            .iter()
+            .map(|share| S::partial_sign(share, &msg))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| CertificateError::SigningFailed(e.to_string()))?;
+
+        Ok(Attestation {
+            signer_idx: signer.id(),
+            fingerprint,
+            partials,
+        })
+    }
+}
+
+/// Checks that the attestation is for the fingerprint of the given output, and
+/// that it contains a valid partial signature for each share of its signer.
+pub fn verify_attestation<C, S>(
+    group: &Group<C>,
+    public: &PublicPoly<C>,
+    attestation: &Attestation,
+) -> DKGResult<()>
+where
+    C: Curve,
+    S: ThresholdScheme<Public = C::Point>,
+{
+    let signer = attestation.signer_idx;
+    let node = group
+        .node(signer)
+        .ok_or(CertificateError::UnknownSigner(signer))?;
+    let fingerprint = Fingerprint::new(group, public);
+    if attestation.fingerprint != fingerprint {
+        return Err(CertificateError::FingerprintMismatch(signer).into());
+    }
+
+    let msg = fingerprint.message();
+    let valid = attestation.partials.len() == node.weight()
+        && owns_partials(node, &attestation.partials)
+        && attestation
+            .partials
+            .iter()
+            .all(|p| S::partial_verify(public, &msg, p).is_ok());
+    if !valid {
+        return Err(CertificateError::InvalidPartial(signer).into());
+    }
+    Ok(())
+}
+
+// checks that the partials are for distinct share indices of the node, so
+// that a member can not attest with the partials of another member. Trailing
+// bytes are rejected like `partial_verify` does.
+fn owns_partials<C: Curve>(node: &Node<C>, partials: &[Partial]) -> bool {
+    let mut seen = HashSet::new();
+    partials.iter().all(|p| {
+        bincode::options()
+            .with_fixint_encoding()
+            .deserialize::<Eval<Vec<u8>>>(p)
+            .map(|eval| node.shares().contains(&eval.index) && seen.insert(eval.index))
+            .unwrap_or(false)
+    })
+}
+
+/// CompletionCertificate is the signature of the fingerprint of a DKG output
+/// under its distributed public key, aggregated from the attestations of a
+/// threshold of shares.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CompletionCertificate {
+    /// The fingerprint of the certified output
+    pub fingerprint: Fingerprint,
+    /// The members whose attestations were aggregated
+    pub signers: Vec<Idx>,
+    /// The threshold signature of the fingerprint
+    pub signature: Vec<u8>,
+}
+
+impl CompletionCertificate {
+    /// Aggregates the valid attestations for the given output into a
+    /// certificate. Invalid attestations, and the attestations of a member
+    /// after its first valid one, are ignored. Returns an error if the members
+    /// with a valid attestation hold less than a threshold of shares.
+    pub fn aggregate<C, S>(
+        group: &Group<C>,
+        public: &PublicPoly<C>,
+        attestations: &[Attestation],
+    ) -> DKGResult<Self>
+    where
+        C: Curve,
+        S: ThresholdScheme<Public = C::Point> + SignatureScheme,
+    {
+        let mut signers = Vec::new();
+        let mut partials = Vec::new();
+        for attestation in attestations {
+            if signers.contains(&attestation.signer_idx)
+                || verify_attestation::<C, S>(group, public, attestation).is_err()
+            {
+                continue;
+            }
+            signers.push(attestation.signer_idx);
+            partials.extend(attestation.partials.iter().cloned());
+        }
+
+        if partials.len() < group.threshold {
+            return Err(
+                CertificateError::NotEnoughAttestations(partials.len(), group.threshold).into(),
+            );
+        }
+
+        let signature = <S as ThresholdScheme>::aggregate(group.threshold, &partials)
+            .map_err(|e| CertificateError::SigningFailed(e.to_string()))?;
+        signers.sort_unstable();
+        let certificate = Self {
+            fingerprint: Fingerprint::new(group, public),
+            signers,
+            signature,
+        };
+        certificate.verify::<C, S>(group, public)?;
+        Ok(certificate)
+    }
+
+    /// Checks that the certificate is for the given output, and that its
+    /// signature is valid under the distributed public key.
+    pub fn verify<C, S>(&self, group: &Group<C>, public: &PublicPoly<C>) -> DKGResult<()>
+    where
+        C: Curve,
+        S: SignatureScheme<Public = C::Point>,
+    {
+        let fingerprint = Fingerprint::new(group, public);
+        if self.fingerprint != fingerprint
+            || S::verify(public.public_key(), &fingerprint.message(), &self.signature).is_err()
+        {
+            return Err(CertificateError::InvalidCertificate.into());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives::{
+        common::tests::{full_dkg, setup_group, setup_weighted_group},
+        default_threshold,
+        joint_feldman::DKG,
+        types::SessionId,
+        DKGError,
+    };
+    use threshold_bls::{
+        curve::bls12377::{G1Curve as BCurve, PairingCurve as PCurve},
+        sig::G1Scheme,
+    };
+
+    type S = G1Scheme<PCurve>;
+
+    fn outputs(
+        group: Group<BCurve>,
+        privs: Vec<<BCurve as Curve>::Scalar>,
+    ) -> Vec<DKGOutput<BCurve>> {
+        let session = SessionId::new(&group, 0, &[]);
+        let dkgs = privs
+            .into_iter()
+            .map(|p| DKG::new(p, group.clone(), session).unwrap())
+            .collect::<Vec<_>>();
+        full_dkg(group.threshold, dkgs).0
+    }
+
+    #[test]
+    fn certify_output() {
+        let n = 5;
+        let (privs, group) = setup_group::<BCurve>(n, default_threshold(n));
+        let outputs = outputs(group, privs);
+        let (qual, public) = (&outputs[0].qual, &outputs[0].public);
+
+        let attestations = outputs
+            .iter()
+            .map(|o| o.attest::<S>().unwrap())
+            .collect::<Vec<_>>();
+        for attestation in &attestations {
+            verify_attestation::<_, S>(qual, public, attestation).unwrap();
+        }
+
+        let certificate =
+            CompletionCertificate::aggregate::<_, S>(qual, public, &attestations[1..]).unwrap();
+        assert_eq!(certificate.signers, vec![1, 2, 3, 4]);
+        certificate.verify::<_, S>(qual, public).unwrap();
+
+        // the fingerprint does not depend on the order of the nodes
+        let mut reordered = qual.clone();
+        reordered.nodes.reverse();
+        assert_eq!(
+            Fingerprint::new(&reordered, public),
+            certificate.fingerprint
+        );
+    }
+
+    #[test]
+    fn reject_other_output() {
+        let (n, thr) = (5, 3);
+        let (privs, group) = setup_group::<BCurve>(n, thr);
+        let outputs = outputs(group, privs);
+        let (qual, public) = (&outputs[0].qual, &outputs[0].public);
+        let mut attestations = outputs
+            .iter()
+            .map(|o| o.attest::<S>().unwrap())
+            .collect::<Vec<_>>();
+
+        // a member which finished with another group
+        let mut other = outputs[1].clone();
+        other.qual.threshold = thr + 1;
+        attestations[1] = other.attest::<S>().unwrap();
+        let err = verify_attestation::<_, S>(qual, public, &attestations[1]).unwrap_err();
+        assert!(matches!(
+            err,
+            DKGError::CertificateError(CertificateError::FingerprintMismatch(1))
+        ));
+
+        // a member which copies the partials of another one
+        attestations[2].partials = attestations[3].partials.clone();
+        let err = verify_attestation::<_, S>(qual, public, &attestations[2]).unwrap_err();
+        assert!(matches!(
+            err,
+            DKGError::CertificateError(CertificateError::InvalidPartial(2))
+        ));
+
+        // a member which appends the order of a derivative to its partial
+        let mut ordered = attestations[4].clone();
+        ordered.partials[0].extend_from_slice(&(thr as u32).to_le_bytes());
+        let err = verify_attestation::<_, S>(qual, public, &ordered).unwrap_err();
+        assert!(matches!(
+            err,
+            DKGError::CertificateError(CertificateError::InvalidPartial(4))
+        ));
+
+        // a threshold of shares is still needed
+        let err =
+            CompletionCertificate::aggregate::<_, S>(qual, public, &attestations[..4]).unwrap_err();
+        assert!(matches!(
+            err,
+            DKGError::CertificateError(CertificateError::NotEnoughAttestations(2, 3))
+        ));
+        let certificate =
+            CompletionCertificate::aggregate::<_, S>(qual, public, &attestations).unwrap();
+        assert_eq!(certificate.signers, vec![0, 3, 4]);
+
+        // the certificate is only valid for its output
+        let mut other_public = public.clone();
+        other_public.add(public);
+        let err = certificate.verify::<_, S>(qual, &other_public).unwrap_err();
+        assert!(matches!(
+            err,
+            DKGError::CertificateError(CertificateError::InvalidCertificate)
+        ));
+    }
+
+    #[test]
+    fn certify_weighted_output() {
+        let thr = default_threshold(6);
+        let (privs, group) = setup_weighted_group::<BCurve>(&[1, 2, 3], thr);
+        let outputs = outputs(group, privs);
+        let (qual, public) = (&outputs[0].qual, &outputs[0].public);
+        let attestations = outputs
+            .iter()
+            .map(|o| o.attest::<S>().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(attestations[2].partials.len(), 3);
+
+        // the nodes of weight 2 and 3 hold a threshold of shares
+        let certificate =
+            CompletionCertificate::aggregate::<_, S>(qual, public, &attestations[1..]).unwrap();
+        certificate.verify::<_, S>(qual, public).unwrap();
+    }
+}
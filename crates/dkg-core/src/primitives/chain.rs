@@ -0,0 +1,459 @@
+//! Verifies a history of resharings and refreshes, so that a light client (e.g. a bridge
+//! contract) which only trusts the distributed public key of the genesis DKG
+//! can trust the current committee.
+//!
+//! An `EpochChain` lists for each epoch the qualified group, its public
+//! polynomial, the transcript of the public polynomials of the dealers which
+//! produced it, and the `CompletionCertificate` of the epoch. The verifier
+//! checks that every dealer of a resharing reshared its share of the previous
+//! epoch, that their polynomials interpolate to the new public polynomial, so
+//! that it has the same free coefficient as the previous one, and that a
+//! threshold of the new shares certified the epoch. A refresh keeps the group
+//! and its threshold: every dealer of the refresh shared zero, and the sum of
+//! their polynomials is added to the previous public polynomial.
+//!
+//! The certificates are needed since anyone can compute commitments with the
+//! right free coefficients from the previous public polynomial: only a
+//! signature under the distributed key shows that the shares of the new group
+//! actually reconstruct it.
+use crate::primitives::{
+    certificate::CompletionCertificate,
+    errors::ChainError,
+    group::Group,
+    resharing::{check_public_resharing, recover_public},
+    types::DKGOutput,
+    DKGResult,
+};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::BTreeMap;
+use threshold_bls::{
+    group::{Curve, Element},
+    poly::{Idx, PublicPoly},
+    sig::SignatureScheme,
+};
+
+/// Epoch is the output of the genesis DKG, of a resharing or of a refresh, as
+/// seen by an outsider.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "C::Scalar: DeserializeOwned")]
+pub struct Epoch<C: Curve> {
+    /// The qualified group holding the shares during the epoch
+    pub group: Group<C>,
+    /// The distributed public polynomial of the epoch
+    pub public: PublicPoly<C>,
+    /// The public polynomials of the dealers which produced the epoch
+    pub transcript: BTreeMap<Idx, PublicPoly<C>>,
+    /// The certificate of the epoch, signed by a threshold of its shares
+    pub certificate: CompletionCertificate,
+    /// Whether the epoch is a refresh of the previous one rather than a
+    /// resharing
+    pub refresh: bool,
+}
+
+impl<C: Curve> Epoch<C> {
+    /// Creates the epoch from the output of a member and the certificate
+    /// aggregated from the attestations of the group.
+    pub fn new(output: &DKGOutput<C>, certificate: CompletionCertificate) -> Self {
+        Self {
+            group: output.qual.clone(),
+            public: output.public.clone(),
+            transcript: output.transcript.clone(),
+            certificate,
+            refresh: false,
+        }
+    }
+
+    /// Creates the epoch from the output of a member of a refresh and the
+    /// certificate aggregated from the attestations of the group.
+    pub fn refreshed(output: &DKGOutput<C>, certificate: CompletionCertificate) -> Self {
+        Self {
+            refresh: true,
+            ..Self::new(output, certificate)
+        }
+    }
+}
+
+/// EpochChain is the ordered list of epochs, starting from the genesis DKG,
+/// each of the following ones being a resharing or a refresh of the previous
+/// one.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "C::Scalar: DeserializeOwned")]
+pub struct EpochChain<C: Curve> {
+    epochs: Vec<Epoch<C>>,
+}
+
+impl<C: Curve> EpochChain<C> {
+    /// Creates a chain starting with the genesis epoch
+    pub fn new(genesis: Epoch<C>) -> Self {
+        Self {
+            epochs: vec![genesis],
+        }
+    }
+
+    /// Appends the epoch of a resharing or a refresh of the current epoch. The
+    /// epoch is only checked by `verify`.
+    pub fn push(&mut self, epoch: Epoch<C>) {
+        self.epochs.push(epoch);
+    }
+
+    /// Returns the epochs, starting from the genesis one
+    pub fn epochs(&self) -> &[Epoch<C>] {
+        &self.epochs
+    }
+
+    /// Verifies the whole chain from the genesis distributed public key, and
+    /// returns the current epoch. The transitions are checked in order, so the
+    /// error refers to the first invalid epoch by its position in the chain.
+    pub fn verify<S>(&self, genesis_key: &C::Point) -> DKGResult<&Epoch<C>>
+    where
+        S: SignatureScheme<Public = C::Point>,
+    {
+        let genesis = self.epochs.first().ok_or(ChainError::EmptyChain)?;
+        if genesis.public.public_key() != genesis_key {
+            return Err(ChainError::GenesisKeyMismatch.into());
+        }
+        // the genesis public polynomial is the sum of the dealers' ones
+        let mut sum = PublicPoly::<C>::zero();
+        genesis.transcript.values().for_each(|p| sum.add(p));
+        if sum != genesis.public {
+            return Err(ChainError::InvalidPublicPoly(0).into());
+        }
+        verify_group(0, genesis)?;
+        verify_epoch::<C, S>(0, genesis)?;
+
+        for (i, pair) in self.epochs.windows(2).enumerate() {
+            // the transitions rely on the threshold of the group, so it is
+            // validated before them
+            verify_group(i + 1, &pair[1])?;
+            if pair[1].refresh {
+                verify_refresh(i + 1, &pair[0], &pair[1])?;
+            } else {
+                verify_resharing(i + 1, &pair[0], &pair[1])?;
+            }
+            verify_epoch::<C, S>(i + 1, &pair[1])?;
+        }
+
+        Ok(self.epochs.last().expect("the chain is not empty"))
+    }
+}
+
+// checks the nodes and the threshold of the group of the epoch
+fn verify_group<C: Curve>(i: usize, epoch: &Epoch<C>) -> DKGResult<()> {
+    epoch
+        .group
+        .validate()
+        .map_err(|_| ChainError::InvalidGroup(i))?;
+    Ok(())
+}
+
+// checks the public polynomial and the certificate of the epoch, whose group
+// has been validated
+fn verify_epoch<C, S>(i: usize, epoch: &Epoch<C>) -> DKGResult<()>
+where
+    C: Curve,
+    S: SignatureScheme<Public = C::Point>,
+{
+    if epoch.public.degree() != epoch.group.threshold - 1 {
+        return Err(ChainError::InvalidPublicPoly(i).into());
+    }
+    epoch
+        .certificate
+        .verify::<C, S>(&epoch.group, &epoch.public)
+        .map_err(|_| ChainError::InvalidCertificate(i))?;
+    Ok(())
+}
+
+// checks that the epoch is a resharing of the previous one
+fn verify_resharing<C: Curve>(i: usize, prev: &Epoch<C>, epoch: &Epoch<C>) -> DKGResult<()> {
+    // the dealers are indexed by the share indices of the previous group
+    let dealers = prev.group.expand();
+    let degree = epoch.group.threshold - 1;
+    for (idx, poly) in &epoch.transcript {
+        let valid = dealers.contains_index(*idx)
+            && poly.degree() == degree
+            && check_public_resharing::<C>(*idx, poly, &prev.public);
+        if !valid {
+            return Err(ChainError::InvalidTranscript(i, *idx).into());
+        }
+    }
+
+    let thr = prev.group.threshold;
+    if epoch.transcript.len() < thr {
+        return Err(ChainError::NotEnoughDealers(i, epoch.transcript.len(), thr).into());
+    }
+    let public = recover_public::<C>(thr, epoch.group.threshold, &epoch.transcript)
+        .map_err(|_| ChainError::InvalidPublicPoly(i))?;
+    if public != epoch.public || public.public_key() != prev.public.public_key() {
+        return Err(ChainError::InvalidPublicPoly(i).into());
+    }
+    Ok(())
+}
+
+// checks that the epoch is a refresh of the previous one
+fn verify_refresh<C: Curve>(i: usize, prev: &Epoch<C>, epoch: &Epoch<C>) -> DKGResult<()> {
+    // the refreshed group is the one of the qualified dealers, with the same
+    // nodes and threshold as before
+    let thr = prev.group.threshold;
+    let same_group = epoch.group.threshold == thr
+        && epoch
+            .group
+            .nodes
+            .iter()
+            .all(|node| match prev.group.node(node.id()) {
+                Some(prev) => prev.key() == node.key() && prev.shares() == node.shares(),
+                None => false,
+            });
+    if !same_group {
+        return Err(ChainError::InvalidGroup(i).into());
+    }
+
+    // the dealers are the nodes of the group, and each of them shares zero
+    for (idx, poly) in &epoch.transcript {
+        let valid = prev.group.contains_index(*idx)
+            && poly.degree() == thr - 1
+            && poly.public_key() == &C::Point::zero();
+        if !valid {
+            return Err(ChainError::InvalidTranscript(i, *idx).into());
+        }
+    }
+    if epoch.transcript.len() < thr {
+        return Err(ChainError::NotEnoughDealers(i, epoch.transcript.len(), thr).into());
+    }
+
+    let mut public = prev.public.clone();
+    epoch.transcript.values().for_each(|p| public.add(p));
+    if public != epoch.public {
+        return Err(ChainError::InvalidPublicPoly(i).into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives::{
+        common::tests::{full_dkg, setup_group},
+        joint_feldman::DKG,
+        phases::{Phase0, Phase1, Phase2},
+        refresh::Refresh,
+        resharing::RDKG,
+        types::SessionId,
+        DKGError,
+    };
+    use rand::thread_rng;
+    use threshold_bls::{
+        curve::bls12377::{G1Curve as BCurve, PairingCurve as PCurve},
+        sig::G1Scheme,
+    };
+
+    type S = G1Scheme<PCurve>;
+    type Scalar = <BCurve as Curve>::Scalar;
+
+    fn certificate(outputs: &[DKGOutput<BCurve>]) -> CompletionCertificate {
+        let attestations = outputs
+            .iter()
+            .map(|o| o.attest::<S>().unwrap())
+            .collect::<Vec<_>>();
+        let out = &outputs[0];
+        CompletionCertificate::aggregate::<_, S>(&out.qual, &out.public, &attestations).unwrap()
+    }
+
+    fn certify(outputs: &[DKGOutput<BCurve>]) -> Epoch<BCurve> {
+        Epoch::new(&outputs[0], certificate(outputs))
+    }
+
+    // reshares the outputs to the new group, whose members are dealers too
+    fn reshare(
+        privs: &[Scalar],
+        outputs: Vec<DKGOutput<BCurve>>,
+        new_group: &Group<BCurve>,
+        epoch: u64,
+    ) -> Vec<DKGOutput<BCurve>> {
+        let session = SessionId::new(new_group, epoch, &[]);
+        let rdkgs = privs
+            .iter()
+            .zip(outputs)
+            .map(|(p, out)| RDKG::new_from_share(*p, out, new_group.clone(), session).unwrap())
+            .collect::<Vec<_>>();
+        let (rdkgs, bundles): (Vec<_>, Vec<_>) = rdkgs
+            .into_iter()
+            .map(|dkg| dkg.encrypt_shares(&mut thread_rng()).unwrap())
+            .unzip();
+        let bundles = bundles.concat();
+        rdkgs
+            .into_iter()
+            .map(|dkg| dkg.process_shares(&bundles, false).unwrap().0)
+            .filter_map(|dkg| dkg.process_responses(&[]).ok())
+            .collect()
+    }
+
+    fn setup_chain() -> (EpochChain<BCurve>, Vec<DKGOutput<BCurve>>) {
+        let n = 5;
+        let (privs, group) = setup_group::<BCurve>(n, 3);
+        let session = SessionId::new(&group, 0, &[]);
+        let dkgs = privs
+            .iter()
+            .map(|p| DKG::new(*p, group.clone(), session).unwrap())
+            .collect::<Vec<_>>();
+        let (outputs, _) = full_dkg(group.threshold, dkgs);
+        let mut chain = EpochChain::new(certify(&outputs));
+
+        // the last node leaves, then the threshold increases
+        let mut group = group;
+        group.nodes.pop();
+        let outputs = reshare(&privs, outputs, &group, 1);
+        chain.push(certify(&outputs));
+        group.threshold = 4;
+        let outputs = reshare(&privs[..4], outputs, &group, 2);
+        chain.push(certify(&outputs));
+
+        // the shares are then refreshed
+        let session = SessionId::new(&group, 3, &[]);
+        let refreshes = privs
+            .iter()
+            .zip(outputs)
+            .map(|(p, out)| Refresh::new_from_share(*p, out, session).unwrap())
+            .collect::<Vec<_>>();
+        let (outputs, _) = full_dkg(group.threshold, refreshes);
+        chain.push(Epoch::refreshed(&outputs[0], certificate(&outputs)));
+        (chain, outputs)
+    }
+
+    #[test]
+    fn verify_chain() {
+        let (chain, outputs) = setup_chain();
+        let genesis_key = chain.epochs()[0].public.public_key().clone();
+        let current = chain.verify::<S>(&genesis_key).unwrap();
+        assert_eq!(current.group, outputs[0].qual);
+        assert_eq!(current.public, outputs[0].public);
+        assert_eq!(current.public.degree(), 3);
+        assert!(current.refresh);
+        assert_ne!(current.public, chain.epochs()[2].public);
+
+        let err = chain
+            .verify::<S>(&chain.epochs()[1].public.get(1))
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            DKGError::ChainError(ChainError::GenesisKeyMismatch)
+        ));
+    }
+
+    #[test]
+    fn invalid_transcript() {
+        let (chain, _) = setup_chain();
+        let genesis_key = chain.epochs()[0].public.public_key().clone();
+
+        // a dealer which did not reshare its share
+        let mut tampered = chain.clone();
+        let poly = tampered.epochs[1].transcript.get_mut(&2).unwrap();
+        poly.add(&chain.epochs()[0].public);
+        let err = tampered.verify::<S>(&genesis_key).unwrap_err();
+        assert!(matches!(
+            err,
+            DKGError::ChainError(ChainError::InvalidTranscript(1, 2))
+        ));
+
+        // too few dealers
+        let mut tampered = chain.clone();
+        tampered.epochs[2].transcript.remove(&0);
+        tampered.epochs[2].transcript.remove(&1);
+        let err = tampered.verify::<S>(&genesis_key).unwrap_err();
+        assert!(matches!(
+            err,
+            DKGError::ChainError(ChainError::NotEnoughDealers(2, 2, 3))
+        ));
+
+        // a refresh dealer which did not share zero
+        let mut tampered = chain.clone();
+        let poly = tampered.epochs[3].transcript.get_mut(&1).unwrap();
+        poly.add(&chain.epochs()[2].public);
+        tampered.epochs[3].public.add(&chain.epochs()[2].public);
+        let err = tampered.verify::<S>(&genesis_key).unwrap_err();
+        assert!(matches!(
+            err,
+            DKGError::ChainError(ChainError::InvalidTranscript(3, 1))
+        ));
+
+        // a refresh which is not added to the previous public polynomial
+        let mut tampered = chain.clone();
+        tampered.epochs[3].public = chain.epochs()[2].public.clone();
+        let err = tampered.verify::<S>(&genesis_key).unwrap_err();
+        assert!(matches!(
+            err,
+            DKGError::ChainError(ChainError::InvalidPublicPoly(3))
+        ));
+
+        // too few refresh dealers
+        let mut tampered = chain.clone();
+        tampered.epochs[3].transcript.remove(&2);
+        let err = tampered.verify::<S>(&genesis_key).unwrap_err();
+        assert!(matches!(
+            err,
+            DKGError::ChainError(ChainError::NotEnoughDealers(3, 3, 4))
+        ));
+
+        // a refresh does not change the group
+        let mut tampered = chain.clone();
+        tampered.epochs[3].group.threshold = 3;
+        let err = tampered.verify::<S>(&genesis_key).unwrap_err();
+        assert!(matches!(
+            err,
+            DKGError::ChainError(ChainError::InvalidGroup(3))
+        ));
+
+        // the threshold of an untrusted group is checked before its use
+        for threshold in &[0, 6] {
+            for i in 0..4 {
+                let mut tampered = chain.clone();
+                tampered.epochs[i].group.threshold = *threshold;
+                let err = tampered.verify::<S>(&genesis_key).unwrap_err();
+                assert!(matches!(
+                    err,
+                    DKGError::ChainError(ChainError::InvalidGroup(j)) if j == i
+                ));
+            }
+        }
+
+        // nor is it a valid resharing
+        let mut tampered = chain.clone();
+        tampered.epochs[3].refresh = false;
+        let err = tampered.verify::<S>(&genesis_key).unwrap_err();
+        assert!(matches!(
+            err,
+            DKGError::ChainError(ChainError::InvalidTranscript(3, _))
+        ));
+    }
+
+    #[test]
+    fn forged_epoch() {
+        let (chain, _) = setup_chain();
+        let genesis_key = chain.epochs()[0].public.public_key().clone();
+
+        // anyone can compute a transcript which reshares the previous public
+        // polynomial, but not the certificate of the forged polynomial
+        let mut forged = chain.clone();
+        let prev = chain.epochs()[1].public.clone();
+        let transcript = forged.epochs[2]
+            .transcript
+            .iter_mut()
+            .map(|(idx, poly)| {
+                let mut coeffs = (0..poly.degree() + 1)
+                    .map(|i| poly.get(i as Idx))
+                    .collect::<Vec<_>>();
+                coeffs[0] = prev.eval(*idx).value;
+                coeffs[1] = prev.get(0);
+                (*idx, PublicPoly::<BCurve>::from(coeffs))
+            })
+            .collect::<BTreeMap<_, _>>();
+        let public = recover_public::<BCurve>(3, 4, &transcript).unwrap();
+        forged.epochs[2].transcript = transcript;
+        forged.epochs[2].public = public;
+        let err = forged.verify::<S>(&genesis_key).unwrap_err();
+        assert!(matches!(
+            err,
+            DKGError::ChainError(ChainError::InvalidCertificate(2))
+        ));
+    }
+}
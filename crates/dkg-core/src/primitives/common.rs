@@ -1,53 +1,107 @@
 use crate::primitives::{
-    group::Group,
+    auth::Bundle,
+    events,
+    group::{Group, Node},
     status::{Status, StatusMatrix},
     types::*,
     DKGError, DKGResult, ShareError,
 };
 
 use rand_core::RngCore;
-use std::collections::HashMap;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use std::collections::{BTreeSet, HashMap};
 use threshold_bls::{
     ecies,
-    group::{Curve, Element},
+    group::Curve,
     poly::{Idx, PrivatePoly, PublicPoly},
+    sig::Share,
+    vss,
 };
 
-pub type ShareInfo<C> = HashMap<Idx, <C as Curve>::Scalar>;
+/// The shares received from each dealer, one for each of our share indices
+pub type ShareInfo<C> = HashMap<Idx, Vec<<C as Curve>::Scalar>>;
 pub type PublicInfo<C> = HashMap<Idx, PublicPoly<C>>;
 
+/// Returns the associated data binding the encryption of a share to the session
+/// and to the dealer and share holder of the share.
+fn share_ad(session: &SessionId, dealer_idx: Idx, share_idx: Idx) -> Vec<u8> {
+    let mut ad = session.as_bytes().to_vec();
+    ad.extend_from_slice(&dealer_idx.to_le_bytes());
+    ad.extend_from_slice(&share_idx.to_le_bytes());
+    ad
+}
+
+/// Decrypts the shares sent to us by the dealer, and checks that there is one
+/// for each of our share indices which matches the dealer's public polynomial.
 pub fn decrypt_and_check_share<C: Curve>(
     private_key: &C::Scalar,
-    own_idx: Idx,
+    session: &SessionId,
+    own: &Node<C>,
     dealer_idx: Idx,
     public: &PublicPoly<C>,
     share: &EncryptedShare<C>,
-) -> Result<C::Scalar, DKGError> {
-    let buff = ecies::decrypt::<C>(private_key, &share.secret).map_err(|err| {
-        println!("ERROR {:?}", err);
-        ShareError::InvalidCiphertext(dealer_idx, err)
-    })?;
+) -> Result<Vec<C::Scalar>, DKGError> {
+    let ad = share_ad(session, dealer_idx, own.id());
+    let buff = ecies::decrypt_with_ad::<C>(private_key, &share.secret, &ad)
+        .map_err(|err| ShareError::InvalidCiphertext(dealer_idx, err))?;
 
-    let clear_share: C::Scalar = bincode::deserialize(&buff)?;
+    let clear_shares: Vec<C::Scalar> = bincode::deserialize(&buff)?;
 
-    if !share_correct::<C>(own_idx, &clear_share, public) {
-        println!("INCORRECT");
+    if !shares_correct::<C>(own, &clear_shares, public) {
         return Err(ShareError::InvalidShare(dealer_idx).into());
     }
 
-    Ok(clear_share)
+    Ok(clear_shares)
+}
+
+/// Keeps a single bundle per sender. Identical copies of a bundle are
+/// deduplicated, while the senders which published conflicting bundles are
+/// dropped altogether and recorded as culprits.
+pub fn remove_equivocations<'a, T>(
+    bundles: impl Iterator<Item = &'a T>,
+    culprits: &mut BTreeSet<Idx>,
+) -> Vec<&'a T>
+where
+    T: Bundle + 'a,
+{
+    let mut firsts = HashMap::new();
+    let mut kept = Vec::new();
+    for bundle in bundles {
+        let sender = bundle.sender();
+        match firsts.get(&sender) {
+            Some(first) => {
+                if conflicting(bundle.session(), *first, bundle) {
+                    culprits.insert(sender);
+                }
+            }
+            None => {
+                firsts.insert(sender, bundle);
+                kept.push(bundle);
+            }
+        }
+    }
+    kept.retain(|b| !culprits.contains(&b.sender()));
+    kept
 }
 
 /// set_statuses set the status of the given responses on the status matrix.
+/// The responses of share holders which equivocated are ignored.
 pub fn set_statuses<C: Curve>(
     holder_idx: Idx,
+    session: &SessionId,
     dealers: &Group<C>,
     holders: &Group<C>,
     statuses: &mut StatusMatrix,
     responses: &[BundledResponses],
+    equivocators: &mut Equivocators,
 ) {
+    // ignore the responses from another session, and the ones of share
+    // holders which equivocated (including us, so that everyone agrees)
+    let responses = responses.iter().filter(|r| &r.session == session);
+    let responses = remove_equivocations(responses, &mut equivocators.share_holders);
     // makes sure the API doesn't take into account our own responses!
-    let not_from_me = responses.iter().filter(|r| r.share_idx != holder_idx);
+    let not_from_me = responses.into_iter().filter(|r| r.share_idx != holder_idx);
     let valid_idx = not_from_me.filter(|r| {
         let good_holder = holders.contains_index(r.share_idx);
         let good_dealers = !r
@@ -66,18 +120,37 @@ pub fn set_statuses<C: Curve>(
     }
 }
 
-/// Checks if the commitment to the share corresponds to the public polynomial's
-/// evaluated at the given point.
-pub fn share_correct<C: Curve>(idx: Idx, share: &C::Scalar, public: &PublicPoly<C>) -> bool {
-    let mut commit = C::Point::one();
-    commit.mul(share);
-    let pub_eval = public.eval(idx);
-    pub_eval.value == commit
+/// Checks that there is one share for each share index of the node, and that
+/// each of them is correct.
+pub fn shares_correct<C: Curve>(
+    node: &Node<C>,
+    shares: &[C::Scalar],
+    public: &PublicPoly<C>,
+) -> bool {
+    if shares.len() != node.weight() {
+        return false;
+    }
+    let shares = node_shares::<C>(node, shares);
+    vss::verify_shares::<C, _>(&shares, public, &mut rand::thread_rng())
+}
+
+// pairs the scalars with the share indices of the node
+fn node_shares<C: Curve>(node: &Node<C>, shares: &[C::Scalar]) -> Vec<Share<C::Scalar>> {
+    node.shares()
+        .zip(shares.iter().cloned())
+        .map(|(index, private)| Share { index, private })
+        .collect()
+}
+
+/// Evaluates the secret polynomial at each share index of the node
+pub fn eval_shares<C: Curve>(node: &Node<C>, secret: &PrivatePoly<C>) -> Vec<C::Scalar> {
+    node.shares().map(|idx| secret.eval(idx).value).collect()
 }
 
 /// Creates the encrypted shares with the given secret polynomial to the given
 /// group.
 pub fn create_share_bundle<C: Curve, R: RngCore>(
+    session: &SessionId,
     dealer_idx: Idx,
     secret: &PrivatePoly<C>,
     public: &PublicPoly<C>,
@@ -88,14 +161,15 @@ pub fn create_share_bundle<C: Curve, R: RngCore>(
         .nodes
         .iter()
         .map(|n| {
-            // evaluate the secret polynomial at the node's id
-            let sec = secret.eval(n.id() as Idx);
+            // evaluate the secret polynomial at the node's share indices
+            let sec = eval_shares(n, secret);
 
-            // serialize the evaluation
-            let buff = bincode::serialize(&sec.value)?;
+            // serialize the evaluations
+            let buff = bincode::serialize(&sec)?;
 
-            // encrypt it
-            let cipher = ecies::encrypt::<C, _>(n.key(), &buff, rng);
+            // encrypt it, bound to this session
+            let ad = share_ad(session, dealer_idx, n.id());
+            let cipher = ecies::encrypt_with_ad::<C, _>(n.key(), &buff, &ad, rng);
 
             // save the share
             Ok(EncryptedShare {
@@ -107,6 +181,7 @@ pub fn create_share_bundle<C: Curve, R: RngCore>(
     // Return the encrypted shares along with a commitment
     // to their secret polynomial.
     Ok(BundledShares {
+        session: *session,
         dealer_idx,
         shares,
         public: public.clone(),
@@ -114,6 +189,7 @@ pub fn create_share_bundle<C: Curve, R: RngCore>(
 }
 
 pub fn compute_bundle_response(
+    session: &SessionId,
     my_idx: Idx,
     statuses: &StatusMatrix,
     publish_all: bool,
@@ -121,9 +197,9 @@ pub fn compute_bundle_response(
     let responses = statuses
         .get_for_share(my_idx)
         .into_iter()
-        .enumerate()
-        .map(|(i, b)| Response {
-            dealer_idx: i as Idx,
+        .zip(statuses.dealers())
+        .map(|(b, &dealer_idx)| Response {
+            dealer_idx,
             status: Status::from(b),
         });
 
@@ -136,8 +212,14 @@ pub fn compute_bundle_response(
         responses.collect::<Vec<_>>()
     };
 
+    responses
+        .iter()
+        .filter(|r| !r.status.is_success())
+        .for_each(|r| events::complaint(my_idx, r.dealer_idx));
+
     if !responses.is_empty() {
         Some(BundledResponses {
+            session: *session,
             responses,
             share_idx: my_idx,
         })
@@ -147,7 +229,8 @@ pub fn compute_bundle_response(
 }
 
 /// Processes the shares and returns the private share of the user and a public
-/// polynomial, as well as the status matrix of the protocol.
+/// polynomial, as well as the status matrix of the protocol. The dealers whose
+/// shares were rejected are recorded in the report.
 ///
 /// Depending on which variant of the DKG protocol is used, the status
 /// matrix responses which correspond to our index may be used in the
@@ -157,72 +240,135 @@ pub fn compute_bundle_response(
 /// not good unless you hear otherwise.  - Broadcast only responses which
 /// are complaints: You assume that shares of other nodes are good unless
 /// you hear otherwise.
+#[allow(clippy::too_many_arguments)]
 pub fn process_shares_get_all<C: Curve>(
     dealers: &Group<C>,
     share_holders: &Group<C>,
-    my_dealer_idx: Option<Idx>,
+    my_dealer_indices: &[Idx],
     my_idx: Idx,
     my_private: &C::Scalar,
+    session: &SessionId,
     bundles: &[BundledShares<C>],
+    equivocators: &mut Equivocators,
+    report: &mut DKGReport,
 ) -> DKGResult<(ShareInfo<C>, PublicInfo<C>, StatusMatrix)> {
+    let me = share_holders.node(my_idx).ok_or(DKGError::NotShareHolder)?;
+
     // there are "old_n" dealers and for each dealer, "new_n" share holders
-    let mut statuses = StatusMatrix::new(dealers.len(), share_holders.len(), Status::Success);
+    let mut statuses = StatusMatrix::with_indices(
+        &dealers.indices(),
+        &share_holders.indices(),
+        Status::Success,
+    );
 
     // set by default all the shares we could receive as complaint - that puts
     // us on the conservative side of only explicitely allowing correct shares.
-    (0..dealers.len())
-        .filter(|&dealer_idx| dealer_idx != my_idx as usize)
+    dealers
+        .indices()
+        .into_iter()
+        .filter(|dealer_idx| !my_dealer_indices.contains(dealer_idx))
         .for_each(|dealer_idx| {
-            statuses.set(dealer_idx as Idx, my_idx, Status::Complaint);
+            statuses.set(dealer_idx, my_idx, Status::Complaint);
         });
 
-    let mut publics = PublicInfo::<C>::new();
-    let valid_shares = bundles
+    let bundles = bundles
         .iter()
-        // check the ones that are not from us (do not filter if there was no dealer idx specified)
-        .filter(|b| my_dealer_idx.map(|idx| b.dealer_idx != idx).unwrap_or(true))
+        // ignore the bundles of other sessions
+        .filter(|b| &b.session == session)
         //check the ones with a valid dealer index
-        .filter(|b| dealers.contains_index(b.dealer_idx))
+        .filter(|b| dealers.contains_index(b.dealer_idx));
+    // dealers which published several bundles are disqualified (including
+    // us, so that everyone agrees)
+    let bundles = remove_equivocations(bundles, &mut equivocators.dealers);
+
+    // dealers which did not publish anything are excluded
+    report.exclude_equivocations(equivocators);
+    for dealer in dealers.nodes.iter().map(|n| n.id()) {
+        if !my_dealer_indices.contains(&dealer) && !bundles.iter().any(|b| b.dealer_idx == dealer) {
+            reject_share(report, my_idx, dealer, ExclusionReason::MissingBundle);
+        }
+    }
+
+    // check the ones that are not from us (there are none to filter if we
+    // are not a dealer)
+    let bundles = bundles
+        .into_iter()
+        .filter(|b| !my_dealer_indices.contains(&b.dealer_idx))
+        .collect::<Vec<_>>();
+
+    // decrypting and checking the shares is the expensive part, so it is done
+    // concurrently before recording the outcomes in order
+    let expected = share_holders.threshold - 1;
+    let check = |bundle: &&BundledShares<C>| -> Result<Vec<C::Scalar>, ExclusionReason> {
         // only consider public polynomial of the right form
-        .filter(|b| b.public.degree() == share_holders.threshold - 1)
-        // save them for later
-        .inspect(|b| {
-            publics.insert(b.dealer_idx, b.public.clone());
-        })
+        if bundle.public.degree() != expected {
+            return Err(ExclusionReason::InvalidPolynomialDegree(
+                bundle.public.degree(),
+                expected,
+            ));
+        }
+
         // get the share which corresponds to us
-        .filter_map(|b| {
-            // TODO: Return an error if there are multiple cases where the share
-            // index matches ours.
-            // `.find` stops at the first occurence only.
-            b.shares
-                .iter()
-                .find(|s| s.share_idx == my_idx)
-                .map(|share| (b, share))
-        })
-        // try to decrypt it (ignore invalid decryptions)
-        .filter_map(|(bundle, encrypted_share)| {
-            decrypt_and_check_share(
-                my_private,
-                my_idx,
-                bundle.dealer_idx,
-                &bundle.public,
-                encrypted_share,
-            )
-            .map(|share| (bundle.dealer_idx, share))
-            .ok()
+        // TODO: Return an error if there are multiple cases where the share
+        // index matches ours.
+        // `.find` stops at the first occurence only.
+        let encrypted_share = bundle
+            .shares
+            .iter()
+            .find(|s| s.share_idx == my_idx)
+            .ok_or(ExclusionReason::MissingShare)?;
+
+        // try to decrypt it (record invalid decryptions)
+        decrypt_and_check_share(
+            my_private,
+            session,
+            me,
+            bundle.dealer_idx,
+            &bundle.public,
+            encrypted_share,
+        )
+        .map_err(|err| match err {
+            DKGError::ShareError(ShareError::InvalidCiphertext(_, _)) => {
+                ExclusionReason::InvalidCiphertext
+            }
+            _ => ExclusionReason::InvalidShare,
         })
-        .fold(ShareInfo::<C>::new(), |mut acc, (didx, share)| {
-            println!(" -- got new share from {}", didx);
-            statuses.set(didx, my_idx, Status::Success);
-            acc.insert(didx, share);
-            acc
-        });
+    };
+    #[cfg(feature = "parallel")]
+    let outcomes = bundles.par_iter().map(check).collect::<Vec<_>>();
+    #[cfg(not(feature = "parallel"))]
+    let outcomes = bundles.iter().map(check).collect::<Vec<_>>();
+
+    let mut publics = PublicInfo::<C>::new();
+    let mut valid_shares = ShareInfo::<C>::new();
+    for (bundle, outcome) in bundles.into_iter().zip(outcomes) {
+        let dealer_idx = bundle.dealer_idx;
+        // save the polynomials of the right form for later
+        if bundle.public.degree() == expected {
+            publics.insert(dealer_idx, bundle.public.clone());
+        }
+        match outcome {
+            Ok(share) => {
+                events::share_accepted(my_idx, dealer_idx);
+                statuses.set(dealer_idx, my_idx, Status::Success);
+                valid_shares.insert(dealer_idx, share);
+            }
+            Err(reason) => reject_share(report, my_idx, dealer_idx, reason),
+        }
+    }
 
     Ok((valid_shares, publics, statuses))
 }
 
+fn reject_share(report: &mut DKGReport, my_idx: Idx, dealer_idx: Idx, reason: ExclusionReason) {
+    events::share_rejected(my_idx, dealer_idx, &reason);
+    report.exclude_dealer(dealer_idx, reason);
+}
+
 pub fn get_justification<C: Curve>(
+    session: &SessionId,
     dealer_idx: Idx,
+    holders: &Group<C>,
     secret: &PrivatePoly<C>,
     public: &PublicPoly<C>,
     statuses: &StatusMatrix,
@@ -234,21 +380,17 @@ pub fn get_justification<C: Curve>(
         let justifications = statuses
             .get_for_dealer(dealer_idx)
             .iter()
-            .enumerate()
-            .filter_map(|(i, success)| {
-                if !success {
-                    // reveal the share
-                    let id = i as Idx;
-                    Some(Justification {
-                        share_idx: id,
-                        share: secret.eval(id).value,
-                    })
-                } else {
-                    None
-                }
+            .zip(statuses.share_holders())
+            .filter(|(success, _)| !**success)
+            .filter_map(|(_, &i)| holders.node(i))
+            // reveal the shares
+            .map(|node| Justification {
+                share_idx: node.id(),
+                shares: eval_shares(node, secret),
             })
             .collect::<Vec<_>>();
         Some(BundledJustification {
+            session: *session,
             dealer_idx,
             justifications,
             public: public.clone(),
@@ -258,38 +400,101 @@ pub fn get_justification<C: Curve>(
     }
 }
 
-/// returns the correct shares destined to the given holder index
+/// Checks the justifications of a dealer, all at once first since they are
+/// usually correct, and one by one to find the incorrect ones otherwise.
+fn justifications_correct<C: Curve>(
+    holders: &Group<C>,
+    justifications: &[Justification<C>],
+    public: &PublicPoly<C>,
+) -> Vec<bool> {
+    let mut shares = Vec::new();
+    let mut valid = justifications
+        .iter()
+        .map(
+            |justification| match holders.node(justification.share_idx) {
+                Some(node) if justification.shares.len() == node.weight() => {
+                    shares.extend(node_shares::<C>(node, &justification.shares));
+                    true
+                }
+                _ => false,
+            },
+        )
+        .collect::<Vec<_>>();
+
+    if !vss::verify_shares::<C, _>(&shares, public, &mut rand::thread_rng()) {
+        for (justification, valid) in justifications.iter().zip(valid.iter_mut()) {
+            *valid = *valid
+                && holders
+                    .node(justification.share_idx)
+                    .map(|node| {
+                        node_shares::<C>(node, &justification.shares)
+                            .iter()
+                            .all(|share| vss::verify_share::<C>(share, public))
+                    })
+                    .unwrap_or(false);
+        }
+    }
+    valid
+}
+
+/// returns the correct shares destined to the given holder index. The
+/// justifications of dealers which equivocated are ignored.
+#[allow(clippy::too_many_arguments)]
 pub fn internal_process_justifications<C: Curve>(
     holder_idx: Idx,
+    session: &SessionId,
     dealers: &Group<C>,
+    holders: &Group<C>,
     statuses: &mut StatusMatrix,
     publics: &PublicInfo<C>,
     justifs: &[BundledJustification<C>],
+    equivocators: &mut Equivocators,
 ) -> ShareInfo<C> {
     let mut valid_shares = ShareInfo::<C>::new();
-    justifs
+    let justifs = justifs
         .iter()
-        .filter(|b| dealers.contains_index(b.dealer_idx))
+        .filter(|b| &b.session == session)
+        .filter(|b| dealers.contains_index(b.dealer_idx));
+    let bundles = remove_equivocations(justifs, &mut equivocators.dealers)
+        .into_iter()
         // get only the bundles for which we have a public polynomial for
         // i.e. only justif for polynomials that have been broadcasted in the
         // first phase
         .filter_map(|b| publics.get(&b.dealer_idx).map(|public| (b, public)))
-        .for_each(|(bundle, public)| {
-            bundle
-                .justifications
-                .iter()
-                // ignore incorrect shares
-                .filter(|justification| {
-                    share_correct::<C>(justification.share_idx, &justification.share, public)
-                })
-                .for_each(|justification| {
-                    // justification is valid, we mark it off from our matrix
-                    statuses.set(bundle.dealer_idx, justification.share_idx, Status::Success);
-                    if holder_idx == justification.share_idx {
-                        valid_shares.insert(bundle.dealer_idx, justification.share.clone());
-                    }
-                })
-        });
+        .collect::<Vec<_>>();
+
+    // the justifications of each dealer are checked concurrently
+    let check = |(bundle, public): &(&BundledJustification<C>, &PublicPoly<C>)| {
+        justifications_correct(holders, &bundle.justifications, public)
+    };
+    #[cfg(feature = "parallel")]
+    let outcomes = bundles.par_iter().map(check).collect::<Vec<_>>();
+    #[cfg(not(feature = "parallel"))]
+    let outcomes = bundles.iter().map(check).collect::<Vec<_>>();
+
+    for ((bundle, _), valid) in bundles.into_iter().zip(outcomes) {
+        bundle
+            .justifications
+            .iter()
+            .zip(valid)
+            // ignore incorrect shares
+            .filter(|(justification, valid)| {
+                events::justification(
+                    holder_idx,
+                    bundle.dealer_idx,
+                    justification.share_idx,
+                    *valid,
+                );
+                *valid
+            })
+            .for_each(|(justification, _)| {
+                // justification is valid, we mark it off from our matrix
+                statuses.set(bundle.dealer_idx, justification.share_idx, Status::Success);
+                if holder_idx == justification.share_idx {
+                    valid_shares.insert(bundle.dealer_idx, justification.shares.clone());
+                }
+            })
+    }
     valid_shares
 }
 
@@ -298,17 +503,21 @@ pub mod tests {
     use super::*;
     use crate::primitives::phases::{Phase0, Phase1, Phase2, Phase3};
     use rand::thread_rng;
-    use threshold_bls::poly::{Eval, Poly, PolyError};
+    use threshold_bls::{
+        group::Element,
+        poly::{Eval, Poly, PolyError},
+    };
 
-    fn reconstruct<C: Curve>(
+    pub fn reconstruct<C: Curve>(
         thr: usize,
         shares: &[DKGOutput<C>],
     ) -> Result<PrivatePoly<C>, PolyError> {
         let evals: Vec<_> = shares
             .iter()
-            .map(|o| Eval {
-                value: o.share.private.clone(),
-                index: o.share.index,
+            .flat_map(|o| &o.shares)
+            .map(|s| Eval {
+                value: s.private.clone(),
+                index: s.index,
             })
             .collect();
         Poly::<C::Scalar>::full_recover(thr, evals)
@@ -332,6 +541,20 @@ pub mod tests {
         (privs, group)
     }
 
+    pub fn setup_weighted_group<C: Curve>(
+        weights: &[usize],
+        thr: usize,
+    ) -> (Vec<C::Scalar>, Group<C>) {
+        let (privs, group) = setup_group::<C>(weights.len(), thr);
+        let list = group
+            .nodes
+            .iter()
+            .zip(weights)
+            .map(|(n, w)| (n.key().clone(), *w))
+            .collect();
+        (privs, Group::new_weighted(list, thr).unwrap())
+    }
+
     pub fn invalid2<C: Curve>(mut s: Vec<BundledShares<C>>) -> Vec<BundledShares<C>> {
         // modify a share
         s[0].shares[1].secret = ecies::encrypt(&C::point(), &[1], &mut thread_rng());
@@ -372,9 +595,7 @@ pub mod tests {
             .into_iter()
             .map(|dkg| {
                 let (ndkg, shares) = dkg.encrypt_shares(&mut thread_rng()).unwrap();
-                if let Some(sh) = shares {
-                    all_shares.push(sh);
-                }
+                all_shares.extend(shares);
                 ndkg
             })
             .collect();
@@ -402,9 +623,7 @@ pub mod tests {
                 Ok(_) => panic!("dkg shouldn't have finished OHE"),
                 Err(next) => match next {
                     Ok((ndkg, justifs)) => {
-                        if let Some(j) = justifs {
-                            justifications.push(j);
-                        }
+                        justifications.extend(justifs);
                         ndkg
                     }
                     Err(e) => std::panic::panic_any(e),
@@ -445,9 +664,7 @@ pub mod tests {
             .into_iter()
             .map(|dkg| {
                 let (ndkg, shares) = dkg.encrypt_shares(&mut thread_rng()).unwrap();
-                if let Some(sh) = shares {
-                    all_shares.push(sh);
-                }
+                all_shares.extend(shares);
                 ndkg
             })
             .collect();
@@ -488,4 +705,29 @@ pub mod tests {
         }
         (outputs, recovered_public)
     }
+
+    #[test]
+    fn batch_justifications() {
+        use threshold_bls::curve::bls12377::G1Curve as BCurve;
+        let (_, group) = setup_weighted_group::<BCurve>(&[1, 3, 2, 1], 4);
+        let private = PrivatePoly::<BCurve>::new(3);
+        let public = private.commit();
+        let mut justifications = group
+            .nodes
+            .iter()
+            .map(|node| Justification {
+                share_idx: node.id(),
+                shares: eval_shares(node, &private),
+            })
+            .collect::<Vec<_>>();
+        let valid = justifications_correct(&group, &justifications, &public);
+        assert_eq!(valid, vec![true; 4]);
+
+        // the incorrect justifications are found one by one
+        justifications[1].shares[2].add(&<BCurve as Curve>::Scalar::one());
+        justifications[2].shares.pop();
+        justifications[3].share_idx = 10;
+        let valid = justifications_correct(&group, &justifications, &public);
+        assert_eq!(valid, vec![true, false, false, false]);
+    }
 }
@@ -1,3 +1,4 @@
+use super::types::DKGReport;
 use thiserror::Error;
 use threshold_bls::{ecies::EciesError, poly, poly::Idx};
 
@@ -19,19 +20,48 @@ pub enum DKGError {
 
     /// InvalidThreshold is raised when creating a group and specifying an
     /// invalid threshold. Either the threshold is too low, inferior to
-    /// what `minimum_threshold()` returns or is too large (i.e. larger than the
-    /// number of nodes).
+    /// what `minimum_threshold()` returns (or zero for a group which is only
+    /// validated) or is too large (i.e. larger than the weight of the group).
     #[error("threshold {0} is not in range [{1},{2}]")]
     InvalidThreshold(usize, usize, usize),
 
+    /// DuplicateIndex is raised when several nodes of a group have the same
+    /// index.
+    #[error("index {0} is used by several nodes")]
+    DuplicateIndex(Idx),
+
+    /// DuplicateShareIndex is raised when a share index is owned by several
+    /// nodes of a weighted group.
+    #[error("share index {0} is owned by several nodes")]
+    DuplicateShareIndex(Idx),
+
+    /// DuplicatePublicKey is raised when the public key of a node is already
+    /// used by another node of the group.
+    #[error("the public key of node {0} is used by another node")]
+    DuplicatePublicKey(Idx),
+
+    /// ZeroWeight is raised when a node of a group does not own any share
+    /// index.
+    #[error("node {0} does not own any share index")]
+    ZeroWeight(Idx),
+
+    /// InvalidPublicKey is raised when the public key of a node is the
+    /// identity point or is not in the prime-order subgroup. Anyone could
+    /// decrypt the shares encrypted to such a key.
+    #[error("the public key of node {0} is not a valid group element")]
+    InvalidPublicKey(Idx),
+
     /// NotEnoughValidShares is raised when the DKG has not successfully
     /// processed enough shares because they were invalid. In that case, the DKG
-    /// can not continue, the protocol MUST be aborted.
+    /// can not continue, the protocol MUST be aborted. The report lists the
+    /// dealers whose shares were rejected.
     #[error("only has {0}/{1} valid shares")]
-    NotEnoughValidShares(usize, usize),
+    NotEnoughValidShares(usize, usize, DKGReport),
 
+    /// NotEnoughJustifications is raised when too many dealers did not answer
+    /// the complaints against them. The report lists the excluded dealers.
     #[error("only has {0}/{1} required justifications")]
-    NotEnoughJustifications(usize, usize),
+    NotEnoughJustifications(usize, usize, DKGReport),
 
     /// Rejected is raised when the participant is rejected from the final
     /// output
@@ -46,6 +76,24 @@ pub enum DKGError {
     #[error(transparent)]
     ShareError(#[from] ShareError),
 
+    /// RepairError is raised when the share of a member cannot be repaired
+    #[error(transparent)]
+    RepairError(#[from] RepairError),
+
+    /// CertificateError is raised when an attestation or a completion
+    /// certificate cannot be created or is invalid
+    #[error(transparent)]
+    CertificateError(#[from] CertificateError),
+
+    /// ChainError is raised when a chain of epochs does not verify
+    #[error(transparent)]
+    ChainError(#[from] ChainError),
+
+    /// SecretError is raised when a byte secret shared among a group cannot
+    /// be decrypted or recovered
+    #[error(transparent)]
+    SecretError(#[from] SecretError),
+
     /// NotDealer is raised when one attempts to call a method of a
     /// dealer during a resharing when it is not a member of the current group.
     #[error("this participant is not a dealer")]
@@ -57,6 +105,16 @@ pub enum DKGError {
     #[error("this participant is not a share holder")]
     NotShareHolder,
 
+    /// InvalidRefreshShare is raised when the share to refresh does not match
+    /// the public polynomial of the group.
+    #[error("the share to refresh does not match the public polynomial")]
+    InvalidRefreshShare,
+
+    /// PublicKeyChanged is raised when the refreshed public polynomial does
+    /// not commit to the same distributed public key as before the refresh.
+    #[error("the refresh changed the distributed public key")]
+    PublicKeyChanged,
+
     #[error("invalid recovery during resharing: {0}")]
     InvalidRecovery(#[from] poly::PolyError),
 }
@@ -81,3 +139,135 @@ pub enum ShareError {
     #[error("[dealer: {0}] polynomial does not have the correct degree, got: {1}, expected {2}")]
     InvalidPublicPolynomial(Idx, usize, usize),
 }
+
+#[derive(Debug, Error)]
+/// Error which may occur while repairing the share of a member. The index in
+/// the variants is the one of the helper at fault, which should be replaced
+/// by another member before trying again.
+pub enum RepairError {
+    /// InvalidHelpers is raised when there are less than a threshold of
+    /// distinct helpers in the group, or when the repaired member is one of
+    /// them.
+    #[error("invalid set of helpers for repairing the share of {0}")]
+    InvalidHelpers(Idx),
+    /// WeightedGroup is raised when the group has nodes owning several share
+    /// indices, since only a single share can be repaired at a time.
+    #[error("repairing the shares of a weighted group is not supported")]
+    WeightedGroup,
+    /// MissingBundle is raised when a helper did not publish its masks
+    #[error("[helper: {0}] missing masks")]
+    MissingBundle(Idx),
+    /// InvalidBundle is raised when the masks of a helper do not add up to
+    /// its contribution to the repaired share
+    #[error("[helper: {0}] masks do not match the public polynomial")]
+    InvalidBundle(Idx),
+    /// InvalidMask is raised when the mask sent to us by a helper cannot be
+    /// decrypted or does not match its commitment
+    #[error("[helper: {0}] invalid mask")]
+    InvalidMask(Idx),
+    /// MissingContribution is raised when a helper did not send its sum of
+    /// masks to the repaired member
+    #[error("[helper: {0}] missing contribution")]
+    MissingContribution(Idx),
+    /// InvalidContribution is raised when the sum of masks sent by a helper
+    /// cannot be decrypted or does not match the commitments
+    #[error("[helper: {0}] invalid contribution")]
+    InvalidContribution(Idx),
+}
+
+#[derive(Debug, Error)]
+/// Error which may occur while attesting to the output of a DKG, or while
+/// aggregating the attestations into a completion certificate. The index in
+/// the variants is the one of the member at fault.
+pub enum CertificateError {
+    /// NotQualified is raised when the shares of the output do not belong to
+    /// a member of its qualified group.
+    #[error("the shares do not belong to a qualified member")]
+    NotQualified,
+    /// SigningFailed is raised when the signature scheme fails to sign or to
+    /// aggregate the partial signatures.
+    #[error("could not sign the fingerprint: {0}")]
+    SigningFailed(String),
+    /// UnknownSigner is raised when the signer of an attestation is not part
+    /// of the qualified group.
+    #[error("[member: {0}] not part of the group")]
+    UnknownSigner(Idx),
+    /// FingerprintMismatch is raised when a member attested to a different
+    /// group or public polynomial.
+    #[error("[member: {0}] attested to another output")]
+    FingerprintMismatch(Idx),
+    /// InvalidPartial is raised when the partial signatures of a member are
+    /// invalid or not made with its own shares.
+    #[error("[member: {0}] invalid partial signatures")]
+    InvalidPartial(Idx),
+    /// NotEnoughAttestations is raised when less than a threshold of shares
+    /// attested to the output.
+    #[error("only has {0}/{1} attesting shares")]
+    NotEnoughAttestations(usize, usize),
+    /// InvalidCertificate is raised when the certificate is not for the given
+    /// output or its signature is invalid.
+    #[error("invalid completion certificate")]
+    InvalidCertificate,
+}
+
+#[derive(Debug, Error)]
+/// Error which may occur while verifying a chain of epochs. The index in the
+/// variants is the position of the first invalid epoch in the chain.
+pub enum ChainError {
+    /// EmptyChain is raised when the chain does not contain any epoch
+    #[error("the chain is empty")]
+    EmptyChain,
+    /// GenesisKeyMismatch is raised when the genesis epoch does not have the
+    /// trusted distributed public key.
+    #[error("the genesis epoch does not have the trusted public key")]
+    GenesisKeyMismatch,
+    /// InvalidGroup is raised when the group of an epoch is invalid
+    #[error("[epoch: {0}] invalid group")]
+    InvalidGroup(usize),
+    /// InvalidTranscript is raised when the polynomial of a dealer in the
+    /// transcript is not a resharing of its share of the previous epoch. The
+    /// second index is the dealer's.
+    #[error("[epoch: {0}] dealer {1} did not reshare its share")]
+    InvalidTranscript(usize, Idx),
+    /// NotEnoughDealers is raised when the transcript has less than a
+    /// threshold of dealers of the previous epoch.
+    #[error("[epoch: {0}] only has {1}/{2} dealers")]
+    NotEnoughDealers(usize, usize, usize),
+    /// InvalidPublicPoly is raised when the public polynomial of an epoch does
+    /// not match its transcript, or does not have the degree of its group.
+    #[error("[epoch: {0}] the public polynomial does not match the transcript")]
+    InvalidPublicPoly(usize),
+    /// InvalidCertificate is raised when the certificate of an epoch is not
+    /// valid for its group and public polynomial.
+    #[error("[epoch: {0}] invalid completion certificate")]
+    InvalidCertificate(usize),
+}
+
+#[derive(Debug, Error)]
+/// Error which may occur while decrypting or recovering a byte secret shared
+/// among a group.
+pub enum SecretError {
+    /// NotRecipient is raised when the secret was not shared with the node
+    #[error("the secret was not shared with node {0}")]
+    NotRecipient(Idx),
+    /// InvalidCiphertext is raised when the shares of the node or the secret
+    /// cannot be decrypted.
+    #[error("[node: {0}] invalid ciphertext")]
+    InvalidCiphertext(Idx, EciesError),
+    /// InvalidShares is raised when the shares of the node do not match the
+    /// public polynomial of the shared key.
+    #[error("[node: {0}] shares do not match the public polynomial")]
+    InvalidShares(Idx),
+    /// NotEnoughShares is raised when less than a threshold of distinct shares
+    /// are given for the recovery.
+    #[error("only has {0}/{1} shares")]
+    NotEnoughShares(usize, usize),
+    /// InvalidKey is raised when the recovered key does not match the public
+    /// polynomial, i.e. one of the shares was not decrypted and checked.
+    #[error("the recovered key does not match the public polynomial")]
+    InvalidKey,
+    /// InvalidSecret is raised when the secret cannot be decrypted with the
+    /// recovered key.
+    #[error("could not decrypt the secret")]
+    InvalidSecret,
+}
@@ -0,0 +1,62 @@
+//! Structured events emitted while running a DKG or a resharing.
+//!
+//! When the `tracing` feature is enabled, the events are recorded with the
+//! [`tracing`](https://docs.rs/tracing) crate under the `dkg_core` target, and
+//! all of them carry the index of the participant emitting them in the `idx`
+//! field. Without the feature, they compile to nothing.
+#![cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+
+use crate::primitives::types::{DKGReport, ExclusionReason};
+use threshold_bls::poly::Idx;
+
+#[cfg(feature = "tracing")]
+const TARGET: &str = "dkg_core";
+
+/// The participant starts processing the given phase
+pub fn phase(idx: Idx, phase: &'static str) {
+    #[cfg(feature = "tracing")]
+    tracing::info!(target: TARGET, idx, phase, "entering phase");
+}
+
+/// The share of the dealer was decrypted and matches its public polynomial
+pub fn share_accepted(idx: Idx, dealer: Idx) {
+    #[cfg(feature = "tracing")]
+    tracing::debug!(target: TARGET, idx, dealer, "share accepted");
+}
+
+/// The bundle of the dealer did not provide a valid share
+pub fn share_rejected(idx: Idx, dealer: Idx, reason: &ExclusionReason) {
+    #[cfg(feature = "tracing")]
+    tracing::warn!(target: TARGET, idx, dealer, ?reason, "share rejected");
+}
+
+/// The participant files a complaint against the dealer
+pub fn complaint(idx: Idx, dealer: Idx) {
+    #[cfg(feature = "tracing")]
+    tracing::info!(target: TARGET, idx, dealer, "complaint filed");
+}
+
+/// A justification of the dealer for the share of `share_idx` was checked
+pub fn justification(idx: Idx, dealer: Idx, share_idx: Idx, valid: bool) {
+    #[cfg(feature = "tracing")]
+    tracing::info!(
+        target: TARGET,
+        idx,
+        dealer,
+        share_idx,
+        valid,
+        "justification verified"
+    );
+}
+
+/// The protocol finished with the given qualified participants
+pub fn finished(idx: Idx, qual: &[Idx], report: &DKGReport) {
+    #[cfg(feature = "tracing")]
+    tracing::info!(target: TARGET, idx, ?qual, ?report, "dkg finished");
+}
+
+/// The protocol was aborted because too few participants were qualified
+pub fn aborted(idx: Idx, report: &DKGReport) {
+    #[cfg(feature = "tracing")]
+    tracing::error!(target: TARGET, idx, ?report, "dkg aborted");
+}
@@ -1,17 +1,28 @@
 use super::{default_threshold, minimum_threshold, DKGError, DKGResult};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
-use threshold_bls::{group::Curve, poly::Idx};
+use std::{collections::HashSet, ops::Range};
+use threshold_bls::{
+    group::{Curve, Element, Scalar},
+    poly::Idx,
+};
 
 /// Node is a participant in the DKG protocol. In a DKG protocol, each
 /// participant must be identified both by an index and a public key. At the end
-/// of the protocol, if sucessful, the index is used to verify the validity of
-/// the share this node holds.
+/// of the protocol, if sucessful, the node holds one share for each of the
+/// share indices it owns, which are used to verify the validity of the shares.
+/// A node owns a single share index, equal to its index, unless it is
+/// weighted.
 #[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
-pub struct Node<C: Curve>(Idx, C::Point);
+pub struct Node<C: Curve>(Idx, C::Point, Range<Idx>);
 
 impl<C: Curve> Node<C> {
     pub fn new(index: Idx, public: C::Point) -> Self {
-        Self(index, public)
+        Self(index, public, index..index + 1)
+    }
+
+    /// Creates a node which owns the provided consecutive share indices
+    pub fn new_weighted(index: Idx, public: C::Point, shares: Range<Idx>) -> Self {
+        Self(index, public, shares)
     }
 }
 
@@ -25,6 +36,16 @@ impl<C: Curve> Node<C> {
     pub fn key(&self) -> &C::Point {
         &self.1
     }
+
+    /// Returns the share indices owned by the node
+    pub fn shares(&self) -> Range<Idx> {
+        self.2.clone()
+    }
+
+    /// Returns the number of share indices owned by the node
+    pub fn weight(&self) -> usize {
+        self.2.len()
+    }
 }
 
 /// A Group is a collection of Nodes with an associated threshold. A DKG scheme
@@ -32,12 +53,15 @@ impl<C: Curve> Node<C> {
 /// new group that contains members that succesfully ran the protocol. When
 /// creating a new group using the `from()` or `from_list()`method, the module
 /// sets the threshold to the output of `default_threshold()`.
+///
+/// The threshold is a number of shares: in a weighted group, where nodes own
+/// several share indices, it is compared to the total weight of the nodes.
 #[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
 #[serde(bound = "C::Scalar: DeserializeOwned")]
 pub struct Group<C: Curve> {
     /// The vector of nodes in the group
     pub nodes: Vec<Node<C>>,
-    /// The minimum number of nodes required to participate in the DKG for this group
+    /// The minimum number of shares required to participate in the DKG for this group
     pub threshold: usize,
 }
 
@@ -46,27 +70,90 @@ where
     C: Curve,
 {
     /// Converts a vector of nodes to a group with the default threshold (51%)
+    ///
+    /// The nodes are not validated here, the DKG checks them with `validate`
+    /// when it starts.
     pub fn from_list(nodes: Vec<Node<C>>) -> Group<C> {
-        let l = nodes.len();
+        let weight = nodes.iter().map(|n| n.weight()).sum();
         Self {
             nodes,
-            threshold: default_threshold(l),
+            threshold: default_threshold(weight),
         }
     }
 
     /// Creates a new group from the provided vector of nodes and threshold.
     ///
-    /// Valid thresholds are `>= 51% * nodes.len()` and `<= 100% * nodes.len()`
+    /// Valid thresholds are `>= 51% * weight` and `<= 100% * weight`, where
+    /// the weight is the number of nodes in an unweighted group. The nodes
+    /// are checked with `validate`.
     pub fn new(nodes: Vec<Node<C>>, threshold: usize) -> DKGResult<Group<C>> {
-        let minimum = minimum_threshold(nodes.len());
-        let maximum = nodes.len();
+        let weight = nodes.iter().map(|n| n.weight()).sum();
+        let minimum = minimum_threshold(weight);
+        let maximum = weight;
 
         // reject invalid thresholds
         if threshold < minimum || threshold > maximum {
             return Err(DKGError::InvalidThreshold(threshold, minimum, maximum));
         }
 
-        Ok(Self { nodes, threshold })
+        let group = Self { nodes, threshold };
+        group.validate()?;
+        Ok(group)
+    }
+
+    /// Checks that the threshold is between 1 and the weight of the group,
+    /// that the nodes have distinct indices and public keys, that every node
+    /// owns at least one share index and no share index is owned by several
+    /// nodes, and that every public key is a valid element of the prime-order
+    /// subgroup other than the identity.
+    pub fn validate(&self) -> DKGResult<()> {
+        let weight = self.weight();
+        if self.threshold == 0 || self.threshold > weight {
+            return Err(DKGError::InvalidThreshold(self.threshold, 1, weight));
+        }
+
+        let mut indices = HashSet::new();
+        let mut shares = HashSet::new();
+        let mut keys = HashSet::new();
+        for node in &self.nodes {
+            let id = node.id();
+            if !indices.insert(id) {
+                return Err(DKGError::DuplicateIndex(id));
+            }
+            if node.weight() == 0 {
+                return Err(DKGError::ZeroWeight(id));
+            }
+            if let Some(idx) = node.shares().find(|idx| !shares.insert(*idx)) {
+                return Err(DKGError::DuplicateShareIndex(idx));
+            }
+            if node.key() == &C::Point::zero() || !in_prime_order_subgroup::<C>(node.key()) {
+                return Err(DKGError::InvalidPublicKey(id));
+            }
+            // points are compared through their canonical encoding
+            if !keys.insert(bincode::serialize(node.key())?) {
+                return Err(DKGError::DuplicatePublicKey(id));
+            }
+        }
+        Ok(())
+    }
+
+    /// Creates a weighted group from the provided public keys and weights.
+    /// The nodes are indexed in order, and each of them owns as many
+    /// consecutive share indices as its weight. The share indices start from
+    /// 0 and do not match the node indices: with the weights 2 and 1, the
+    /// node 0 owns the shares 0 and 1, and the node 1 owns the share 2.
+    pub fn new_weighted(list: Vec<(C::Point, usize)>, threshold: usize) -> DKGResult<Group<C>> {
+        let mut next = 0;
+        let nodes = list
+            .into_iter()
+            .enumerate()
+            .map(|(i, (public, weight))| {
+                let shares = next..next + weight as Idx;
+                next = shares.end;
+                Node::new_weighted(i as Idx, public, shares)
+            })
+            .collect();
+        Self::new(nodes, threshold)
     }
 
     /// Returns the number of nodes in the group
@@ -74,6 +161,41 @@ where
         self.nodes.len()
     }
 
+    /// Returns the indices of the nodes, which may not be contiguous
+    pub fn indices(&self) -> Vec<Idx> {
+        self.nodes.iter().map(|n| n.id()).collect()
+    }
+
+    /// Returns the total number of share indices owned by the nodes
+    pub fn weight(&self) -> usize {
+        self.nodes.iter().map(|n| n.weight()).sum()
+    }
+
+    /// Returns the total weight of the nodes with the provided indices
+    pub fn weight_of<'a>(&self, indices: impl IntoIterator<Item = &'a Idx>) -> usize {
+        indices
+            .into_iter()
+            .filter_map(|&idx| self.node(idx))
+            .map(|n| n.weight())
+            .sum()
+    }
+
+    /// Returns the unweighted group with one node for each share index, which
+    /// is indexed by the share index and has the key of the node owning it.
+    /// The nodes of a weighted node share the same key, so the expanded group
+    /// does not pass `validate`.
+    pub fn expand(&self) -> Group<C> {
+        let nodes = self
+            .nodes
+            .iter()
+            .flat_map(|n| n.shares().map(move |idx| Node::new(idx, n.key().clone())))
+            .collect();
+        Self {
+            nodes,
+            threshold: self.threshold,
+        }
+    }
+
     /// Checks if the group is empty
     pub fn is_empty(&self) -> bool {
         self.nodes.is_empty()
@@ -87,21 +209,139 @@ where
     pub fn contains_index(&self, idx: Idx) -> bool {
         self.nodes.iter().any(|n| n.0 == idx)
     }
+
+    /// Gets the node with the provided index
+    pub fn node(&self, idx: Idx) -> Option<&Node<C>> {
+        self.nodes.iter().find(|n| n.0 == idx)
+    }
+}
+
+// checks that `r * point` is the identity, where r is the order of the scalar
+// field. Since r is zero as a scalar, this is computed as `(r - 1) * point +
+// point`, with `r - 1` being the scalar -1.
+fn in_prime_order_subgroup<C: Curve>(point: &C::Point) -> bool {
+    let mut minus_one = C::Scalar::one();
+    minus_one.negate();
+    let mut res = point.clone();
+    res.mul(&minus_one);
+    res.add(point);
+    res == C::Point::zero()
 }
 
 impl<C> From<Vec<C::Point>> for Group<C>
 where
     C: Curve,
 {
+    /// Creates a group with the default threshold from a list of public keys
+    ///
+    /// Like `from_list`, the keys are not validated here, the DKG checks them
+    /// with `validate` when it starts.
     fn from(list: Vec<C::Point>) -> Self {
-        let thr = default_threshold(list.len());
-
         let nodes = list
             .into_iter()
             .enumerate()
             .map(|(i, public)| Node::new(i as Idx, public))
             .collect();
 
-        Self::new(nodes, thr).expect("threshold should be good here")
+        Self::from_list(nodes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::thread_rng;
+    use threshold_bls::curve::bls12377::{G1Curve as BCurve, G1};
+
+    fn keys(n: usize) -> Vec<G1> {
+        (0..n).map(|_| G1::rand(&mut thread_rng())).collect()
+    }
+
+    fn nodes(keys: &[G1], indices: &[Idx]) -> Vec<Node<BCurve>> {
+        keys.iter()
+            .zip(indices)
+            .map(|(key, &idx)| Node::new(idx, key.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn valid_group() {
+        let keys = keys(3);
+        let group = Group::<BCurve>::new(nodes(&keys, &[0, 3, 7]), 2).unwrap();
+        assert_eq!(group.indices(), vec![0, 3, 7]);
+    }
+
+    #[test]
+    fn duplicate_index() {
+        let keys = keys(3);
+        let err = Group::<BCurve>::new(nodes(&keys, &[0, 1, 1]), 2).unwrap_err();
+        assert!(matches!(err, DKGError::DuplicateIndex(1)));
+    }
+
+    #[test]
+    fn duplicate_key() {
+        let mut keys = keys(3);
+        keys[2] = keys[0].clone();
+        let err = Group::<BCurve>::new(nodes(&keys, &[0, 1, 2]), 2).unwrap_err();
+        assert!(matches!(err, DKGError::DuplicatePublicKey(2)));
+
+        // groups which skipped the validation are rejected by the DKG
+        let group = Group::<BCurve>::from_list(nodes(&keys, &[0, 1, 2]));
+        let session = crate::primitives::types::SessionId::new(&group, 0, &[]);
+        let private = <BCurve as Curve>::Scalar::rand(&mut thread_rng());
+        let err = crate::primitives::joint_feldman::DKG::new(private, group, session);
+        assert!(matches!(err, Err(DKGError::DuplicatePublicKey(2))));
+    }
+
+    #[test]
+    fn identity_key() {
+        let mut keys = keys(3);
+        keys[1] = G1::zero();
+        let err = Group::<BCurve>::new(nodes(&keys, &[0, 1, 2]), 2).unwrap_err();
+        assert!(matches!(err, DKGError::InvalidPublicKey(1)));
+
+        // converting a list of keys does not validate them
+        let group = Group::<BCurve>::from(keys);
+        assert_eq!(group.threshold, default_threshold(3));
+        let err = group.validate().unwrap_err();
+        assert!(matches!(err, DKGError::InvalidPublicKey(1)));
+    }
+
+    #[test]
+    fn overlapping_shares() {
+        let keys = keys(2);
+        let nodes = vec![
+            Node::<BCurve>::new_weighted(0, keys[0].clone(), 0..2),
+            Node::new_weighted(1, keys[1].clone(), 1..3),
+        ];
+        let err = Group::new(nodes, 3).unwrap_err();
+        assert!(matches!(err, DKGError::DuplicateShareIndex(1)));
+    }
+
+    #[test]
+    fn invalid_threshold_and_weight() {
+        let keys = keys(3);
+        let mut group = Group::<BCurve>::from_list(nodes(&keys, &[0, 1, 2]));
+        group.threshold = 0;
+        let err = group.validate().unwrap_err();
+        assert!(matches!(err, DKGError::InvalidThreshold(0, 1, 3)));
+        group.threshold = 4;
+        let err = group.validate().unwrap_err();
+        assert!(matches!(err, DKGError::InvalidThreshold(4, 1, 3)));
+
+        let nodes = vec![
+            Node::<BCurve>::new_weighted(0, keys[0].clone(), 0..2),
+            Node::new_weighted(1, keys[1].clone(), 2..2),
+            Node::new_weighted(2, keys[2].clone(), 2..3),
+        ];
+        let err = Group::new(nodes, 2).unwrap_err();
+        assert!(matches!(err, DKGError::ZeroWeight(1)));
+    }
+
+    #[test]
+    fn subgroup() {
+        let mut key = G1::one();
+        key.mul(&<BCurve as Curve>::Scalar::rand(&mut thread_rng()));
+        assert!(in_prime_order_subgroup::<BCurve>(&key));
     }
 }
@@ -3,6 +3,7 @@
 //! The protocol runs at minimum in two phases and at most in three phases.
 use super::common::*;
 use crate::primitives::{
+    events,
     group::Group,
     phases::{Phase0, Phase1, Phase2, Phase3},
     status::{Status, StatusMatrix},
@@ -18,7 +19,7 @@ use threshold_bls::{
 
 use rand_core::RngCore;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
-use std::{cell::RefCell, collections::HashMap, fmt::Debug};
+use std::{cell::RefCell, collections::BTreeMap, fmt::Debug, ops::Range};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(bound = "C::Scalar: DeserializeOwned")]
@@ -27,16 +28,12 @@ struct DKGInfo<C: Curve> {
     public_key: C::Point,
     index: Idx,
     group: Group<C>,
+    session: SessionId,
     secret: Poly<C::Scalar>,
     public: Poly<C::Point>,
 }
 
 impl<C: Curve> DKGInfo<C> {
-    /// Returns the number of nodes participating in the group for this DKG
-    fn n(&self) -> usize {
-        self.group.len()
-    }
-
     /// Returns the threshold of the group for this DKG
     fn thr(&self) -> usize {
         self.group.threshold
@@ -59,21 +56,42 @@ pub struct DKG<C: Curve> {
 }
 
 impl<C: Curve> DKG<C> {
-    /// Creates a new DKG instance from the provided private key and group.
+    /// Creates a new DKG instance from the provided private key, group and
+    /// session. Bundles which do not belong to the session are ignored.
     ///
     /// The private key must be part of the group, otherwise this will return an error.
-    pub fn new(private_key: C::Scalar, group: Group<C>) -> Result<DKG<C>, DKGError> {
+    pub fn new(
+        private_key: C::Scalar,
+        group: Group<C>,
+        session: SessionId,
+    ) -> Result<DKG<C>, DKGError> {
         use rand::prelude::*;
-        Self::new_rand(private_key, group, &mut thread_rng())
+        Self::new_rand(private_key, group, session, &mut thread_rng())
     }
 
-    /// Creates a new DKG instance from the provided private key, group and RNG.
+    /// Creates a new DKG instance from the provided private key, group, session
+    /// and RNG.
     ///
     /// The private key must be part of the group, otherwise this will return an error.
     pub fn new_rand<R: RngCore>(
         private_key: C::Scalar,
         group: Group<C>,
+        session: SessionId,
         rng: &mut R,
+    ) -> Result<DKG<C>, DKGError> {
+        // the threshold is checked before generating the polynomial
+        group.validate()?;
+        // Generate a secret polynomial
+        let secret = PrivatePoly::<C>::new_from(group.threshold - 1, rng);
+        Self::new_with_secret(private_key, group, session, secret)
+    }
+
+    /// Creates a new DKG instance which deals the provided secret polynomial.
+    pub(crate) fn new_with_secret(
+        private_key: C::Scalar,
+        group: Group<C>,
+        session: SessionId,
+        secret: PrivatePoly<C>,
     ) -> Result<DKG<C>, DKGError> {
         // get the public key
         let mut public_key = C::Point::one();
@@ -84,13 +102,16 @@ impl<C: Curve> DKG<C> {
             return Err(DKGError::PrivateKeyInvalid);
         }
 
+        // groups which were not built with `Group::new` (e.g. deserialized)
+        // may contain invalid or duplicate keys
+        group.validate()?;
+
         // check if the public key is part of the group
         let index = group
             .index(&public_key)
             .ok_or(DKGError::PublicKeyNotFound)?;
 
-        // Generate a secret polynomial and commit to it
-        let secret = PrivatePoly::<C>::new_from(group.threshold - 1, rng);
+        // commit to the secret polynomial
         let public = secret.commit::<C::Point>();
 
         let info = DKGInfo {
@@ -98,12 +119,27 @@ impl<C: Curve> DKG<C> {
             public_key,
             index,
             group,
+            session,
             secret,
             public,
         };
 
         Ok(DKG { info })
     }
+
+    /// Returns the index of this participant in the group
+    pub(crate) fn index(&self) -> Idx {
+        self.info.index
+    }
+
+    /// Returns the share indices of this participant in the group
+    pub(crate) fn share_indices(&self) -> Range<Idx> {
+        self.info
+            .group
+            .node(self.info.index)
+            .expect("we are part of the group")
+            .shares()
+    }
 }
 
 impl<C: Curve> Phase0<C> for DKG<C> {
@@ -114,8 +150,10 @@ impl<C: Curve> Phase0<C> for DKG<C> {
     fn encrypt_shares<R: RngCore>(
         self,
         rng: &mut R,
-    ) -> DKGResult<(DKGWaitingShare<C>, Option<BundledShares<C>>)> {
+    ) -> DKGResult<(DKGWaitingShare<C>, Vec<BundledShares<C>>)> {
+        events::phase(self.info.index, "encrypt_shares");
         let bundle = create_share_bundle(
+            &self.info.session,
             self.info.index,
             &self.info.secret,
             &self.info.public,
@@ -123,7 +161,7 @@ impl<C: Curve> Phase0<C> for DKG<C> {
             rng,
         )?;
         let dw = DKGWaitingShare { info: self.info };
-        Ok((dw, Some(bundle)))
+        Ok((dw, vec![bundle]))
     }
 }
 
@@ -137,6 +175,13 @@ pub struct DKGWaitingShare<C: Curve> {
     info: DKGInfo<C>,
 }
 
+impl<C: Curve> DKGWaitingShare<C> {
+    /// Returns the session of this DKG
+    pub(crate) fn session(&self) -> &SessionId {
+        &self.info.session
+    }
+}
+
 impl<C: Curve> Phase1<C> for DKGWaitingShare<C> {
     type Next = DKGWaitingResponse<C>;
     #[allow(unused_assignments)]
@@ -151,6 +196,7 @@ impl<C: Curve> Phase1<C> for DKGWaitingShare<C> {
     /// - invalid encryption
     /// - invalid length of public polynomial
     /// - invalid share w.r.t. public polynomial
+    /// - conflicting bundles from the same dealer
     fn process_shares(
         self,
         bundles: &[BundledShares<C>],
@@ -159,13 +205,19 @@ impl<C: Curve> Phase1<C> for DKGWaitingShare<C> {
         publish_all = false;
         let thr = self.info.thr();
         let my_idx = self.info.index;
-        let (shares, publics, mut statuses) = process_shares_get_all(
+        events::phase(my_idx, "process_shares");
+        let mut equivocators = Equivocators::default();
+        let mut report = DKGReport::default();
+        let (mut shares, mut publics, mut statuses) = process_shares_get_all(
             &self.info.group,
             &self.info.group,
-            Some(my_idx),
+            &[my_idx],
             my_idx,
             &self.info.private_key,
+            &self.info.session,
             bundles,
+            &mut equivocators,
+            &mut report,
         )?;
 
         // in DKG every dealer is also a share holder, we assume that a dealer
@@ -174,25 +226,35 @@ impl<C: Curve> Phase1<C> for DKGWaitingShare<C> {
             statuses.set(n.id(), n.id(), Status::Success);
         }
 
-        // we check with `thr - 1` because we already have our shares
-        if shares.len() < thr - 1 {
+        // we add our own weight because we already have our shares
+        let me = self.info.group.node(my_idx).expect("we are in the group");
+        let valid = self.info.group.weight_of(shares.keys());
+        if valid + me.weight() < thr {
             // that means the threat model is not respected since there should
             // be at least a threshold of honest shares
-            return Err(DKGError::NotEnoughValidShares(shares.len(), thr));
+            events::aborted(my_idx, &report);
+            return Err(DKGError::NotEnoughValidShares(valid, thr, report));
         }
 
-        // The user's secret share is the sum of all received shares (remember:
-        // each share is an evaluation of a participant's private polynomial at
-        // our index)
-        let mut fshare = self.info.secret.eval(self.info.index).value;
-        // The public key polynomial is the sum of all shared polynomials
-        let mut fpub = self.info.public.clone();
-        shares.iter().for_each(|(&dealer_idx, share)| {
-            fpub.add(publics.get(&dealer_idx).unwrap());
-            fshare.add(share);
+        // we register our own shares into the mix, and our public polynomial if
+        // our bundle made it to the board, like for the other dealers
+        shares.insert(my_idx, eval_shares(me, &self.info.secret));
+        let published = bundles.iter().any(|b| {
+            b.session == self.info.session && b.dealer_idx == my_idx && b.public == self.info.public
         });
-        let bundle = compute_bundle_response(my_idx, &statuses, publish_all);
-        let new_dkg = DKGWaitingResponse::new(self.info, fshare, fpub, statuses, publics);
+        if published {
+            publics.insert(my_idx, self.info.public.clone());
+        }
+
+        let bundle = compute_bundle_response(&self.info.session, my_idx, &statuses, publish_all);
+        let new_dkg = DKGWaitingResponse {
+            info: self.info,
+            shares,
+            statuses,
+            publics,
+            equivocators,
+            report,
+        };
 
         Ok((new_dkg, bundle))
     }
@@ -205,28 +267,13 @@ impl<C: Curve> Phase1<C> for DKGWaitingShare<C> {
 /// if there are complaints.
 pub struct DKGWaitingResponse<C: Curve> {
     info: DKGInfo<C>,
-    dist_share: C::Scalar,
-    dist_pub: PublicPoly<C>,
+    // the valid shares received from each dealer, including our own
+    shares: ShareInfo<C>,
     statuses: StatusMatrix,
     publics: PublicInfo<C>,
-}
-
-impl<C: Curve> DKGWaitingResponse<C> {
-    fn new(
-        info: DKGInfo<C>,
-        dist_share: C::Scalar,
-        dist_pub: PublicPoly<C>,
-        statuses: StatusMatrix,
-        publics: PublicInfo<C>,
-    ) -> Self {
-        Self {
-            info,
-            dist_share,
-            dist_pub,
-            statuses,
-            publics,
-        }
-    }
+    equivocators: Equivocators,
+    // the dealers whose shares we rejected during the first phase
+    report: DKGReport,
 }
 
 impl<C: Curve> Phase2<C> for DKGWaitingResponse<C> {
@@ -240,48 +287,63 @@ impl<C: Curve> Phase2<C> for DKGWaitingResponse<C> {
     fn process_responses(
         self,
         responses: &[BundledResponses],
-    ) -> Result<DKGOutput<C>, DKGResult<(Self::Next, Option<BundledJustification<C>>)>> {
+    ) -> Result<DKGOutput<C>, DKGResult<(Self::Next, Vec<BundledJustification<C>>)>> {
         let info = self.info;
+        events::phase(info.index, "process_responses");
         let mut statuses = self.statuses;
+        let mut equivocators = self.equivocators;
         set_statuses(
             info.index,
+            &info.session,
             &info.group,
             &info.group,
             &mut statuses,
             responses,
+            &mut equivocators,
         );
 
         // find out if justifications are required
-        // if there is a least one participant that issued one complaint
-        let justifications_required = info.group.nodes.iter().any(|n| !statuses.all_true(n.id()));
+        // if there is a least one participant that issued one complaint, or
+        // if a participant must be disqualified for equivocating
+        let justifications_required =
+            info.group.nodes.iter().any(|n| !statuses.all_true(n.id())) || !equivocators.is_empty();
 
         if justifications_required {
-            let bundled_justifications =
-                get_justification(info.index, &info.secret, &info.public, &statuses);
+            let bundled_justifications = get_justification(
+                &info.session,
+                info.index,
+                &info.group,
+                &info.secret,
+                &info.public,
+                &statuses,
+            )
+            .into_iter()
+            .collect();
             let dkg = DKGWaitingJustification {
                 info,
-                dist_share: self.dist_share,
-                dist_pub: self.dist_pub,
+                shares: self.shares,
                 statuses: RefCell::new(statuses),
                 publics: self.publics,
+                equivocators,
+                report: self.report,
             };
 
             return Err(Ok((dkg, bundled_justifications)));
         }
 
         // bingo ! Returns the final share now and stop the protocol
-        let share = Share {
-            index: info.index,
-            private: self.dist_share,
-        };
-
-        Ok(DKGOutput {
-            // everybody is qualified in this case since there is no
-            // complaint at all
-            qual: info.group,
-            public: self.dist_pub,
-            share,
-        })
+        // everybody is qualified in this case since there is no complaint at
+        // all
+        let qual = info.group.nodes.iter().map(|n| n.id()).collect::<Vec<_>>();
+        compute_output(
+            info,
+            &self.shares,
+            &self.publics,
+            &qual,
+            equivocators,
+            self.report,
+        )
+        .map_err(Err)
     }
 }
 
@@ -293,11 +355,14 @@ pub struct DKGWaitingJustification<C: Curve> {
     // TODO: transform that into one info variable that gets default value for
     // missing parts depending in the round of the protocol.
     info: DKGInfo<C>,
-    dist_share: C::Scalar,
-    dist_pub: PublicPoly<C>,
+    // the valid shares received from each dealer, including our own
+    shares: ShareInfo<C>,
     // guaranteed to be of the right size (n)
     statuses: RefCell<StatusMatrix>,
-    publics: HashMap<Idx, PublicPoly<C>>,
+    publics: PublicInfo<C>,
+    equivocators: Equivocators,
+    // the dealers whose shares we rejected during the first phase
+    report: DKGReport,
 }
 
 impl<C> Phase3<C> for DKGWaitingJustification<C>
@@ -309,74 +374,170 @@ where
     /// - a justification was required for the given share (no-op)
     /// - share corresponds to public polynomial received in the bundled shares during
     /// first period.
+    /// - the dealer did not publish conflicting bundles
     /// Return an output if `len(qual) > thr`
     fn process_justifications(
         self,
         justifs: &[BundledJustification<C>],
     ) -> Result<DKGOutput<C>, DKGError> {
-        // Calculate the share and public polynomial from the provided justifications
-        // (they will later be added to our existing share and public polynomial)
-        let mut add_share = C::Scalar::zero();
-        let mut add_public = PublicPoly::<C>::zero();
+        events::phase(self.info.index, "process_justifications");
+        let mut equivocators = self.equivocators;
         let valid_shares = internal_process_justifications(
             self.info.index,
+            &self.info.session,
+            &self.info.group,
             &self.info.group,
             &mut self.statuses.borrow_mut(),
             &self.publics,
             justifs,
+            &mut equivocators,
         );
+        let mut shares = self.shares;
+        shares.extend(valid_shares);
 
-        for (idx, share) in &valid_shares {
-            add_share.add(share);
-            // unwrap since internal_process_justi. gauarantees each share comes
-            // from a public polynomial we've seen in the first round.
-            add_public.add(self.publics.get(idx).unwrap());
-        }
-        // QUAL is the set of all entries in the matrix where all bits are set
+        // QUAL is the set of all entries in the matrix where all bits are set,
+        // without the participants which equivocated (in the DKG, every share
+        // holder is also a dealer)
         let statuses = self.statuses.borrow();
-        let qual_indices = (0..self.info.n())
-            .filter(|&dealer| statuses.all_true(dealer as Idx))
+        let qual_indices = self
+            .info
+            .group
+            .nodes
+            .iter()
+            .map(|n| n.id())
+            .filter(|&dealer| statuses.all_true(dealer))
+            .filter(|dealer| {
+                !equivocators.dealers.contains(dealer)
+                    && !equivocators.share_holders.contains(dealer)
+            })
             .collect::<Vec<_>>();
 
-        let thr = self.info.group.threshold;
-        if qual_indices.len() < thr {
+        let thr = self.info.thr();
+        let qual_weight = self.info.group.weight_of(&qual_indices);
+        if qual_weight < thr {
             // too many unanswered justifications, DKG abort !
-            return Err(DKGError::NotEnoughJustifications(qual_indices.len(), thr));
+            let report = final_report(self.report, &self.info.group, &qual_indices, &equivocators);
+            events::aborted(self.info.index, &report);
+            return Err(DKGError::NotEnoughJustifications(qual_weight, thr, report));
         }
 
-        // create a group out of the qualifying nodes
-        let qual_nodes = self
-            .info
-            .group
-            .nodes
-            .into_iter()
-            .filter(|n| qual_indices.contains(&(n.id() as usize)))
-            .collect();
-        let group = Group::<C>::new(qual_nodes, thr)?;
-
-        // add all good shares and public poly together
-        add_share.add(&self.dist_share);
-        add_public.add(&self.dist_pub);
-        let ds = Share {
-            index: self.info.index,
-            private: add_share,
-        };
+        compute_output(
+            self.info,
+            &shares,
+            &self.publics,
+            &qual_indices,
+            equivocators,
+            self.report,
+        )
+    }
+}
 
-        Ok(DKGOutput {
-            qual: group,
-            public: add_public,
-            share: ds,
-        })
+/// Returns the report of the participants which are not qualified. The
+/// reasons recorded during the first phase are only kept for the dealers which
+/// did not justify themselves afterwards.
+fn final_report<C: Curve>(
+    mut pending: DKGReport,
+    group: &Group<C>,
+    qual_indices: &[Idx],
+    equivocators: &Equivocators,
+) -> DKGReport {
+    pending.exclude_equivocations(equivocators);
+    let mut report = DKGReport {
+        share_holders: pending.share_holders,
+        ..Default::default()
+    };
+    for idx in group.nodes.iter().map(|n| n.id()) {
+        if qual_indices.contains(&idx) {
+            continue;
+        }
+        let reason = pending
+            .dealers
+            .remove(&idx)
+            .or_else(|| report.share_holders.get(&idx).cloned())
+            .unwrap_or(ExclusionReason::UnansweredComplaint);
+        report.exclude_dealer(idx, reason);
+    }
+    report
+}
+
+/// Computes the final share and public polynomial as the sum of the shares and
+/// public polynomials of the dealers which gave us valid shares, except the
+/// ones which equivocated.
+fn compute_output<C: Curve>(
+    info: DKGInfo<C>,
+    shares: &ShareInfo<C>,
+    publics: &PublicInfo<C>,
+    qual_indices: &[Idx],
+    equivocators: Equivocators,
+    report: DKGReport,
+) -> DKGResult<DKGOutput<C>> {
+    // The user's secret shares are the sums of all received shares (remember:
+    // each share is an evaluation of a participant's private polynomial at
+    // one of our share indices) and the public key polynomial is the sum of
+    // all shared polynomials.
+    let me = info.group.node(info.index).expect("we are in the group");
+    let mut shares_sum = vec![C::Scalar::zero(); me.weight()];
+    let mut public = PublicPoly::<C>::zero();
+    let mut transcript = BTreeMap::new();
+    for (idx, s) in shares {
+        if equivocators.dealers.contains(idx) {
+            continue;
+        }
+        // we hold our own public polynomial even if our bundle did not make it
+        // to the board
+        let p = if *idx == info.index {
+            &info.public
+        } else {
+            // the shares of the first phase and the justified ones were all
+            // checked against the dealer's public polynomial
+            publics
+                .get(idx)
+                .expect("a valid share has a public polynomial")
+        };
+        shares_sum.iter_mut().zip(s).for_each(|(sum, s)| sum.add(s));
+        public.add(p);
+        transcript.insert(*idx, p.clone());
     }
+    let shares = me
+        .shares()
+        .zip(shares_sum)
+        .map(|(index, private)| Share { index, private })
+        .collect();
+
+    let report = final_report(report, &info.group, qual_indices, &equivocators);
+    events::finished(info.index, qual_indices, &report);
+
+    // create a group out of the qualifying nodes
+    let thr = info.thr();
+    let qual_nodes = info
+        .group
+        .nodes
+        .into_iter()
+        .filter(|n| qual_indices.contains(&n.id()))
+        .collect();
+    let qual = Group::<C>::new(qual_nodes, thr)?;
+
+    Ok(DKGOutput {
+        qual,
+        public,
+        shares,
+        report,
+        transcript,
+    })
 }
 
 #[cfg(test)]
 pub mod tests {
     use super::*;
     use crate::primitives::{
-        common::tests::{check2, full_dkg, id_out, id_resp, invalid2, invalid_shares, setup_group},
+        common::tests::{
+            check2, full_dkg, id_out, id_resp, invalid2, invalid_shares, setup_group,
+            setup_weighted_group,
+        },
         default_threshold,
+        group::Node,
     };
+    use rand::thread_rng;
     use std::fmt::Debug;
     use threshold_bls::curve::bls12377::{G1Curve as BCurve, G1};
 
@@ -393,9 +554,10 @@ pub mod tests {
 
     fn setup_dkg<C: Curve>(n: usize) -> Vec<DKG<C>> {
         let (privs, group) = setup_group::<C>(n, default_threshold(n));
+        let session = SessionId::new(&group, 0, &[]);
         privs
             .into_iter()
-            .map(|p| DKG::new(p, group.clone()).unwrap())
+            .map(|p| DKG::new(p, group.clone(), session).unwrap())
             .collect::<Vec<_>>()
     }
 
@@ -415,7 +577,54 @@ pub mod tests {
     fn test_full_dkg() {
         let n = 5;
         let thr = default_threshold(n);
-        full_dkg(thr, setup_dkg::<BCurve>(n));
+        let (outputs, _) = full_dkg(thr, setup_dkg::<BCurve>(n));
+        assert!(outputs.iter().all(|out| out.report.is_empty()));
+    }
+
+    #[test]
+    fn sparse_indices() {
+        let n = 5;
+        let (privs, group) = setup_group::<BCurve>(n, default_threshold(n));
+        let nodes = group
+            .nodes
+            .iter()
+            .zip(&[0, 2, 3, 7, 9])
+            .map(|(n, &idx)| Node::<BCurve>::new(idx, n.key().clone()))
+            .collect();
+        let group = Group::new(nodes, group.threshold).unwrap();
+        let session = SessionId::new(&group, 0, &[]);
+        let dkgs = privs
+            .iter()
+            .map(|p| DKG::new(*p, group.clone(), session).unwrap())
+            .collect::<Vec<_>>();
+        invalid_shares(group.threshold, dkgs, invalid2, id_resp, check2, |outs| {
+            for out in &outs {
+                assert_eq!(out.qual, group);
+            }
+            outs
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn weighted_dkg() {
+        let thr = default_threshold(6);
+        let (privs, group) = setup_weighted_group::<BCurve>(&[1, 2, 3], thr);
+        let session = SessionId::new(&group, 0, &[]);
+        let dkgs = privs
+            .iter()
+            .map(|p| DKG::new(*p, group.clone(), session).unwrap())
+            .collect::<Vec<_>>();
+        let (outputs, _) = full_dkg(thr, dkgs);
+        for (out, node) in outputs.iter().zip(&group.nodes) {
+            let indices = out.shares.iter().map(|s| s.index);
+            assert!(indices.eq(node.shares()));
+        }
+
+        // the threshold is a number of shares, so the nodes of weight 1 and 3
+        // can not reach it together
+        assert_eq!(group.weight_of(&[0, 2]), thr - 1);
+        assert!(Group::new(group.nodes.clone(), 7).is_err());
     }
 
     #[test]
@@ -432,4 +641,191 @@ pub mod tests {
         )
         .unwrap();
     }
+
+    #[test]
+    fn reject_other_session() {
+        let n = 5;
+        let thr = default_threshold(n);
+        let (privs, group) = setup_group::<BCurve>(n, thr);
+        let dkgs = |epoch| {
+            let session = SessionId::new(&group, epoch, b"nonce");
+            privs
+                .iter()
+                .map(|p| DKG::new(*p, group.clone(), session).unwrap())
+                .collect::<Vec<_>>()
+        };
+
+        // the shares of a previous run with the same group
+        let old_shares = dkgs(0)
+            .into_iter()
+            .flat_map(|dkg| dkg.encrypt_shares(&mut thread_rng()).unwrap().1)
+            .collect::<Vec<_>>();
+
+        // replaying them in the new run is rejected
+        let dkg = dkgs(1).remove(0);
+        let (dkg, _) = dkg.encrypt_shares(&mut thread_rng()).unwrap();
+        let err = dkg.process_shares(&old_shares, false).unwrap_err();
+        match err {
+            DKGError::NotEnoughValidShares(0, _, report) => {
+                assert_eq!(report.dealers.len(), n - 1);
+                assert!(report
+                    .dealers
+                    .values()
+                    .all(|reason| reason == &ExclusionReason::MissingBundle));
+            }
+            err => panic!("unexpected error {:?}", err),
+        }
+
+        // even when relabeled with the new session, since the encryption of the
+        // shares is bound to the session
+        let new_session = SessionId::new(&group, 1, b"nonce");
+        let relabeled = old_shares
+            .into_iter()
+            .map(|mut b| {
+                b.session = new_session;
+                b
+            })
+            .collect::<Vec<_>>();
+        let dkg = dkgs(1).remove(0);
+        let (dkg, _) = dkg.encrypt_shares(&mut thread_rng()).unwrap();
+        let err = dkg.process_shares(&relabeled, false).unwrap_err();
+        match err {
+            DKGError::NotEnoughValidShares(0, _, report) => {
+                assert_eq!(report.dealers.len(), n - 1);
+                assert!(report
+                    .dealers
+                    .values()
+                    .all(|reason| reason == &ExclusionReason::InvalidCiphertext));
+            }
+            err => panic!("unexpected error {:?}", err),
+        }
+    }
+
+    #[test]
+    fn equivocating_dealer() {
+        let n = 5;
+        let thr = default_threshold(n);
+        let (privs, group) = setup_group::<BCurve>(n, thr);
+        let session = SessionId::new(&group, 0, &[]);
+        let dkgs = privs
+            .into_iter()
+            .map(|p| DKG::new(p, group.clone(), session).unwrap())
+            .collect::<Vec<_>>();
+
+        invalid_shares(
+            thr,
+            dkgs,
+            // the first dealer publishes shares of a second polynomial
+            |mut s| {
+                let secret =
+                    Poly::<<BCurve as Curve>::Scalar>::new_from(thr - 1, &mut thread_rng());
+                let public = secret.commit::<G1>();
+                let other =
+                    create_share_bundle(&session, 0, &secret, &public, &group, &mut thread_rng())
+                        .unwrap();
+                s.push(other);
+                s
+            },
+            id_resp,
+            |j| j,
+            |outs| {
+                for out in &outs {
+                    assert!(!out.qual.contains_index(0));
+                    assert_eq!(out.report.dealers[&0], ExclusionReason::Equivocation);
+                    assert!(!out.transcript.contains_key(&0));
+                }
+                // the disqualified dealer's share is not part of the new key
+                outs.into_iter()
+                    .filter(|o| o.shares[0].index != 0)
+                    .collect()
+            },
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn equivocating_share_holder() {
+        let n = 5;
+        let thr = default_threshold(n);
+        let (privs, group) = setup_group::<BCurve>(n, thr);
+        let session = SessionId::new(&group, 0, &[]);
+        let dkgs = privs
+            .into_iter()
+            .map(|p| DKG::new(p, group.clone(), session).unwrap())
+            .collect::<Vec<_>>();
+
+        let complaint = |dealer_idx| BundledResponses {
+            session,
+            share_idx: 4,
+            responses: vec![Response {
+                dealer_idx,
+                status: Status::Complaint,
+            }],
+        };
+        invalid_shares(
+            thr,
+            dkgs,
+            |s| s,
+            // the last share holder complains about different dealers
+            |mut r| {
+                r.push(complaint(1));
+                r.push(complaint(2));
+                r
+            },
+            |j| {
+                // the conflicting complaints are ignored
+                assert!(j.is_empty());
+                j
+            },
+            |outs| {
+                for out in &outs {
+                    assert_eq!(out.qual.len(), 4);
+                    assert!(!out.qual.contains_index(4));
+                    assert_eq!(out.report.share_holders[&4], ExclusionReason::Equivocation);
+                }
+                outs.into_iter()
+                    .filter(|o| o.shares[0].index != 4)
+                    .collect()
+            },
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn report_excluded_dealers() {
+        let n = 7;
+        let thr = default_threshold(n);
+        invalid_shares(
+            thr,
+            setup_dkg::<BCurve>(n),
+            |mut s| {
+                // the first dealer does not publish its shares
+                s.remove(0);
+                // the second one publishes a polynomial of the wrong degree
+                let secret = Poly::<<BCurve as Curve>::Scalar>::new_from(thr, &mut thread_rng());
+                s[0].public = secret.commit::<G1>();
+                s
+            },
+            id_resp,
+            |j| j,
+            |outs| {
+                let outs = outs
+                    .into_iter()
+                    .filter(|o| o.shares[0].index > 1)
+                    .collect::<Vec<_>>();
+                for out in &outs {
+                    assert_eq!(out.qual.len(), n - 2);
+                    assert_eq!(out.report.dealers.len(), 2);
+                    assert_eq!(out.report.dealers[&0], ExclusionReason::MissingBundle);
+                    assert_eq!(
+                        out.report.dealers[&1],
+                        ExclusionReason::InvalidPolynomialDegree(thr, thr - 1)
+                    );
+                    assert!(out.report.share_holders.is_empty());
+                }
+                outs
+            },
+        )
+        .unwrap();
+    }
 }
@@ -8,6 +8,10 @@ pub use phases::*;
 pub(crate) mod types;
 pub use types::*;
 
+/// Signed envelopes binding bundles to the long-term key of their sender
+pub(crate) mod auth;
+pub use auth::{verify_bundles, Bundle, Signature, Signed};
+
 /// 2D binary array utilities for tracking successful (or not) participation in the DKG
 pub(crate) mod status;
 
@@ -15,10 +19,25 @@ pub mod joint_feldman;
 
 pub mod resharing;
 
+pub mod refresh;
+
+pub mod repair;
+
+pub mod certificate;
+
+pub mod chain;
+
+pub mod secret;
+
 mod common;
 
+/// Structured events emitted through `tracing` when the feature is enabled
+mod events;
+
 mod errors;
-pub use errors::{DKGError, DKGResult, ShareError};
+pub use errors::{
+    CertificateError, ChainError, DKGError, DKGResult, RepairError, SecretError, ShareError,
+};
 
 /// The minimum allowed threshold is 51%
 pub fn minimum_threshold(n: usize) -> usize {
@@ -15,10 +15,13 @@ use std::fmt::Debug;
 pub trait Phase0<C: Curve>: Clone + Debug + Serialize + for<'a> Deserialize<'a> {
     type Next: Phase1<C>;
 
+    /// Returns the bundles to publish: none if the node is not a dealer, and
+    /// a single one unless it reshares the shares of a weighted node, which
+    /// deals one bundle per share index.
     fn encrypt_shares<R: RngCore>(
         self,
         rng: &mut R,
-    ) -> DKGResult<(Self::Next, Option<BundledShares<C>>)>;
+    ) -> DKGResult<(Self::Next, Vec<BundledShares<C>>)>;
 }
 
 /// Phase1 is the trait abstracting the second step of a distributed key
@@ -42,7 +45,8 @@ pub trait Phase1<C: Curve>: Clone + Debug + Serialize + for<'a> Deserialize<'a>
 /// The return method of this trait is first the `DKGOutput` if the protocol can
 /// be finished already. If not, the call returns an error which either contains
 /// the next phase and potential justifications or a fatal error that makes this
-/// node unable to continue participating in the protocol.
+/// node unable to continue participating in the protocol. Like in `Phase0`, a
+/// dealer publishes one bundle of justifications per share index it deals.
 pub trait Phase2<C: Curve>: Clone + Debug + Serialize + for<'a> Deserialize<'a> {
     type Next: Phase3<C>;
 
@@ -50,13 +54,13 @@ pub trait Phase2<C: Curve>: Clone + Debug + Serialize + for<'a> Deserialize<'a>
     fn process_responses(
         self,
         responses: &[BundledResponses],
-    ) -> Result<DKGOutput<C>, DKGResult<(Self::Next, Option<BundledJustification<C>>)>>;
+    ) -> Result<DKGOutput<C>, DKGResult<(Self::Next, Vec<BundledJustification<C>>)>>;
 }
 
 /// Phase3 is the trait abstracting the final stage of a distributed key
 /// generation protocol. At this stage, the share holders process the potential
 /// justifications, and look if they can finish the protocol.
-pub trait Phase3<C: Curve>: Debug {
+pub trait Phase3<C: Curve>: Clone + Debug + Serialize + for<'a> Deserialize<'a> {
     fn process_justifications(
         self,
         justifs: &[BundledJustification<C>],
@@ -0,0 +1,409 @@
+//! Implements a proactive refresh of the shares of a group, from [Herzberg et al.](https://link.springer.com/content/pdf/10.1007/3-540-44750-4_27.pdf).
+//! The members of the group run a JF-DKG in which every dealer shares a
+//! polynomial whose free coefficient is zero. Each member then adds the sum of
+//! the shares it received to its current share, so that the shares change
+//! while the distributed public key stays the same.
+//!
+//! The refresh is cheaper than a resharing since the dealers and the share
+//! holders are the same nodes, and there is no need to interpolate the shares.
+//! Shares from different epochs can not be combined together, so an attacker
+//! needs to compromise a threshold of shares during the same epoch to recover
+//! the distributed private key. Each member must therefore delete its previous
+//! share once the refresh is over, and use a new `SessionId` (e.g. by
+//! increasing the epoch) for every refresh.
+use super::joint_feldman::*;
+use crate::primitives::{
+    events,
+    phases::{Phase0, Phase1, Phase2, Phase3},
+    types::*,
+    DKGError, DKGResult,
+};
+
+use threshold_bls::{
+    group::{Curve, Element},
+    poly::{Idx, PrivatePoly, PublicPoly},
+    sig::Share,
+};
+
+use rand_core::RngCore;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::fmt::Debug;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "C::Scalar: DeserializeOwned")]
+struct RefreshInfo<C: Curve> {
+    // the index of the member in the group
+    index: Idx,
+    // the shares being refreshed
+    shares: Vec<Share<C::Scalar>>,
+    // the distributed public polynomial of the group
+    public: PublicPoly<C>,
+    // the dealers which published a polynomial not committing to zero
+    invalid: Vec<Idx>,
+}
+
+impl<C: Curve> RefreshInfo<C> {
+    /// Adds the refreshing shares and polynomial to the current ones, and
+    /// checks that the distributed public key did not change.
+    fn refresh(self, mut out: DKGOutput<C>) -> DKGResult<DKGOutput<C>> {
+        let shares = self
+            .shares
+            .iter()
+            .zip(out.shares.iter())
+            .map(|(curr, refresh)| {
+                let mut private = curr.private.clone();
+                private.add(&refresh.private);
+                Share {
+                    index: curr.index,
+                    private,
+                }
+            })
+            .collect();
+        let mut public = self.public.clone();
+        public.add(&out.public);
+        if public.public_key() != self.public.public_key() {
+            return Err(DKGError::PublicKeyChanged);
+        }
+
+        self.report_invalid(&mut out.report);
+        Ok(DKGOutput {
+            shares,
+            public,
+            ..out
+        })
+    }
+
+    fn report_invalid(&self, report: &mut DKGReport) {
+        for idx in &self.invalid {
+            report.dealers.insert(*idx, ExclusionReason::InvalidRefresh);
+        }
+    }
+
+    fn map_err(&self, err: DKGError) -> DKGError {
+        match err {
+            DKGError::NotEnoughValidShares(got, required, mut report) => {
+                self.report_invalid(&mut report);
+                DKGError::NotEnoughValidShares(got, required, report)
+            }
+            DKGError::NotEnoughJustifications(got, required, mut report) => {
+                self.report_invalid(&mut report);
+                DKGError::NotEnoughJustifications(got, required, report)
+            }
+            err => err,
+        }
+    }
+}
+
+/// Refresh is the struct containing the logic to run a proactive refresh of
+/// the shares of a group. It has the same phases as the DKG, and its output
+/// contains the refreshed share, which replaces the previous one, and the new
+/// distributed public polynomial, which has the same public key as before.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "C::Scalar: DeserializeOwned")]
+pub struct Refresh<C: Curve> {
+    info: RefreshInfo<C>,
+    dkg: DKG<C>,
+}
+
+impl<C: Curve> Refresh<C> {
+    /// Creates a new refresh of the shares obtained from a previous DKG,
+    /// resharing or refresh. The refresh runs among the qualified nodes of
+    /// that output.
+    pub fn new_from_share(
+        private_key: C::Scalar,
+        curr_share: DKGOutput<C>,
+        session: SessionId,
+    ) -> DKGResult<Refresh<C>> {
+        use rand::prelude::*;
+        Self::new_from_share_rng(private_key, curr_share, session, &mut thread_rng())
+    }
+
+    pub fn new_from_share_rng<R: RngCore>(
+        private_key: C::Scalar,
+        curr_share: DKGOutput<C>,
+        session: SessionId,
+        rng: &mut R,
+    ) -> DKGResult<Refresh<C>> {
+        let group = curr_share.qual;
+        let shares = curr_share.shares;
+        let public = curr_share.public;
+        group.validate()?;
+        if public.degree() != group.threshold - 1
+            || !shares
+                .iter()
+                .all(|share| share_matches::<C>(share, &public))
+        {
+            return Err(DKGError::InvalidRefreshShare);
+        }
+
+        // generate a secret polynomial which shares zero
+        let mut secret = PrivatePoly::<C>::new_from(group.threshold - 1, rng);
+        secret.set(0, C::Scalar::zero());
+        let dkg = DKG::new_with_secret(private_key, group, session, secret)?;
+        // the shares must be the ones of this member in the group
+        let index = dkg.index();
+        if !dkg
+            .share_indices()
+            .eq(shares.iter().map(|share| share.index))
+        {
+            return Err(DKGError::InvalidRefreshShare);
+        }
+
+        let info = RefreshInfo {
+            index,
+            shares,
+            public,
+            invalid: Vec::new(),
+        };
+        Ok(Refresh { info, dkg })
+    }
+}
+
+// checks that the share is the evaluation of the private polynomial which is
+// committed to in the public polynomial
+fn share_matches<C: Curve>(share: &Share<C::Scalar>, public: &PublicPoly<C>) -> bool {
+    let mut commit = C::Point::one();
+    commit.mul(&share.private);
+    public.eval(share.index).value == commit
+}
+
+impl<C: Curve> Phase0<C> for Refresh<C> {
+    type Next = RefreshWaitingShare<C>;
+
+    /// Evaluates the zero-sharing polynomial at the index of each member and
+    /// encrypts the result with the corresponding public key.
+    fn encrypt_shares<R: RngCore>(
+        self,
+        rng: &mut R,
+    ) -> DKGResult<(RefreshWaitingShare<C>, Vec<BundledShares<C>>)> {
+        let (dkg, bundle) = self.dkg.encrypt_shares(rng)?;
+        Ok((
+            RefreshWaitingShare {
+                info: self.info,
+                dkg,
+            },
+            bundle,
+        ))
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "C::Scalar: DeserializeOwned")]
+/// Refresh stage which waits to receive the shares from the other members.
+pub struct RefreshWaitingShare<C: Curve> {
+    info: RefreshInfo<C>,
+    dkg: DKGWaitingShare<C>,
+}
+
+impl<C: Curve> Phase1<C> for RefreshWaitingShare<C> {
+    type Next = RefreshWaitingResponse<C>;
+
+    /// Processes the shares like in the DKG. The dealers which published a
+    /// polynomial with a non-zero free coefficient are excluded, as they would
+    /// change the distributed public key.
+    fn process_shares(
+        self,
+        bundles: &[BundledShares<C>],
+        publish_all: bool,
+    ) -> DKGResult<(RefreshWaitingResponse<C>, Option<BundledResponses>)> {
+        let mut info = self.info;
+        let session = self.dkg.session();
+        let mut invalid = bundles
+            .iter()
+            .filter(|b| &b.session == session)
+            .filter(|b| b.public.public_key() != &C::Point::zero())
+            .map(|b| b.dealer_idx)
+            .collect::<Vec<_>>();
+        invalid.sort_unstable();
+        invalid.dedup();
+        for dealer in &invalid {
+            events::share_rejected(info.index, *dealer, &ExclusionReason::InvalidRefresh);
+        }
+
+        // all the bundles of these dealers are dropped, so that a dealer can
+        // not equivocate with a valid and an invalid bundle
+        let bundles = bundles
+            .iter()
+            .filter(|b| !invalid.contains(&b.dealer_idx))
+            .cloned()
+            .collect::<Vec<_>>();
+        info.invalid = invalid;
+
+        match self.dkg.process_shares(&bundles, publish_all) {
+            Ok((dkg, bundle)) => Ok((RefreshWaitingResponse { info, dkg }, bundle)),
+            Err(err) => Err(info.map_err(err)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "C::Scalar: DeserializeOwned")]
+/// Refresh stage which waits to receive the responses from the other members.
+pub struct RefreshWaitingResponse<C: Curve> {
+    info: RefreshInfo<C>,
+    dkg: DKGWaitingResponse<C>,
+}
+
+impl<C: Curve> Phase2<C> for RefreshWaitingResponse<C> {
+    type Next = RefreshWaitingJustification<C>;
+
+    #[allow(clippy::type_complexity)]
+    /// Processes the responses like in the DKG, and refreshes the share if
+    /// there is no complaint.
+    fn process_responses(
+        self,
+        responses: &[BundledResponses],
+    ) -> Result<DKGOutput<C>, DKGResult<(Self::Next, Vec<BundledJustification<C>>)>> {
+        let info = self.info;
+        match self.dkg.process_responses(responses) {
+            Ok(out) => info.refresh(out).map_err(Err),
+            Err(Ok((dkg, justifs))) => {
+                Err(Ok((RefreshWaitingJustification { info, dkg }, justifs)))
+            }
+            Err(Err(err)) => Err(Err(info.map_err(err))),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "C::Scalar: DeserializeOwned")]
+/// Refresh stage which waits to receive the justifications from the other
+/// members.
+pub struct RefreshWaitingJustification<C: Curve> {
+    info: RefreshInfo<C>,
+    dkg: DKGWaitingJustification<C>,
+}
+
+impl<C: Curve> Phase3<C> for RefreshWaitingJustification<C> {
+    /// Processes the justifications like in the DKG, and refreshes the share
+    /// with the shares of the qualified members.
+    fn process_justifications(
+        self,
+        justifs: &[BundledJustification<C>],
+    ) -> DKGResult<DKGOutput<C>> {
+        let info = self.info;
+        match self.dkg.process_justifications(justifs) {
+            Ok(out) => info.refresh(out),
+            Err(err) => Err(info.map_err(err)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives::{
+        common::tests::{full_dkg, id_resp, invalid_shares, setup_group},
+        default_threshold,
+    };
+    use rand::thread_rng;
+    use threshold_bls::{
+        curve::bls12377::{G1Curve as BCurve, G1},
+        poly::{Eval, Poly},
+    };
+
+    fn setup_refresh(n: usize) -> (Vec<Refresh<BCurve>>, PublicPoly<BCurve>) {
+        let (privs, group) = setup_group::<BCurve>(n, default_threshold(n));
+        let session = SessionId::new(&group, 0, &[]);
+        let dkgs = privs
+            .iter()
+            .map(|p| DKG::new(*p, group.clone(), session).unwrap())
+            .collect::<Vec<_>>();
+        let (outputs, public) = full_dkg(group.threshold, dkgs);
+
+        let session = SessionId::new(&group, 1, &[]);
+        let refreshes = privs
+            .into_iter()
+            .zip(outputs)
+            .map(|(p, out)| Refresh::new_from_share(p, out, session).unwrap())
+            .collect();
+        (refreshes, public)
+    }
+
+    #[test]
+    fn refresh_keeps_public_key() {
+        let n = 5;
+        let thr = default_threshold(n);
+        let (refreshes, public) = setup_refresh(n);
+        let old_shares = refreshes
+            .iter()
+            .map(|r| r.info.shares[0].clone())
+            .collect::<Vec<_>>();
+
+        let (outputs, new_public) = full_dkg(thr, refreshes);
+        assert_eq!(new_public.public_key(), public.public_key());
+        for (out, old) in outputs.iter().zip(&old_shares) {
+            assert_eq!(out.public.public_key(), public.public_key());
+            assert_eq!(out.shares[0].index, old.index);
+            assert_ne!(out.shares[0].private, old.private);
+        }
+
+        // shares of different epochs can not be combined
+        let mixed = old_shares
+            .iter()
+            .take(thr - 1)
+            .map(|s| Eval {
+                index: s.index,
+                value: s.private,
+            })
+            .chain(outputs.iter().skip(thr - 1).take(1).map(|o| Eval {
+                index: o.shares[0].index,
+                value: o.shares[0].private,
+            }))
+            .collect::<Vec<_>>();
+        let mixed = Poly::full_recover(thr, mixed).unwrap();
+        assert_ne!(mixed.commit::<G1>().public_key(), public.public_key());
+    }
+
+    #[test]
+    fn invalid_refresh_is_excluded() {
+        let n = 5;
+        let thr = default_threshold(n);
+        let (refreshes, public) = setup_refresh(n);
+        let new_public = invalid_shares(
+            thr,
+            refreshes,
+            |mut s| {
+                // the first dealer shares a polynomial which does not commit
+                // to zero
+                let mut secret =
+                    Poly::<<BCurve as Curve>::Scalar>::new_from(thr - 1, &mut thread_rng());
+                secret.set(0, <BCurve as Curve>::Scalar::one());
+                s[0].public = secret.commit::<G1>();
+                s
+            },
+            id_resp,
+            |j| j,
+            |outs| {
+                let outs = outs
+                    .into_iter()
+                    .filter(|o| o.shares[0].index != 0)
+                    .collect::<Vec<_>>();
+                for out in &outs {
+                    assert!(!out.qual.contains_index(0));
+                    assert_eq!(out.report.dealers[&0], ExclusionReason::InvalidRefresh);
+                }
+                outs
+            },
+        )
+        .unwrap();
+        assert_eq!(new_public.public_key(), public.public_key());
+    }
+
+    #[test]
+    fn invalid_share() {
+        let n = 4;
+        let (privs, group) = setup_group::<BCurve>(n, default_threshold(n));
+        let session = SessionId::new(&group, 0, &[]);
+        let dkgs = privs
+            .iter()
+            .map(|p| DKG::new(*p, group.clone(), session).unwrap())
+            .collect::<Vec<_>>();
+        let (mut outputs, _) = full_dkg(group.threshold, dkgs);
+
+        let mut out = outputs.remove(0);
+        out.shares[0].private = <BCurve as Curve>::Scalar::one();
+        let err = Refresh::new_from_share(privs[0], out, session).unwrap_err();
+        assert!(matches!(err, DKGError::InvalidRefreshShare));
+    }
+}
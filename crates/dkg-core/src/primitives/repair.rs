@@ -0,0 +1,591 @@
+//! Implements the repair of the share of a member which lost it, from the
+//! enrollment protocol of [Laing and Stinson](https://eprint.iacr.org/2017/1155.pdf).
+//! The member keeps its identity key, and a threshold of helpers from the
+//! group rebuild its share without revealing their own shares:
+//!
+//! 1. each helper `j` multiplies its share by its Lagrange coefficient at the
+//!    index of the repaired member, splits the result into random masks, one
+//!    for each helper, and publishes the masks encrypted to the other helpers
+//!    along with a commitment to each of them (`RepairBundle`).
+//! 2. each helper `k` sums the masks it received and sends the sum encrypted
+//!    to the repaired member (`RepairContribution`).
+//! 3. the repaired member sums the contributions to get its share.
+//!
+//! The commitments of a helper add up to the commitment of its share,
+//! evaluated from the public polynomial, times its Lagrange coefficient, so
+//! that every mask and contribution can be checked, as well as the repaired
+//! share against `public.eval(i)`. A helper which misbehaves is reported in
+//! the error, and should be replaced before running the repair again.
+use crate::primitives::{
+    group::Group,
+    types::{DKGOutput, DKGReport, SessionId},
+    DKGError, DKGResult, RepairError,
+};
+
+use rand_core::RngCore;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use threshold_bls::{
+    ecies::{self, EciesCipher},
+    group::{Curve, Element, Scalar},
+    poly::{x_coordinate, Idx, PublicPoly},
+    sig::Share,
+    vss,
+};
+
+/// Domain separators for the encryption of the masks and contributions
+const MASK_DOMAIN: &[u8] = b"dkg-core repair mask";
+const CONTRIBUTION_DOMAIN: &[u8] = b"dkg-core repair contribution";
+
+/// A mask sent by a helper to another helper
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "C::Scalar: DeserializeOwned")]
+pub struct MaskedShare<C: Curve> {
+    /// The index of the helper this mask is encrypted to
+    pub share_idx: Idx,
+    /// The commitment to the mask
+    pub commitment: C::Point,
+    /// The ECIES encrypted mask
+    pub secret: EciesCipher<C>,
+}
+
+/// A `RepairBundle` is published by each helper during the first step of the
+/// repair, and contains one mask for each helper (including itself).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "C::Scalar: DeserializeOwned")]
+pub struct RepairBundle<C: Curve> {
+    /// The session of the repair
+    pub session: SessionId,
+    /// The index of the helper which created the masks
+    pub helper_idx: Idx,
+    /// The index of the member whose share is repaired
+    pub target_idx: Idx,
+    /// The masks, one for each helper
+    pub masks: Vec<MaskedShare<C>>,
+}
+
+/// A `RepairContribution` is sent by each helper to the repaired member during
+/// the second step of the repair, and contains the sum of the masks it
+/// received.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "C::Scalar: DeserializeOwned")]
+pub struct RepairContribution<C: Curve> {
+    /// The session of the repair
+    pub session: SessionId,
+    /// The index of the helper which sent the contribution
+    pub helper_idx: Idx,
+    /// The index of the member whose share is repaired
+    pub target_idx: Idx,
+    /// The ECIES encrypted sum of masks
+    pub secret: EciesCipher<C>,
+}
+
+#[derive(Clone, Debug)]
+struct RepairInfo<C: Curve> {
+    session: SessionId,
+    target: Idx,
+    // sorted and deduplicated
+    helpers: Vec<Idx>,
+    public: PublicPoly<C>,
+}
+
+impl<C: Curve> RepairInfo<C> {
+    fn new(
+        group: &Group<C>,
+        public: PublicPoly<C>,
+        target: Idx,
+        mut helpers: Vec<Idx>,
+        session: SessionId,
+    ) -> Result<Self, RepairError> {
+        let weighted = group
+            .nodes
+            .iter()
+            .any(|n| n.shares() != (n.id()..n.id() + 1));
+        if weighted {
+            return Err(RepairError::WeightedGroup);
+        }
+        helpers.sort_unstable();
+        helpers.dedup();
+        let valid = helpers.len() >= group.threshold
+            && group.contains_index(target)
+            && !helpers.contains(&target)
+            && helpers.iter().all(|&h| group.contains_index(h));
+        if !valid {
+            return Err(RepairError::InvalidHelpers(target));
+        }
+        Ok(Self {
+            session,
+            target,
+            helpers,
+            public,
+        })
+    }
+
+    /// Returns the bundle of each helper, after checking that its masks add up
+    /// to the helper's contribution to the repaired share.
+    fn check_bundles<'a>(
+        &self,
+        bundles: &'a [RepairBundle<C>],
+    ) -> Result<Vec<&'a RepairBundle<C>>, RepairError> {
+        self.helpers
+            .iter()
+            .map(|&helper| {
+                let mut from_helper = bundles.iter().filter(|b| {
+                    b.session == self.session
+                        && b.target_idx == self.target
+                        && b.helper_idx == helper
+                });
+                let bundle = from_helper
+                    .next()
+                    .ok_or(RepairError::MissingBundle(helper))?;
+                // a helper may not publish several bundles
+                if from_helper.next().is_some() {
+                    return Err(RepairError::InvalidBundle(helper));
+                }
+
+                let recipients = bundle.masks.iter().map(|m| m.share_idx).collect::<Vec<_>>();
+                if recipients != self.helpers {
+                    return Err(RepairError::InvalidBundle(helper));
+                }
+
+                let sum = bundle.masks.iter().fold(C::Point::zero(), |mut sum, m| {
+                    sum.add(&m.commitment);
+                    sum
+                });
+                let mut expected = self.public.eval(helper).value;
+                expected.mul(&self.lagrange(helper));
+                if sum != expected {
+                    return Err(RepairError::InvalidBundle(helper));
+                }
+
+                Ok(bundle)
+            })
+            .collect()
+    }
+
+    /// Returns the Lagrange coefficient of the helper, evaluated at the index
+    /// of the repaired member
+    fn lagrange(&self, helper: Idx) -> C::Scalar {
+        let x = x_coordinate::<C::Scalar>;
+        let (target, xi) = (x(self.target), x(helper));
+        let mut num = C::Scalar::one();
+        let mut den = C::Scalar::one();
+        for xj in self.helpers.iter().filter(|&&j| j != helper).map(|&j| x(j)) {
+            let mut tmp = target.clone();
+            tmp.sub(&xj);
+            num.mul(&tmp);
+            let mut tmp = xi.clone();
+            tmp.sub(&xj);
+            den.mul(&tmp);
+        }
+        num.mul(&den.inverse().expect("helpers are distinct"));
+        num
+    }
+}
+
+fn repair_ad(domain: &[u8], session: &SessionId, from: Idx, to: Idx) -> Vec<u8> {
+    let mut ad = domain.to_vec();
+    ad.extend_from_slice(session.as_bytes());
+    ad.extend_from_slice(&from.to_le_bytes());
+    ad.extend_from_slice(&to.to_le_bytes());
+    ad
+}
+
+fn encrypt<C: Curve, R: RngCore>(
+    to: &C::Point,
+    value: &C::Scalar,
+    ad: &[u8],
+    rng: &mut R,
+) -> DKGResult<EciesCipher<C>> {
+    let buff = bincode::serialize(value)?;
+    Ok(ecies::encrypt_with_ad::<C, _>(to, &buff, ad, rng))
+}
+
+// decrypts the value and checks it against its commitment
+fn decrypt<C: Curve>(
+    private_key: &C::Scalar,
+    cipher: &EciesCipher<C>,
+    ad: &[u8],
+    commitment: &C::Point,
+) -> Option<C::Scalar> {
+    let buff = ecies::decrypt_with_ad::<C>(private_key, cipher, ad).ok()?;
+    let value: C::Scalar = bincode::deserialize(&buff).ok()?;
+    let mut commit = C::point();
+    commit.mul(&value);
+    if &commit == commitment {
+        Some(value)
+    } else {
+        None
+    }
+}
+
+/// A member of the group which helps repairing the share of another member.
+#[derive(Clone, Debug)]
+pub struct RepairHelper<C: Curve> {
+    info: RepairInfo<C>,
+    private_key: C::Scalar,
+    share: Share<C::Scalar>,
+    group: Group<C>,
+}
+
+impl<C: Curve> RepairHelper<C> {
+    /// Creates a helper from its output of a previous DKG, resharing or
+    /// refresh. There must be at least a threshold of helpers, including this
+    /// one, and the repaired member must be part of the group but not one of
+    /// the helpers.
+    pub fn new(
+        private_key: C::Scalar,
+        curr_share: DKGOutput<C>,
+        target: Idx,
+        helpers: Vec<Idx>,
+        session: SessionId,
+    ) -> DKGResult<Self> {
+        let group = curr_share.qual;
+        let info = RepairInfo::new(&group, curr_share.public, target, helpers, session)?;
+        let share = match curr_share.shares.as_slice() {
+            [share] if info.helpers.contains(&share.index) => share.clone(),
+            _ => return Err(RepairError::InvalidHelpers(target).into()),
+        };
+        Ok(Self {
+            info,
+            private_key,
+            share,
+            group,
+        })
+    }
+
+    /// Splits the contribution of this helper to the repaired share into
+    /// random masks, encrypted to each helper.
+    pub fn mask<R: RngCore>(&self, rng: &mut R) -> DKGResult<RepairBundle<C>> {
+        let idx = self.share.index;
+        let mut own = self.share.private.clone();
+        own.mul(&self.info.lagrange(idx));
+
+        let masks = self
+            .info
+            .helpers
+            .iter()
+            .map(|&helper| {
+                // the mask of the last helper is chosen so that they all add
+                // up to our contribution
+                let value = if Some(&helper) == self.info.helpers.last() {
+                    own.clone()
+                } else {
+                    let value = C::Scalar::rand(rng);
+                    own.sub(&value);
+                    value
+                };
+                let mut commitment = C::point();
+                commitment.mul(&value);
+
+                let key = self
+                    .group
+                    .node(helper)
+                    .expect("helpers are in the group")
+                    .key();
+                let ad = repair_ad(MASK_DOMAIN, &self.info.session, idx, helper);
+                Ok(MaskedShare {
+                    share_idx: helper,
+                    commitment,
+                    secret: encrypt(key, &value, &ad, rng)?,
+                })
+            })
+            .collect::<DKGResult<Vec<_>>>()?;
+
+        Ok(RepairBundle {
+            session: self.info.session,
+            helper_idx: idx,
+            target_idx: self.info.target,
+            masks,
+        })
+    }
+
+    /// Checks the bundles of all the helpers, and sums the masks which were
+    /// sent to us into a contribution encrypted to the repaired member.
+    pub fn contribute<R: RngCore>(
+        &self,
+        bundles: &[RepairBundle<C>],
+        rng: &mut R,
+    ) -> DKGResult<RepairContribution<C>> {
+        let idx = self.share.index;
+        let mut sum = C::Scalar::zero();
+        for bundle in self.info.check_bundles(bundles)? {
+            let mask = bundle
+                .masks
+                .iter()
+                .find(|m| m.share_idx == idx)
+                .expect("bundles contain a mask for each helper");
+            let ad = repair_ad(MASK_DOMAIN, &self.info.session, bundle.helper_idx, idx);
+            let value = decrypt(&self.private_key, &mask.secret, &ad, &mask.commitment)
+                .ok_or(RepairError::InvalidMask(bundle.helper_idx))?;
+            sum.add(&value);
+        }
+
+        let target = self.info.target;
+        let key = self
+            .group
+            .node(target)
+            .expect("target is in the group")
+            .key();
+        let ad = repair_ad(CONTRIBUTION_DOMAIN, &self.info.session, idx, target);
+        Ok(RepairContribution {
+            session: self.info.session,
+            helper_idx: idx,
+            target_idx: target,
+            secret: encrypt(key, &sum, &ad, rng)?,
+        })
+    }
+}
+
+/// The member of the group whose share is repaired.
+#[derive(Clone, Debug)]
+pub struct RepairTarget<C: Curve> {
+    info: RepairInfo<C>,
+    private_key: C::Scalar,
+    group: Group<C>,
+}
+
+impl<C: Curve> RepairTarget<C> {
+    /// Creates the repaired member from its identity key, and the group and
+    /// distributed public polynomial of the DKG whose share was lost.
+    pub fn new(
+        private_key: C::Scalar,
+        group: Group<C>,
+        public: PublicPoly<C>,
+        helpers: Vec<Idx>,
+        session: SessionId,
+    ) -> DKGResult<Self> {
+        let mut public_key = C::point();
+        public_key.mul(&private_key);
+        let target = group
+            .index(&public_key)
+            .ok_or(DKGError::PublicKeyNotFound)?;
+        let info = RepairInfo::new(&group, public, target, helpers, session)?;
+        Ok(Self {
+            info,
+            private_key,
+            group,
+        })
+    }
+
+    /// Checks the bundles and the contributions of all the helpers, and
+    /// rebuilds the share as the sum of the contributions.
+    pub fn repair(
+        self,
+        bundles: &[RepairBundle<C>],
+        contributions: &[RepairContribution<C>],
+    ) -> DKGResult<DKGOutput<C>> {
+        let bundles = self.info.check_bundles(bundles)?;
+        let target = self.info.target;
+        let mut private = C::Scalar::zero();
+        for &helper in &self.info.helpers {
+            let contribution = contributions
+                .iter()
+                .find(|c| {
+                    c.session == self.info.session
+                        && c.target_idx == target
+                        && c.helper_idx == helper
+                })
+                .ok_or(RepairError::MissingContribution(helper))?;
+
+            // the contribution must match the masks sent to the helper
+            let commitment = bundles.iter().fold(C::Point::zero(), |mut sum, b| {
+                let mask = b.masks.iter().find(|m| m.share_idx == helper);
+                sum.add(
+                    &mask
+                        .expect("bundles contain a mask for each helper")
+                        .commitment,
+                );
+                sum
+            });
+            let ad = repair_ad(CONTRIBUTION_DOMAIN, &self.info.session, helper, target);
+            let value = decrypt(&self.private_key, &contribution.secret, &ad, &commitment)
+                .ok_or(RepairError::InvalidContribution(helper))?;
+            private.add(&value);
+        }
+
+        // guaranteed by the checks above, since the masks add up to the
+        // Lagrange interpolation of the helpers' shares at our index
+        let share = Share {
+            index: target,
+            private,
+        };
+        debug_assert!(vss::verify_share::<C>(&share, &self.info.public));
+
+        Ok(DKGOutput {
+            qual: self.group,
+            public: self.info.public,
+            shares: vec![share],
+            report: DKGReport::default(),
+            transcript: Default::default(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives::{
+        common::tests::{full_dkg, setup_group},
+        default_threshold,
+        joint_feldman::DKG,
+    };
+    use rand::thread_rng;
+    use threshold_bls::curve::bls12377::G1Curve as BCurve;
+
+    struct Setup {
+        privs: Vec<<BCurve as Curve>::Scalar>,
+        outputs: Vec<DKGOutput<BCurve>>,
+        session: SessionId,
+    }
+
+    fn setup(n: usize) -> Setup {
+        let (privs, group) = setup_group::<BCurve>(n, default_threshold(n));
+        let session = SessionId::new(&group, 0, &[]);
+        let dkgs = privs
+            .iter()
+            .map(|p| DKG::new(*p, group.clone(), session).unwrap())
+            .collect::<Vec<_>>();
+        let (outputs, _) = full_dkg(group.threshold, dkgs);
+        let session = SessionId::new(&group, 1, b"repair");
+        Setup {
+            privs,
+            outputs,
+            session,
+        }
+    }
+
+    impl Setup {
+        fn helpers(&self, target: Idx, helpers: &[Idx]) -> Vec<RepairHelper<BCurve>> {
+            helpers
+                .iter()
+                .map(|&h| {
+                    RepairHelper::new(
+                        self.privs[h as usize],
+                        self.outputs[h as usize].clone(),
+                        target,
+                        helpers.to_vec(),
+                        self.session,
+                    )
+                    .unwrap()
+                })
+                .collect()
+        }
+
+        fn target(&self, target: Idx, helpers: &[Idx]) -> RepairTarget<BCurve> {
+            let out = &self.outputs[target as usize];
+            RepairTarget::new(
+                self.privs[target as usize],
+                out.qual.clone(),
+                out.public.clone(),
+                helpers.to_vec(),
+                self.session,
+            )
+            .unwrap()
+        }
+    }
+
+    fn bundles(helpers: &[RepairHelper<BCurve>]) -> Vec<RepairBundle<BCurve>> {
+        helpers
+            .iter()
+            .map(|h| h.mask(&mut thread_rng()).unwrap())
+            .collect()
+    }
+
+    fn contributions(
+        helpers: &[RepairHelper<BCurve>],
+        bundles: &[RepairBundle<BCurve>],
+    ) -> Vec<RepairContribution<BCurve>> {
+        helpers
+            .iter()
+            .map(|h| h.contribute(bundles, &mut thread_rng()).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn repair_share() {
+        let setup = setup(6);
+        let ids = [0, 1, 3, 4, 5];
+        let helpers = setup.helpers(2, &ids);
+        let bundles = bundles(&helpers);
+        let contributions = contributions(&helpers, &bundles);
+
+        let out = setup
+            .target(2, &ids)
+            .repair(&bundles, &contributions)
+            .unwrap();
+        assert_eq!(out.shares, setup.outputs[2].shares);
+        assert_eq!(out.public, setup.outputs[2].public);
+    }
+
+    #[test]
+    fn invalid_helpers() {
+        let setup = setup(6);
+        // not enough helpers
+        let err = RepairTarget::new(
+            setup.privs[2],
+            setup.outputs[2].qual.clone(),
+            setup.outputs[2].public.clone(),
+            vec![0, 1, 1, 3],
+            setup.session,
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            DKGError::RepairError(RepairError::InvalidHelpers(2))
+        ));
+
+        // the repaired member helps itself
+        let err = RepairHelper::new(
+            setup.privs[2],
+            setup.outputs[2].clone(),
+            2,
+            vec![0, 1, 2, 3, 4],
+            setup.session,
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            DKGError::RepairError(RepairError::InvalidHelpers(2))
+        ));
+    }
+
+    #[test]
+    fn misbehaving_helpers() {
+        let setup = setup(6);
+        let ids = [0, 1, 3, 4, 5];
+        let helpers = setup.helpers(2, &ids);
+        let bundles = bundles(&helpers);
+
+        // the masks do not add up to the helper's contribution
+        let mut invalid = bundles.clone();
+        invalid[1].masks[0].commitment = BCurve::point();
+        let err = helpers[0]
+            .contribute(&invalid, &mut thread_rng())
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            DKGError::RepairError(RepairError::InvalidBundle(1))
+        ));
+
+        // a helper did not publish its masks
+        let err = helpers[0]
+            .contribute(&bundles[1..], &mut thread_rng())
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            DKGError::RepairError(RepairError::MissingBundle(0))
+        ));
+
+        // a helper sends the contribution of another helper
+        let mut contributions = contributions(&helpers, &bundles);
+        contributions[4].secret = contributions[3].secret.clone();
+        let err = setup
+            .target(2, &ids)
+            .repair(&bundles, &contributions)
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            DKGError::RepairError(RepairError::InvalidContribution(5))
+        ));
+    }
+}
@@ -6,6 +6,7 @@
 //! receives a refreshed share of the same secret).
 use super::common::*;
 use crate::primitives::{
+    events,
     group::Group,
     phases::{Phase0, Phase1, Phase2, Phase3},
     status::{Status, StatusMatrix},
@@ -21,36 +22,48 @@ use threshold_bls::{
 
 use rand_core::RngCore;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
-use std::{cell::RefCell, fmt::Debug};
+use std::{cell::RefCell, collections::BTreeMap, fmt::Debug};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(bound = "C::Scalar: DeserializeOwned")]
 struct ReshareInfo<C: Curve> {
     private_key: C::Scalar,
     public_key: C::Point,
-    // our previous index in the group - it can be none if we are a new member
-    prev_index: Option<Idx>,
-    // previous group on which to reshare
+    // the polynomials resharing each of our shares in the previous group - it
+    // is empty if we are a new member
+    dealings: Vec<Dealing<C>>,
+    // previous group on which to reshare, with one dealer per share index
     prev_group: Group<C>,
     // previous group distributed public polynomial
     prev_public: Poly<C::Point>,
-    // secret and public polynomial of a dealer
-    secret: Option<Poly<C::Scalar>>,
-    public: Option<Poly<C::Point>>,
 
     // our new index in the group - it can be none if we are a leaving member
     new_index: Option<Idx>,
     // new group that is receiving the refreshed shares
     new_group: Group<C>,
+    // the session of this resharing
+    session: SessionId,
 }
 
 impl<C: Curve> ReshareInfo<C> {
-    fn is_dealer(&self) -> bool {
-        self.prev_index.is_some()
-    }
     fn is_share_holder(&self) -> bool {
         self.new_index.is_some()
     }
+    // our indices as a dealer, i.e. the indices of our previous shares
+    fn dealer_indices(&self) -> Vec<Idx> {
+        self.dealings.iter().map(|d| d.index).collect()
+    }
+}
+
+// The secret polynomial resharing one of our previous shares, which is its free
+// coefficient, and its commitment
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "C::Scalar: DeserializeOwned")]
+struct Dealing<C: Curve> {
+    // the index of the reshared share, which is our dealer index for it
+    index: Idx,
+    secret: PrivatePoly<C>,
+    public: PublicPoly<C>,
 }
 
 /// RDKG is the struct containing the logic to run the resharing scheme from
@@ -68,65 +81,102 @@ pub struct RDKG<C: Curve> {
 }
 
 impl<C: Curve> RDKG<C> {
+    /// Creates a resharing instance from the output of a node of the current
+    /// group.
+    ///
+    /// In the resharing, the current group is expanded to one dealer per
+    /// share index (see `Group::expand`): a node of a weighted group deals
+    /// one polynomial for each of its shares, and publishes one bundle per
+    /// share index during the phases where dealers publish.
     pub fn new_from_share(
         private_key: C::Scalar,
         curr_share: DKGOutput<C>,
         new_group: Group<C>,
+        session: SessionId,
     ) -> Result<RDKG<C>, DKGError> {
         use rand::prelude::*;
-        Self::new_from_share_rng(private_key, curr_share, new_group, &mut thread_rng())
+        Self::new_from_share_rng(
+            private_key,
+            curr_share,
+            new_group,
+            session,
+            &mut thread_rng(),
+        )
     }
 
     pub fn new_from_share_rng<R: RngCore>(
         private_key: C::Scalar,
         curr_share: DKGOutput<C>,
         new_group: Group<C>,
+        session: SessionId,
         rng: &mut R,
     ) -> Result<RDKG<C>, DKGError> {
-        let oldi = Some(curr_share.share.index);
-        let prev_group = curr_share.qual;
-        let prev_public = curr_share.public;
-        // generate a secret polynomial with the share being the free
-        // coefficient
-        let mut secret = PrivatePoly::<C>::new_from(new_group.threshold - 1, rng);
-        secret.set(0, curr_share.share.private);
-        let public = secret.commit::<C::Point>();
+        if curr_share.shares.is_empty() {
+            return Self::new_member(
+                private_key,
+                curr_share.qual,
+                curr_share.public,
+                new_group,
+                session,
+            );
+        }
+
+        new_group.validate()?;
         let mut pubkey = C::point();
         pubkey.mul(&private_key);
         let new_idx = new_group.index(&pubkey);
+        let dealings = curr_share
+            .shares
+            .into_iter()
+            .map(|share| {
+                // generate a secret polynomial with the share being the free
+                // coefficient
+                let mut secret = PrivatePoly::<C>::new_from(new_group.threshold - 1, rng);
+                secret.set(0, share.private);
+                let public = secret.commit::<C::Point>();
+                Dealing {
+                    index: share.index,
+                    secret,
+                    public,
+                }
+            })
+            .collect();
         let info = ReshareInfo {
             private_key,
             public_key: pubkey,
-            prev_index: oldi,
-            prev_group,
-            prev_public,
-            secret: Some(secret),
-            public: Some(public),
+            dealings,
+            prev_group: curr_share.qual.expand(),
+            prev_public: curr_share.public,
             new_index: new_idx,
             new_group,
+            session,
         };
         Ok(RDKG { info })
     }
 
+    /// Creates a resharing instance for a node which does not hold a share of
+    /// the current group. The current group is expanded to one dealer per
+    /// share index if it is weighted.
     pub fn new_member(
         private_key: C::Scalar,
         curr_group: Group<C>,
         curr_public: PublicPoly<C>,
         new_group: Group<C>,
+        session: SessionId,
     ) -> Result<RDKG<C>, DKGError> {
+        new_group.validate()?;
         let mut pubkey = C::point();
         pubkey.mul(&private_key);
         let new_idx = new_group.index(&pubkey);
         let info = ReshareInfo {
             private_key,
             public_key: pubkey,
-            prev_index: None,
-            prev_group: curr_group,
+            dealings: Vec::new(),
+            prev_group: curr_group.expand(),
             prev_public: curr_public,
-            secret: None,
-            public: None,
             new_index: new_idx,
             new_group,
+            session,
         };
         Ok(RDKG { info })
     }
@@ -137,28 +187,24 @@ impl<C: Curve> Phase0<C> for RDKG<C> {
     fn encrypt_shares<R: RngCore>(
         self,
         rng: &mut R,
-    ) -> DKGResult<(RDKGWaitingShare<C>, Option<BundledShares<C>>)> {
-        if !self.info.is_dealer() {
-            return Ok((RDKGWaitingShare { info: self.info }, None));
-        }
+    ) -> DKGResult<(RDKGWaitingShare<C>, Vec<BundledShares<C>>)> {
         let info = self.info;
-        let public = info.public.unwrap();
-        let secret = info.secret.unwrap();
-        let bundle = create_share_bundle(
-            info.prev_index.unwrap(),
-            &secret,
-            &public,
-            &info.new_group,
-            rng,
-        )?;
-        let dw = RDKGWaitingShare {
-            info: ReshareInfo {
-                public: Some(public),
-                secret: Some(secret),
-                ..info
-            },
-        };
-        Ok((dw, Some(bundle)))
+        let bundles = info
+            .dealings
+            .iter()
+            .map(|dealing| {
+                events::phase(dealing.index, "encrypt_shares");
+                create_share_bundle(
+                    &info.session,
+                    dealing.index,
+                    &dealing.secret,
+                    &dealing.public,
+                    &info.new_group,
+                    rng,
+                )
+            })
+            .collect::<DKGResult<Vec<_>>>()?;
+        Ok((RDKGWaitingShare { info }, bundles))
     }
 }
 
@@ -183,27 +229,35 @@ impl<C: Curve> Phase1<C> for RDKGWaitingShare<C> {
         if !self.info.is_share_holder() {
             return Ok((
                 RDKGWaitingResponse {
-                    statuses: StatusMatrix::new(
-                        self.info.prev_group.len(),
-                        self.info.new_group.len(),
+                    statuses: StatusMatrix::with_indices(
+                        &self.info.prev_group.indices(),
+                        &self.info.new_group.indices(),
                         Status::Success,
                     ),
                     info: self.info,
                     shares: ShareInfo::<C>::new(),
                     publics: PublicInfo::<C>::new(),
+                    equivocators: Equivocators::default(),
+                    report: DKGReport::default(),
                 },
                 None,
             ));
         }
 
         let my_idx = self.info.new_index.unwrap();
+        events::phase(my_idx, "process_shares");
+        let mut equivocators = Equivocators::default();
+        let mut report = DKGReport::default();
         let (mut shares, mut publics, mut statuses) = process_shares_get_all(
             &self.info.prev_group,
             &self.info.new_group,
-            self.info.prev_index,
+            &self.info.dealer_indices(),
             my_idx,
             &self.info.private_key,
+            &self.info.session,
             bundles,
+            &mut equivocators,
+            &mut report,
         )?;
 
         // set the status to true for any dealer that is also a share holder
@@ -215,14 +269,6 @@ impl<C: Curve> Phase1<C> for RDKGWaitingShare<C> {
             }
         }
 
-        println!(
-            "{} - PROCESS SHARES: {:?} -> {:?} - {}",
-            self.info.new_index.as_ref().unwrap(),
-            bundles.iter().map(|b| b.dealer_idx).collect::<Vec<Idx>>(),
-            shares.iter().map(|(&k, _)| k).collect::<Vec<Idx>>(),
-            statuses,
-        );
-
         bundles
             .iter()
             // this bundle was invalid for some reason
@@ -233,42 +279,44 @@ impl<C: Curve> Phase1<C> for RDKGWaitingShare<C> {
                 !check_public_resharing::<C>(b.dealer_idx, &b.public, &self.info.prev_public)
             })
             .for_each(|b| {
-                println!("REMOVE BUNDLE: {}", b.dealer_idx);
+                let reason = ExclusionReason::InvalidResharing;
+                events::share_rejected(my_idx, b.dealer_idx, &reason);
+                report.exclude_dealer(b.dealer_idx, reason);
                 shares.remove(&b.dealer_idx);
                 for n in &self.info.new_group.nodes {
                     statuses.set(b.dealer_idx, n.id(), Status::Complaint);
                 }
             });
 
-        let mut info = self.info;
-        if info.is_dealer() {
-            let public = info.public.take().unwrap();
-            let secret = info.secret.take().unwrap();
-            // we register our own share and publics into the mix
-            let didx = info.prev_index.unwrap();
-            shares.insert(didx, secret.eval(didx).value);
-            publics.insert(didx, public.clone());
-            // we treat our own share as valid!
-            statuses.set(didx, my_idx, Status::Success);
-            info.public = Some(public);
-            info.secret = Some(secret);
+        // we register our own shares and publics into the mix, and treat our
+        // own shares as valid!
+        let info = self.info;
+        let me = info.new_group.node(my_idx).expect("we are a share holder");
+        for dealing in &info.dealings {
+            shares.insert(dealing.index, eval_shares(me, &dealing.secret));
+            publics.insert(dealing.index, dealing.public.clone());
+            statuses.set(dealing.index, my_idx, Status::Success);
         }
 
         // we need at least a threshold of dealers to share their share to be
         // able to reconstruct a share of the same distributed private key.
         if shares.len() < info.prev_group.threshold {
+            events::aborted(my_idx, &report);
             return Err(DKGError::NotEnoughValidShares(
                 shares.len(),
                 info.prev_group.threshold,
+                report,
             ));
         }
 
-        let bundle = compute_bundle_response(my_idx, &statuses, publish_all);
+        let bundle = compute_bundle_response(&info.session, my_idx, &statuses, publish_all);
         let new_dkg = RDKGWaitingResponse {
             info,
             shares,
             publics,
             statuses,
+            equivocators,
+            report,
         };
         Ok((new_dkg, bundle))
     }
@@ -281,6 +329,9 @@ pub struct RDKGWaitingResponse<C: Curve> {
     shares: ShareInfo<C>,
     publics: PublicInfo<C>,
     statuses: StatusMatrix,
+    equivocators: Equivocators,
+    // the dealers whose shares we rejected during the first phase
+    report: DKGReport,
 }
 
 impl<C: Curve> Phase2<C> for RDKGWaitingResponse<C> {
@@ -295,7 +346,7 @@ impl<C: Curve> Phase2<C> for RDKGWaitingResponse<C> {
     fn process_responses(
         self,
         responses: &[BundledResponses],
-    ) -> Result<DKGOutput<C>, DKGResult<(Self::Next, Option<BundledJustification<C>>)>> {
+    ) -> Result<DKGOutput<C>, DKGResult<(Self::Next, Vec<BundledJustification<C>>)>> {
         if !self.info.is_share_holder() {
             // we just silently pass
             let dkg = RDKGWaitingJustification {
@@ -303,18 +354,24 @@ impl<C: Curve> Phase2<C> for RDKGWaitingResponse<C> {
                 shares: self.shares,
                 statuses: RefCell::new(self.statuses),
                 publics: self.publics,
+                equivocators: self.equivocators,
+                report: self.report,
             };
-            return Err(Ok((dkg, None)));
+            return Err(Ok((dkg, Vec::new())));
         }
 
         let info = self.info;
+        events::phase(info.new_index.unwrap(), "process_responses");
         let mut statuses = self.statuses;
+        let mut equivocators = self.equivocators;
         set_statuses(
             info.new_index.unwrap(),
+            &info.session,
             &info.prev_group,
             &info.new_group,
             &mut statuses,
             responses,
+            &mut equivocators,
         );
 
         // find out if justifications are required
@@ -327,24 +384,40 @@ impl<C: Curve> Phase2<C> for RDKGWaitingResponse<C> {
 
         if justifications_required {
             // we can only create justifications if we are a dealer
-            let bundled_justifications = if info.is_dealer() {
-                let public = info.public.as_ref().unwrap();
-                let secret = info.secret.as_ref().unwrap();
-                get_justification(info.prev_index.unwrap(), secret, public, &statuses)
-            } else {
-                None
-            };
+            let bundled_justifications = info
+                .dealings
+                .iter()
+                .filter_map(|dealing| {
+                    get_justification(
+                        &info.session,
+                        dealing.index,
+                        &info.new_group,
+                        &dealing.secret,
+                        &dealing.public,
+                        &statuses,
+                    )
+                })
+                .collect();
             let dkg = RDKGWaitingJustification {
                 info,
                 shares: self.shares,
                 statuses: RefCell::new(statuses),
                 publics: self.publics,
+                equivocators,
+                report: self.report,
             };
             return Err(Ok((dkg, bundled_justifications)));
         }
         // in case of error here, the protocol must be aborted
-        compute_resharing_output(info, self.shares, self.publics, RefCell::new(statuses))
-            .map_err(Err)
+        compute_resharing_output(
+            info,
+            self.shares,
+            self.publics,
+            RefCell::new(statuses),
+            equivocators,
+            self.report,
+        )
+        .map_err(Err)
     }
 }
 
@@ -356,6 +429,9 @@ pub struct RDKGWaitingJustification<C: Curve> {
     publics: PublicInfo<C>,
     // guaranteed to be of the right size (n)
     statuses: RefCell<StatusMatrix>,
+    equivocators: Equivocators,
+    // the dealers whose shares we rejected
+    report: DKGReport,
 }
 
 impl<C> Phase3<C> for RDKGWaitingJustification<C>
@@ -375,18 +451,24 @@ where
         if !self.info.is_share_holder() {
             return Err(DKGError::NotShareHolder);
         }
+        events::phase(self.info.new_index.unwrap(), "process_justifications");
+        let mut equivocators = self.equivocators;
         let mut valid_shares = internal_process_justifications(
             self.info.new_index.unwrap(),
+            &self.info.session,
             &self.info.prev_group,
+            &self.info.new_group,
             &mut self.statuses.borrow_mut(),
             &self.publics,
             justifs,
+            &mut equivocators,
         );
 
         let info = self.info;
         let publics = self.publics;
         let shares = self.shares;
         let statuses = self.statuses;
+        let mut report = self.report;
         justifs
             .iter()
             // this bundle was already invalid for some reason
@@ -403,6 +485,9 @@ where
             })
             .for_each(|b| {
                 // we remove the shares coming from invalid justification
+                let reason = ExclusionReason::InvalidResharing;
+                events::share_rejected(info.new_index.unwrap(), b.dealer_idx, &reason);
+                report.exclude_dealer(b.dealer_idx, reason);
                 valid_shares.remove(&b.dealer_idx);
                 for n in &info.new_group.nodes {
                     statuses
@@ -411,12 +496,13 @@ where
                 }
             });
 
-        println!(" BEFORE JUSTIFICATION OUTPUT: {}", statuses.borrow());
         compute_resharing_output(
             info,
             valid_shares.into_iter().chain(shares).collect(),
             publics,
             statuses,
+            equivocators,
+            report,
         )
     }
 }
@@ -426,83 +512,142 @@ fn compute_resharing_output<C: Curve>(
     shares: ShareInfo<C>,
     publics: PublicInfo<C>,
     statuses: RefCell<StatusMatrix>,
+    equivocators: Equivocators,
+    mut pending: DKGReport,
 ) -> DKGResult<DKGOutput<C>> {
-    // to compute the final share, we interpolate all the valid shares received
-    let mut shares_eval: Vec<Eval<C::Scalar>> = shares
-        .into_iter()
-        .map(|(idx, sh)| Eval {
-            value: sh,
-            index: idx,
-        })
-        .collect();
+    pending.exclude_equivocations(&equivocators);
 
-    // only take the first t shares sorted
-    shares_eval.sort_by(|a, b| a.index.cmp(&b.index));
-    let shares_indexes = shares_eval.iter().map(|e| e.index).collect::<Vec<Idx>>();
-    let shortened_evals = shares_eval
+    // to compute the final shares, we interpolate for each of our share
+    // indices the valid shares received from the dealers which did not
+    // equivocate, taking only the first t dealers sorted
+    let thr = info.prev_group.threshold;
+    let mut shares = shares
         .into_iter()
-        .take(info.prev_group.threshold)
-        .collect::<Vec<Eval<C::Scalar>>>();
-
-    //println!(" --- recovering on shares: {:?}", shortened_evals);
-    let recovered_share = Poly::recover(info.prev_group.threshold, shortened_evals)
-        .map_err(DKGError::InvalidRecovery)?;
-    // recover public polynomial by interpolating coefficient-wise all
-    // polynomials. the new public polynomial have "newT"
-    // coefficients
-    let recovered_public: PublicPoly<C> = (0..info.new_group.threshold)
-        .map(|cidx| {
-            // interpolate the cidx coefficient of the final public polynomial
-            let to_recover = shares_indexes
+        .filter(|(idx, _)| !equivocators.dealers.contains(idx))
+        .collect::<Vec<_>>();
+    shares.sort_by(|a, b| a.0.cmp(&b.0));
+    let shares_indexes = shares.iter().map(|(idx, _)| *idx).collect::<Vec<Idx>>();
+    let me = info
+        .new_group
+        .node(info.new_index.unwrap())
+        .expect("we are a share holder");
+    let recovered_shares = me
+        .shares()
+        .enumerate()
+        .map(|(i, index)| {
+            let evals = shares
                 .iter()
-                .map(|sh_idx| {
-                    match publics.get(sh_idx) {
-                        Some(poly) => Eval {
-                            // value is the cidx coefficient of that dealer's public
-                            // poly
-                            value: poly.get(cidx as Idx),
-                            // the index is the index from the dealer
-                            index: *sh_idx,
-                        },
-                        None => panic!("BUG: public polynomial evaluating failed"),
-                    }
+                .take(thr)
+                .map(|(idx, sh)| Eval {
+                    value: sh[i].clone(),
+                    index: *idx,
                 })
-                .collect::<Vec<_>>();
+                .collect();
+            let private = Poly::recover(thr, evals).map_err(DKGError::InvalidRecovery)?;
+            Ok(Share { index, private })
+        })
+        .collect::<DKGResult<Vec<_>>>()?;
 
-            // recover the cidx coefficient of the final public polynomial
-            Poly::recover(info.prev_group.threshold, to_recover).map_err(DKGError::InvalidRecovery)
+    // recover public polynomial by interpolating coefficient-wise all
+    // polynomials of the dealers
+    let transcript = shares_indexes
+        .iter()
+        .map(|idx| match publics.get(idx) {
+            Some(poly) => (*idx, poly.clone()),
+            None => panic!("BUG: public polynomial evaluating failed"),
         })
-        .collect::<Result<Vec<C::Point>, DKGError>>()?
-        .into();
+        .collect::<BTreeMap<_, _>>();
+    let recovered_public = recover_public::<C>(
+        info.prev_group.threshold,
+        info.new_group.threshold,
+        &transcript,
+    )?;
     // To compute the QUAL in the resharing case, we take each new nodes whose
-    // column in the status matrix contains true for all valid dealers.
+    // column in the status matrix contains true for all valid dealers and
+    // which did not equivocate.
     let qual = info
         .new_group
         .nodes
-        .into_iter()
+        .iter()
+        .filter(|node| !equivocators.share_holders.contains(&node.id()))
         .filter(|node| {
             shares_indexes
                 .iter()
                 .all(|&sidx| statuses.borrow().get(sidx, node.id()).is_success())
         })
+        .cloned()
         .collect::<Vec<_>>();
 
+    // the dealers without a valid share for us and the share holders out of
+    // QUAL are reported, with the reason recorded along the way if any
+    let mut report = DKGReport::default();
+    for node in &info.prev_group.nodes {
+        if !shares_indexes.contains(&node.id()) {
+            let reason = pending
+                .dealers
+                .remove(&node.id())
+                .unwrap_or(ExclusionReason::UnansweredComplaint);
+            report.exclude_dealer(node.id(), reason);
+        }
+    }
+    for node in &info.new_group.nodes {
+        if !qual.iter().any(|n| n.id() == node.id()) {
+            let reason = pending
+                .share_holders
+                .remove(&node.id())
+                .unwrap_or(ExclusionReason::UnansweredComplaint);
+            report.exclude_share_holder(node.id(), reason);
+        }
+    }
+
+    let qual_indices = qual.iter().map(|n| n.id()).collect::<Vec<_>>();
+    events::finished(info.new_index.unwrap(), &qual_indices, &report);
+
     let qual_group = Group::<C>::new(qual, info.new_group.threshold)?;
     Ok(DKGOutput {
         qual: qual_group,
         public: recovered_public,
-        share: Share {
-            index: info.new_index.unwrap(),
-            private: recovered_share,
-        },
+        shares: recovered_shares,
+        report,
+        transcript,
     })
 }
 
+/// Recovers the public polynomial of a resharing by interpolating
+/// coefficient-wise the public polynomials of the dealers, which were reshared
+/// with `thr` of the previous group. The new public polynomial has
+/// `new_thr` coefficients.
+pub(crate) fn recover_public<C: Curve>(
+    thr: usize,
+    new_thr: usize,
+    dealers: &BTreeMap<Idx, PublicPoly<C>>,
+) -> DKGResult<PublicPoly<C>> {
+    let public = (0..new_thr)
+        .map(|cidx| {
+            // interpolate the cidx coefficient of the final public polynomial
+            let to_recover = dealers
+                .iter()
+                .map(|(idx, poly)| Eval {
+                    // value is the cidx coefficient of that dealer's public
+                    // poly
+                    value: poly.get(cidx as Idx),
+                    // the index is the index from the dealer
+                    index: *idx,
+                })
+                .collect::<Vec<_>>();
+
+            // recover the cidx coefficient of the final public polynomial
+            Poly::recover(thr, to_recover).map_err(DKGError::InvalidRecovery)
+        })
+        .collect::<Result<Vec<C::Point>, DKGError>>()?;
+    Ok(public.into())
+}
+
 // we verify that the public polynomial is created with the public
 // share of the dealer,i.e. it's actually a resharing
 // if it returns false, we must set the dealer's shares as being complaint, all
 // of them since he is not respecting the protocol
-fn check_public_resharing<C: Curve>(
+pub(crate) fn check_public_resharing<C: Curve>(
     dealer_idx: Idx,
     deal_poly: &PublicPoly<C>,
     group_poly: &PublicPoly<C>,
@@ -519,8 +664,12 @@ fn check_public_resharing<C: Curve>(
 mod tests {
     use super::*;
     use crate::primitives::{
-        common::tests::{check2, full_dkg, id_out, id_resp, invalid2, invalid_shares, setup_group},
+        common::tests::{
+            check2, full_dkg, id_out, id_resp, invalid2, invalid_shares, reconstruct, setup_group,
+            setup_weighted_group,
+        },
         default_threshold,
+        joint_feldman::DKG,
     };
     use threshold_bls::{
         curve::bls12377::{G1Curve as BCurve, Scalar, G1},
@@ -576,19 +725,22 @@ mod tests {
             new_group.threshold = old_thr;
         }
 
+        let session = SessionId::new(&new_group, 1, &[]);
         let mut dkgs = prev_privs
             .into_iter()
             .zip(shares.into_iter())
             .map(|(p, sh)| {
                 let out = DKGOutput {
-                    share: Share {
+                    shares: vec![Share {
                         index: sh.index,
                         private: sh.value,
-                    },
+                    }],
                     public: public_poly.clone(),
                     qual: prev_group.clone(),
+                    report: DKGReport::default(),
+                    transcript: BTreeMap::new(),
                 };
-                RDKG::new_from_share(p, out, new_group.clone()).unwrap()
+                RDKG::new_from_share(p, out, new_group.clone(), session).unwrap()
             })
             .collect::<Vec<_>>();
         if new_n > 0 {
@@ -600,6 +752,7 @@ mod tests {
                         prev_group.clone(),
                         public_poly.clone(),
                         new_group.clone(),
+                        session,
                     )
                     .unwrap()
                 }))
@@ -631,6 +784,7 @@ mod tests {
             let nsecret = Poly::<Scalar>::new_from(thr - 1, &mut thread_rng());
             let npublic = nsecret.commit::<G1>();
             s[target_idx] = create_share_bundle(
+                &s[target_idx].session,
                 s[target_idx].dealer_idx,
                 &nsecret,
                 &npublic,
@@ -650,7 +804,19 @@ mod tests {
                 assert_eq!(j[0].dealer_idx, target_idx as u32);
                 j
             },
-            |outs| outs.into_iter().filter(|o| o.share.index != 0).collect(),
+            |outs| {
+                let outs = outs
+                    .into_iter()
+                    .filter(|o| o.shares[0].index != 0)
+                    .collect::<Vec<_>>();
+                for out in &outs {
+                    assert_eq!(
+                        out.report.dealers[&(target_idx as Idx)],
+                        ExclusionReason::InvalidResharing
+                    );
+                }
+                outs
+            },
         )
         .unwrap();
         // test that it gives the same public key
@@ -685,7 +851,11 @@ mod tests {
             // we skip too many justifications such that the protocol should
             // fail
             |bundles| bundles.into_iter().skip(thr - 1).collect(),
-            |outs| outs.into_iter().filter(|o| o.share.index != 0).collect(),
+            |outs| {
+                outs.into_iter()
+                    .filter(|o| o.shares[0].index != 0)
+                    .collect()
+            },
         )
         .unwrap_err();
     }
@@ -738,4 +908,123 @@ mod tests {
         // test that it gives the same public key
         assert_eq!(public.public_key(), reshared.public_key());
     }
+
+    #[test]
+    fn weighted_resharing() {
+        // the nodes have a weight of 1, 2 and 3 before the resharing, and of
+        // 2, 2 and 1 after
+        let (privs, group) = setup_weighted_group::<BCurve>(&[1, 2, 3], default_threshold(6));
+        let session = SessionId::new(&group, 0, &[]);
+        let dkgs = privs
+            .iter()
+            .map(|p| DKG::new(*p, group.clone(), session).unwrap())
+            .collect::<Vec<_>>();
+        let (outputs, public) = full_dkg(group.threshold, dkgs);
+
+        let list = group
+            .nodes
+            .iter()
+            .zip(&[2, 2, 1])
+            .map(|(n, w)| (n.key().clone(), *w))
+            .collect();
+        let new_group = Group::new_weighted(list, default_threshold(5)).unwrap();
+        let session = SessionId::new(&new_group, 1, &[]);
+
+        // each node deals one bundle per share, and is a single share holder
+        let rdkgs = privs
+            .iter()
+            .zip(outputs)
+            .map(|(p, out)| RDKG::new_from_share(*p, out, new_group.clone(), session).unwrap())
+            .collect::<Vec<_>>();
+        let (rdkgs, bundles): (Vec<_>, Vec<_>) = rdkgs
+            .into_iter()
+            .map(|dkg| dkg.encrypt_shares(&mut thread_rng()).unwrap())
+            .unzip();
+        assert!(bundles.iter().map(|b| b.len()).eq(vec![1, 2, 3]));
+        let bundles = bundles.concat();
+        let outputs = rdkgs
+            .into_iter()
+            .map(|dkg| dkg.process_shares(&bundles, false).unwrap().0)
+            .filter_map(|dkg| dkg.process_responses(&[]).ok())
+            .collect::<Vec<_>>();
+
+        assert_eq!(outputs.len(), 3);
+        for (out, node) in outputs.iter().zip(&new_group.nodes) {
+            assert!(out.shares.iter().map(|s| s.index).eq(node.shares()));
+            assert_eq!(out.public.public_key(), public.public_key());
+        }
+        let private = reconstruct(new_group.threshold, &outputs).unwrap();
+        assert_eq!(private.commit::<G1>().public_key(), public.public_key());
+    }
+
+    #[test]
+    fn resharing_with_gaps() {
+        let n = 5;
+        let (privs, group) = setup_group::<BCurve>(n, default_threshold(n));
+        let session = SessionId::new(&group, 0, &[]);
+        let dkgs = privs
+            .iter()
+            .map(|p| DKG::new(*p, group.clone(), session).unwrap())
+            .collect::<Vec<_>>();
+        let (outputs, public) = full_dkg(group.threshold, dkgs);
+
+        // the nodes 0 and 2 leave the group, which keeps its other indices
+        let remaining = [1, 3, 4];
+        let nodes = group
+            .nodes
+            .iter()
+            .filter(|n| remaining.contains(&n.id()))
+            .cloned()
+            .collect();
+        let new_group = Group::new(nodes, 2).unwrap();
+        let session = SessionId::new(&new_group, 1, &[]);
+        let rdkgs = privs
+            .iter()
+            .zip(outputs)
+            .map(|(p, out)| RDKG::new_from_share(*p, out, new_group.clone(), session).unwrap())
+            .collect::<Vec<_>>();
+        let (rdkgs, bundles): (Vec<_>, Vec<_>) = rdkgs
+            .into_iter()
+            .map(|dkg| dkg.encrypt_shares(&mut thread_rng()).unwrap())
+            .unzip();
+        let bundles = bundles.concat();
+        let outputs = rdkgs
+            .into_iter()
+            .map(|dkg| dkg.process_shares(&bundles, false).unwrap().0)
+            .filter_map(|dkg| dkg.process_responses(&[]).ok())
+            .collect::<Vec<_>>();
+        assert_eq!(outputs.len(), 3);
+        for (out, idx) in outputs.iter().zip(&remaining) {
+            assert_eq!(out.shares[0].index, *idx);
+            assert_eq!(out.qual, new_group);
+        }
+
+        // the group left with gaps can reshare again, with a complaint
+        // against one of the dealers
+        let thr = new_group.threshold;
+        let session = SessionId::new(&new_group, 2, &[]);
+        let rdkgs = remaining
+            .iter()
+            .zip(outputs)
+            .map(|(&idx, out)| {
+                RDKG::new_from_share(privs[idx as usize], out, new_group.clone(), session).unwrap()
+            })
+            .collect::<Vec<_>>();
+        let reshared = invalid_shares(
+            thr,
+            rdkgs,
+            |mut s| {
+                s[2].shares[0].secret = ecies::encrypt(&G1::one(), &[1], &mut thread_rng());
+                s
+            },
+            id_resp,
+            |j| {
+                assert_eq!(j.len(), 1);
+                j
+            },
+            id_out,
+        )
+        .unwrap();
+        assert_eq!(public.public_key(), reshared.public_key());
+    }
 }
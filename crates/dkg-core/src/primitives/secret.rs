@@ -0,0 +1,231 @@
+//! Shares arbitrary byte secrets (API keys, seeds, ...) among the members of a
+//! group, e.g. the committee which ran a DKG.
+//!
+//! The secret is encrypted with ECIES to a fresh key pair, whose private key is
+//! split with a polynomial of degree `threshold - 1`: each node receives the
+//! evaluations at its share indices, encrypted to its own key. The public
+//! polynomial of the key is published along with them, so that each node can
+//! check its shares, and any threshold of shares recover the private key, and
+//! then the secret.
+use crate::primitives::{
+    common::{eval_shares, shares_correct},
+    errors::SecretError,
+    group::{Group, Node},
+    types::EncryptedShare,
+    DKGResult,
+};
+
+use rand_core::RngCore;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::BTreeMap;
+use threshold_bls::{
+    ecies::{self, EciesCipher},
+    group::Curve,
+    poly::{Eval, Poly, PrivatePoly, PublicPoly},
+    sig::Share,
+};
+
+/// SharedSecret is published by the dealer of a byte secret. It holds the
+/// encrypted secret, and the encrypted shares of the key it is encrypted to.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "C::Scalar: DeserializeOwned")]
+pub struct SharedSecret<C: Curve> {
+    /// The number of shares required to recover the secret
+    pub threshold: usize,
+    /// The public polynomial of the shared key, whose public key is the one
+    /// the secret is encrypted to
+    pub public: PublicPoly<C>,
+    /// The encrypted secret
+    pub secret: EciesCipher<C>,
+    /// The shares of each node of the group, encrypted to its key
+    pub shares: Vec<EncryptedShare<C>>,
+}
+
+impl<C: Curve> SharedSecret<C> {
+    /// Splits the secret among the nodes of the group, so that the threshold
+    /// of the group (counted in shares for weighted groups) is required to
+    /// recover it. The group is validated first, since encrypting a share to
+    /// an invalid key, e.g. the identity, would reveal it.
+    pub fn new<R: RngCore>(secret: &[u8], group: &Group<C>, rng: &mut R) -> DKGResult<Self> {
+        group.validate()?;
+
+        let private = PrivatePoly::<C>::new_from(group.threshold - 1, rng);
+        let public = private.commit::<C::Point>();
+        let secret = ecies::encrypt::<C, _>(public.public_key(), secret, rng);
+
+        let shares = group
+            .nodes
+            .iter()
+            .map(|node| {
+                let buff = bincode::serialize(&eval_shares(node, &private))?;
+                Ok(EncryptedShare {
+                    share_idx: node.id(),
+                    secret: ecies::encrypt::<C, _>(node.key(), &buff, rng),
+                })
+            })
+            .collect::<DKGResult<Vec<_>>>()?;
+
+        Ok(Self {
+            threshold: group.threshold,
+            public,
+            secret,
+            shares,
+        })
+    }
+
+    /// Decrypts the shares of the node with its private key, and checks them
+    /// against the public polynomial.
+    pub fn decrypt_shares(
+        &self,
+        node: &Node<C>,
+        private_key: &C::Scalar,
+    ) -> DKGResult<Vec<Share<C::Scalar>>> {
+        let idx = node.id();
+        let share = self
+            .shares
+            .iter()
+            .find(|share| share.share_idx == idx)
+            .ok_or(SecretError::NotRecipient(idx))?;
+        let buff = ecies::decrypt::<C>(private_key, &share.secret)
+            .map_err(|err| SecretError::InvalidCiphertext(idx, err))?;
+        let shares: Vec<C::Scalar> = bincode::deserialize(&buff)?;
+
+        if self.public.degree() + 1 != self.threshold
+            || !shares_correct::<C>(node, &shares, &self.public)
+        {
+            return Err(SecretError::InvalidShares(idx).into());
+        }
+
+        Ok(node
+            .shares()
+            .zip(shares)
+            .map(|(index, private)| Share { index, private })
+            .collect())
+    }
+
+    /// Recovers the secret from the decrypted shares of the nodes. At least a
+    /// threshold of distinct share indices are required.
+    pub fn recover(&self, shares: &[Share<C::Scalar>]) -> DKGResult<Vec<u8>> {
+        let evals = shares
+            .iter()
+            .map(|share| (share.index, share.private.clone()))
+            .collect::<BTreeMap<_, _>>();
+        if evals.len() < self.threshold {
+            return Err(SecretError::NotEnoughShares(evals.len(), self.threshold).into());
+        }
+
+        let evals = evals
+            .into_iter()
+            .map(|(index, value)| Eval { index, value })
+            .collect();
+        let key = Poly::<C::Scalar>::recover(self.threshold, evals)?;
+        if &C::generator_table().mul(&key) != self.public.public_key() {
+            return Err(SecretError::InvalidKey.into());
+        }
+
+        ecies::decrypt::<C>(&key, &self.secret).map_err(|_| SecretError::InvalidSecret.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives::{
+        common::tests::{setup_group, setup_weighted_group},
+        DKGError,
+    };
+    use rand::thread_rng;
+    use threshold_bls::{curve::bls12377::G1Curve as BCurve, group::Element};
+
+    #[test]
+    fn share_and_recover() {
+        let rng = &mut thread_rng();
+        let msg = b"an api key or a seed, of any length".to_vec();
+        let (privs, group) = setup_weighted_group::<BCurve>(&[1, 2, 1, 3], 4);
+        let shared = SharedSecret::new(&msg, &group, rng).unwrap();
+        assert_eq!(shared.shares.len(), group.len());
+
+        let shares = group
+            .nodes
+            .iter()
+            .zip(&privs)
+            .map(|(node, private)| shared.decrypt_shares(node, private).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(shares[3].len(), 3);
+
+        // nodes 1 and 3 hold 5 shares, nodes 0 and 2 only 2 of them
+        assert!(matches!(
+            shared.recover(&shares[3]).unwrap_err(),
+            DKGError::SecretError(SecretError::NotEnoughShares(3, 4))
+        ));
+        let subset = [shares[1].clone(), shares[3].clone()].concat();
+        assert_eq!(shared.recover(&subset).unwrap(), msg);
+        let subset = [shares[0].clone(), shares[2].clone(), shares[0].clone()].concat();
+        assert!(matches!(
+            shared.recover(&subset).unwrap_err(),
+            DKGError::SecretError(SecretError::NotEnoughShares(2, 4))
+        ));
+        let all = shares.concat();
+        assert_eq!(shared.recover(&all).unwrap(), msg);
+
+        // an incorrect share is detected
+        let mut invalid = subset[..1].to_vec();
+        invalid.extend_from_slice(&shares[3]);
+        invalid[0].private.add(&<BCurve as Curve>::Scalar::one());
+        assert!(matches!(
+            shared.recover(&invalid).unwrap_err(),
+            DKGError::SecretError(SecretError::InvalidKey)
+        ));
+    }
+
+    #[test]
+    fn invalid_shares() {
+        let rng = &mut thread_rng();
+        let (privs, group) = setup_group::<BCurve>(4, 3);
+        let shared = SharedSecret::new(&[1, 9, 6, 9], &group, rng).unwrap();
+
+        // decrypting with the key of another node
+        let err = shared
+            .decrypt_shares(&group.nodes[0], &privs[1])
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            DKGError::SecretError(SecretError::InvalidCiphertext(0, _))
+        ));
+
+        // shares encrypted to the right node, but of another polynomial
+        let mut other = SharedSecret::new(&[1, 9, 6, 9], &group, rng).unwrap();
+        other.public = shared.public.clone();
+        let err = other
+            .decrypt_shares(&group.nodes[2], &privs[2])
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            DKGError::SecretError(SecretError::InvalidShares(2))
+        ));
+
+        let mut missing = shared.clone();
+        missing.shares.remove(1);
+        let err = missing
+            .decrypt_shares(&group.nodes[1], &privs[1])
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            DKGError::SecretError(SecretError::NotRecipient(1))
+        ));
+
+        let mut group = group;
+        group.threshold = 5;
+        let err = SharedSecret::new(&[1, 9, 6, 9], &group, rng).unwrap_err();
+        assert!(matches!(err, DKGError::InvalidThreshold(5, 1, 4)));
+    }
+
+    #[test]
+    fn identity_key() {
+        let rng = &mut thread_rng();
+        let (_, mut group) = setup_group::<BCurve>(4, 3);
+        group.nodes[1] = Node::new(1, <BCurve as Curve>::Point::zero());
+        let err = SharedSecret::new(&[1, 9, 6, 9], &group, rng).unwrap_err();
+        assert!(matches!(err, DKGError::InvalidPublicKey(1)));
+    }
+}
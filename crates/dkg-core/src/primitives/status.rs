@@ -42,6 +42,10 @@ impl Status {
 /// A `StatusMatrix` is a 2D binary array containing `Status::Success` or `Status::Complaint`
 /// values. Under the hood, it utilizes [`bitvec`]
 ///
+/// The rows and columns are addressed by the indices of the dealers and of the
+/// share holders, which do not need to be contiguous (e.g. after a resharing
+/// removed some nodes from the group).
+///
 /// # Examples
 ///
 /// ```rust,ignore
@@ -65,15 +69,24 @@ impl Status {
 ///
 /// // check if all values in a row are OK
 /// let all_ones: bool = matrix.all_true(2);
+///
+/// // a matrix for a group with gaps in its indices
+/// let matrix = StatusMatrix::with_indices(&[0, 3, 7], &[0, 3, 7], Status::Success);
 /// ```
 ///
 /// [`bitvec`]: http://docs.rs/bitvec/0.17.4/
 #[derive(Clone, Debug, Serialize, Deserialize, Hash, PartialEq, Eq)]
-pub struct StatusMatrix(Vec<BitVec>);
+pub struct StatusMatrix {
+    /// sorted indices of the dealers, one per row
+    dealers: Vec<Idx>,
+    /// sorted indices of the share holders, one per column
+    share_holders: Vec<Idx>,
+    statuses: Vec<BitVec>,
+}
 
 impl AsRef<[BitVec]> for StatusMatrix {
     fn as_ref(&self) -> &[BitVec] {
-        &self.0
+        &self.statuses
     }
 }
 
@@ -82,13 +95,13 @@ impl IntoIterator for StatusMatrix {
     type IntoIter = std::vec::IntoIter<Self::Item>;
 
     fn into_iter(self) -> Self::IntoIter {
-        self.0.into_iter()
+        self.statuses.into_iter()
     }
 }
 
 impl fmt::Display for StatusMatrix {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        for (dealer, shares) in self.0.iter().enumerate() {
+        for (dealer, shares) in self.dealers.iter().zip(&self.statuses) {
             writeln!(f, "-> dealer {}: {}", dealer, shares)?;
         }
         Ok(())
@@ -97,81 +110,111 @@ impl fmt::Display for StatusMatrix {
 
 impl StatusMatrix {
     /// Returns a MxN Status Matrix (M = dealers, N = share_holders) where all elements
-    /// are initialized to `default`.
-    ///
-    /// # Panics
-    ///
-    /// If `dealers > share_holders`
+    /// are initialized to `default`. The dealers and share holders are indexed
+    /// from 0 to M-1 and N-1 respectively.
+    #[allow(unused)]
     pub fn new(dealers: usize, share_holders: usize, default: Status) -> StatusMatrix {
-        let m = (0..dealers)
-            .map(|_| bitvec![default.to_bool() as u8; share_holders])
+        let dealers = (0..dealers as Idx).collect::<Vec<_>>();
+        let share_holders = (0..share_holders as Idx).collect::<Vec<_>>();
+        Self::with_indices(&dealers, &share_holders, default)
+    }
+
+    /// Returns a Status Matrix with a row for each of the dealer indices and a
+    /// column for each of the share holder indices, where all elements are
+    /// initialized to `default`.
+    pub fn with_indices(dealers: &[Idx], share_holders: &[Idx], default: Status) -> StatusMatrix {
+        let mut dealers = dealers.to_vec();
+        dealers.sort_unstable();
+        dealers.dedup();
+        let mut share_holders = share_holders.to_vec();
+        share_holders.sort_unstable();
+        share_holders.dedup();
+
+        let statuses = dealers
+            .iter()
+            .map(|_| bitvec![default.to_bool() as u8; share_holders.len()])
             .collect();
+        Self {
+            dealers,
+            share_holders,
+            statuses,
+        }
+    }
+
+    /// Returns the indices of the dealers, in the order of the rows
+    pub fn dealers(&self) -> &[Idx] {
+        &self.dealers
+    }
+
+    /// Returns the indices of the share holders, in the order of the columns
+    pub fn share_holders(&self) -> &[Idx] {
+        &self.share_holders
+    }
+
+    fn row(&self, dealer: Idx) -> usize {
+        self.dealers
+            .binary_search(&dealer)
+            .expect("dealer index out of bounds")
+    }
 
-        Self(m)
+    fn column(&self, share: Idx) -> usize {
+        self.share_holders
+            .binary_search(&share)
+            .expect("share index out of bounds")
     }
 
     /// Sets an element at the cell corresponding to (dealer, share) to `status`.
     ///
     /// # Panics
     ///
-    /// - If the `share` index is greater than the number of shareholders
-    /// - If the `dealer` index is greater than the number of dealers
+    /// - If the `share` index is not one of the shareholders
+    /// - If the `dealer` index is not one of the dealers
     pub fn set(&mut self, dealer: Idx, share: Idx, status: Status) {
-        self.0[dealer as usize].set(share as usize, status.to_bool());
+        let (row, column) = (self.row(dealer), self.column(share));
+        self.statuses[row].set(column, status.to_bool());
     }
 
     /// Gets the element at the cell corresponding to (dealer, share)
     ///
     /// # Panics
     ///
-    /// - If the `share` index is greater than the number of shareholders
-    /// - If the `dealer` index is greater than the number of dealers
+    /// - If the `share` index is not one of the shareholders
+    /// - If the `dealer` index is not one of the dealers
     #[allow(unused)]
     pub fn get(&self, dealer: Idx, share: Idx) -> Status {
-        Status::from(
-            *self.0[dealer as usize]
-                .get(share as usize)
-                .expect("share index out of bounds"),
-        )
+        let (row, column) = (self.row(dealer), self.column(share));
+        Status::from(self.statuses[row][column])
     }
 
-    /// Returns the column corresponding to the shareholder at `share`.
+    /// Returns the column corresponding to the shareholder at `share`, with
+    /// one bit per dealer in the order of `dealers()`.
     ///
     /// This will allocate a new vector, and as such changing the underlying
     /// status matrix will _not_ affect the returned value.
     ///
     /// # Panics
     ///
-    /// If the `share` index is greater than the number of shareholders
+    /// If the `share` index is not one of the shareholders
     pub fn get_for_share(&self, share: Idx) -> BitVec {
-        let mut col = bitvec![0; self.0.len()];
-
-        for (dealer_idx, shares) in self.0.iter().enumerate() {
-            let share_bit = shares
-                .get(share as usize)
-                .expect("share index out of bounds");
-            col.set(dealer_idx, *share_bit);
-        }
-
-        col
+        let column = self.column(share);
+        self.statuses.iter().map(|shares| shares[column]).collect()
     }
 
-    /// Returns the row corresponding to the dealer at `dealer`
+    /// Returns the row corresponding to the dealer at `dealer`, with one bit
+    /// per share holder in the order of `share_holders()`.
     ///
     /// # Panics
     ///
-    /// If the `dealer` index is greater than the number of dealers
+    /// If the `dealer` index is not one of the dealers
     pub fn get_for_dealer(&self, dealer: Idx) -> &BitVec {
-        self.0
-            .get(dealer as usize)
-            .expect("dealer index out of bounds")
+        &self.statuses[self.row(dealer)]
     }
 
     /// Returns `true` if the row corresponding to `dealer` is all 1s.
     ///
     /// # Panics
     ///
-    /// If the `dealer` index is greater than the number of dealers
+    /// If the `dealer` index is not one of the dealers
     pub fn all_true(&self, dealer: Idx) -> bool {
         self.get_for_dealer(dealer).all()
     }
@@ -218,6 +261,25 @@ mod tests {
         matrix.get_for_share(5);
     }
 
+    #[test]
+    fn sparse_indices() {
+        let mut matrix = StatusMatrix::with_indices(&[7, 0, 3], &[3, 9], Status::Success);
+        assert_eq!(matrix.dealers(), &[0, 3, 7]);
+        matrix.set(7, 9, Status::Complaint);
+        assert_eq!(matrix.get(7, 9), Status::Complaint);
+        assert_eq!(matrix.get_for_dealer(7), &bitvec![1, 0]);
+        assert_eq!(matrix.get_for_share(9), bitvec![1, 1, 0]);
+        assert!(matrix.all_true(3));
+        assert!(!matrix.all_true(7));
+    }
+
+    #[test]
+    #[should_panic(expected = "dealer index out of bounds")]
+    fn missing_dealer() {
+        let matrix = StatusMatrix::with_indices(&[0, 3, 7], &[0, 3, 7], Status::Complaint);
+        matrix.get_for_dealer(1);
+    }
+
     #[test]
     fn display() {
         let matrix = StatusMatrix::new(3, 3, Status::Complaint);
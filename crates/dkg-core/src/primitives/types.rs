@@ -1,7 +1,15 @@
-use crate::primitives::{group::Group, status::Status};
+use crate::primitives::{
+    auth::{Bundle, Signed},
+    group::Group,
+    status::Status,
+};
 
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
-use std::fmt::Debug;
+use sha2::{Digest, Sha256};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Debug,
+};
 use threshold_bls::{
     ecies::EciesCipher,
     group::Curve,
@@ -18,8 +26,117 @@ pub struct DKGOutput<C: Curve> {
     pub qual: Group<C>,
     /// The distributed public key
     pub public: PublicPoly<C>,
-    /// The private share which corresponds to the participant's index
-    pub share: Share<C::Scalar>,
+    /// The private shares which correspond to the participant's share indices,
+    /// a single one unless the group is weighted
+    pub shares: Vec<Share<C::Scalar>>,
+    /// The participants which were excluded from the protocol and why
+    pub report: DKGReport,
+    /// The public polynomials of the dealers whose shares were combined into
+    /// this output, by dealer index. In a resharing, it proves that the public
+    /// polynomial shares the same key as the previous one (see `chain`).
+    pub transcript: BTreeMap<Idx, PublicPoly<C>>,
+}
+
+/// The reason why a participant was excluded from the DKG
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExclusionReason {
+    /// The dealer did not publish its shares during the first phase
+    MissingBundle,
+    /// The dealer's bundle did not contain a share for us
+    MissingShare,
+    /// The dealer's public polynomial has a degree different from
+    /// `threshold - 1`. The two fields are the degree of the polynomial and
+    /// the expected degree.
+    InvalidPolynomialDegree(usize, usize),
+    /// The dealer's share for us could not be decrypted
+    InvalidCiphertext,
+    /// The dealer's share for us does not match its public polynomial
+    InvalidShare,
+    /// A complaint against the dealer was not answered by a valid
+    /// justification, or the complaint of the share holder was not answered
+    UnansweredComplaint,
+    /// The dealer's public polynomial does not reshare its previous share
+    InvalidResharing,
+    /// The dealer's public polynomial does not commit to a zero secret during
+    /// a refresh
+    InvalidRefresh,
+    /// The participant published conflicting bundles
+    Equivocation,
+}
+
+/// DKGReport lists the dealers and share holders which were excluded from
+/// the DKG, along with the reason of their exclusion. In the JF-DKG every
+/// participant is both a dealer and a share holder.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DKGReport {
+    /// The excluded dealers, by index
+    pub dealers: BTreeMap<Idx, ExclusionReason>,
+    /// The excluded share holders, by index
+    pub share_holders: BTreeMap<Idx, ExclusionReason>,
+}
+
+impl DKGReport {
+    /// Records the exclusion of a dealer. Only the first reason given for a
+    /// dealer is kept.
+    pub fn exclude_dealer(&mut self, idx: Idx, reason: ExclusionReason) {
+        self.dealers.entry(idx).or_insert(reason);
+    }
+
+    /// Records the exclusion of a share holder. Only the first reason given
+    /// for a share holder is kept.
+    pub fn exclude_share_holder(&mut self, idx: Idx, reason: ExclusionReason) {
+        self.share_holders.entry(idx).or_insert(reason);
+    }
+
+    /// Records the exclusion of the participants which equivocated
+    pub fn exclude_equivocations(&mut self, equivocators: &Equivocators) {
+        for &dealer in &equivocators.dealers {
+            self.exclude_dealer(dealer, ExclusionReason::Equivocation);
+        }
+        for &holder in &equivocators.share_holders {
+            self.exclude_share_holder(holder, ExclusionReason::Equivocation);
+        }
+    }
+
+    /// Returns true if no participant was excluded
+    pub fn is_empty(&self) -> bool {
+        self.dealers.is_empty() && self.share_holders.is_empty()
+    }
+}
+
+/// A domain separator
+const SESSION_DOMAIN: &[u8] = b"dkg-core session";
+
+/// SessionId identifies a single run of the DKG or of a resharing. It is
+/// included in every bundle and bound to the encryption of the shares, so that
+/// the messages of a run cannot be replayed into another run with the same
+/// group.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SessionId([u8; 32]);
+
+impl SessionId {
+    /// Derives the session identifier from the group receiving the shares (its
+    /// nodes and threshold), the epoch of the run and a nonce which all the
+    /// participants agree on, e.g. a block hash.
+    pub fn new<C: Curve>(group: &Group<C>, epoch: u64, nonce: &[u8]) -> Self {
+        let nodes = bincode::serialize(&group.nodes).expect("could not serialize nodes");
+        let hash = Sha256::new()
+            .chain(SESSION_DOMAIN)
+            .chain(&nodes)
+            .chain((group.threshold as u64).to_le_bytes())
+            .chain(epoch.to_le_bytes())
+            .chain(nonce)
+            .result();
+
+        let mut id = [0u8; 32];
+        id.copy_from_slice(&hash);
+        Self(id)
+    }
+
+    /// Returns the bytes of the session identifier
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
 }
 
 /// BundledShares holds all encrypted shares a dealer creates during the first
@@ -27,6 +144,8 @@ pub struct DKGOutput<C: Curve> {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(bound = "C::Scalar: DeserializeOwned")]
 pub struct BundledShares<C: Curve> {
+    /// The session in which the shares were created
+    pub session: SessionId,
     /// The dealer's index
     pub dealer_idx: Idx,
     /// The encrypted shared created by the dealer
@@ -37,16 +156,16 @@ pub struct BundledShares<C: Curve> {
     pub public: PublicPoly<C>,
 }
 
-/// EncryptedShare holds the ECIES encryption of a share destined to the
-/// `share_idx`-th participant. When receiving the share, if the participant has
-/// the same specified index, the corresponding dkg state decrypts the share using
-/// the participant's private key.
+/// EncryptedShare holds the ECIES encryption of the shares destined to the
+/// `share_idx`-th participant, one for each of its share indices. When
+/// receiving the shares, if the participant has the same specified index, the
+/// corresponding dkg state decrypts them using the participant's private key.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(bound = "C::Scalar: DeserializeOwned")]
 pub struct EncryptedShare<C: Curve> {
     /// The index of the participant this share belongs to
     pub share_idx: Idx,
-    /// The ECIES encrypted share
+    /// The ECIES encrypted shares
     pub secret: EciesCipher<C>,
 }
 
@@ -57,21 +176,23 @@ pub struct EncryptedShare<C: Curve> {
 /// index of the participant that created the share (a *dealer*),
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BundledResponses {
+    /// The session in which the responses were created
+    pub session: SessionId,
     /// share_idx is the index of the node that received the shares
     pub share_idx: Idx,
     /// A vector of responses from each share creator
     pub responses: Vec<Response>,
 }
 
-/// A `Justification` contains the share of the share holder that issued a
+/// A `Justification` contains the shares of the share holder that issued a
 /// complaint, in plaintext.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(bound = "C::Scalar: DeserializeOwned")]
 pub struct Justification<C: Curve> {
     /// The share holder's index
     pub share_idx: Idx,
-    /// The plaintext share
-    pub share: C::Scalar,
+    /// The plaintext shares, one for each of the share holder's share indices
+    pub shares: Vec<C::Scalar>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -79,6 +200,8 @@ pub struct Justification<C: Curve> {
 /// A BundledJustification is broadcast by a dealer and contains the justifications
 /// they have received along with their corresponding Public polynomial
 pub struct BundledJustification<C: Curve> {
+    /// The session in which the justifications were created
+    pub session: SessionId,
     /// The dealer's index
     pub dealer_idx: Idx,
     /// The justifications
@@ -95,3 +218,90 @@ pub struct Response {
     /// The status of the response (whether it suceeded or if there were complaints)
     pub status: Status,
 }
+
+/// Equivocators lists the participants which published conflicting bundles
+/// during the same phase of a session. They are disqualified from the DKG.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Equivocators {
+    /// The dealers which published conflicting shares or justifications
+    pub dealers: BTreeSet<Idx>,
+    /// The share holders which published conflicting responses
+    pub share_holders: BTreeSet<Idx>,
+}
+
+impl Equivocators {
+    /// Returns true if no participant equivocated
+    pub fn is_empty(&self) -> bool {
+        self.dealers.is_empty() && self.share_holders.is_empty()
+    }
+}
+
+/// An Equivocation proves that a participant signed two conflicting bundles
+/// during the same phase of a session, e.g. to give different share holders
+/// shares of different polynomials. It is collected by the
+/// `AuthenticatedBoard`, and can be submitted to a third party, e.g. to slash
+/// the participant.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "C::Scalar: DeserializeOwned")]
+pub enum Equivocation<C: Curve> {
+    /// A dealer signed two different bundles of shares
+    Shares(Signed<C, BundledShares<C>>, Signed<C, BundledShares<C>>),
+    /// A share holder signed two different bundles of responses
+    Responses(Signed<C, BundledResponses>, Signed<C, BundledResponses>),
+    /// A dealer signed two different bundles of justifications
+    Justifications(
+        Signed<C, BundledJustification<C>>,
+        Signed<C, BundledJustification<C>>,
+    ),
+}
+
+impl<C: Curve> Equivocation<C> {
+    /// Returns the index of the participant which equivocated. It is a dealer
+    /// index for shares and justifications, and a share holder index for
+    /// responses.
+    pub fn culprit(&self) -> Idx {
+        match self {
+            Equivocation::Shares(first, _) => first.bundle.sender(),
+            Equivocation::Responses(first, _) => first.bundle.sender(),
+            Equivocation::Justifications(first, _) => first.bundle.sender(),
+        }
+    }
+
+    /// Returns true if the equivocation was made by a dealer
+    pub fn is_dealer(&self) -> bool {
+        !matches!(self, Equivocation::Responses(_, _))
+    }
+
+    /// Checks that both bundles belong to the session, were created by the
+    /// same participant and are different, and that both are signed by the
+    /// key registered for the culprit in the group. The group is the one of
+    /// the dealers for shares and justifications, and the one of the share
+    /// holders for responses.
+    pub fn verify(&self, session: &SessionId, group: &Group<C>) -> bool {
+        match self {
+            Equivocation::Shares(first, second) => proves(session, group, first, second),
+            Equivocation::Responses(first, second) => proves(session, group, first, second),
+            Equivocation::Justifications(first, second) => proves(session, group, first, second),
+        }
+    }
+}
+
+// Returns true if both bundles conflict and are signed by their sender
+fn proves<C: Curve, T: Bundle>(
+    session: &SessionId,
+    group: &Group<C>,
+    first: &Signed<C, T>,
+    second: &Signed<C, T>,
+) -> bool {
+    conflicting(session, &first.bundle, &second.bundle)
+        && first.verify(group)
+        && second.verify(group)
+}
+
+/// Returns true if both bundles are from the session and sender but differ
+pub(crate) fn conflicting<T: Bundle>(session: &SessionId, first: &T, second: &T) -> bool {
+    first.session() == session
+        && second.session() == session
+        && first.sender() == second.sender()
+        && bincode::serialize(first).ok() != bincode::serialize(second).ok()
+}
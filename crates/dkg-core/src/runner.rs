@@ -0,0 +1,614 @@
+//! # Runner
+//!
+//! The runner drives a DKG participant through all the phases of the protocol,
+//! publishing its own bundles and reading the other participants' bundles from
+//! the board once the deadline of each phase has passed.
+use super::{
+    board::{BoardPhase, BoardPublisher, BoardReader},
+    checkpoint::CheckpointStore,
+    node::{DKGPhase, NodeError, NodeResult, Phase2Result},
+    primitives::{
+        phases::{Phase0, Phase1, Phase2},
+        types::{BundledShares, DKGOutput},
+    },
+};
+
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::{marker::PhantomData, time::Duration};
+use threshold_bls::group::Curve;
+
+/// The amount of time given to the participants to publish their bundles
+/// during each phase.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PhaseTimeouts {
+    /// Duration of the phase during which the shares are published
+    pub shares: Duration,
+    /// Duration of the phase during which the responses are published
+    pub responses: Duration,
+    /// Duration of the phase during which the justifications are published
+    pub justifications: Duration,
+}
+
+impl Default for PhaseTimeouts {
+    fn default() -> Self {
+        let timeout = Duration::from_secs(60);
+        Self {
+            shares: timeout,
+            responses: timeout,
+            justifications: timeout,
+        }
+    }
+}
+
+type Phase2Of<C, P> = <<P as Phase0<C>>::Next as Phase1<C>>::Next;
+type Phase3Of<C, P> = <Phase2Of<C, P> as Phase2<C>>::Next;
+
+/// DKGState is the state of a participant between two phases of the DKG,
+/// starting from the given `Phase0` implementation. Each variant holds the
+/// phase which is next to run, after the bundles of the previous phases have
+/// been published.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub enum DKGState<C: Curve, P: Phase0<C>> {
+    /// The participant has not dealt its shares yet
+    Phase0(P),
+    /// The participant dealt its shares, which may not have been published
+    /// yet. Resuming from this state publishes the same bundles again rather
+    /// than dealing a new polynomial, which would be an equivocation.
+    Dealt(P::Next, Vec<BundledShares<C>>),
+    /// The participant waits for the shares of the other participants
+    Phase1(P::Next),
+    /// The participant waits for the responses of the other participants
+    Phase2(Phase2Of<C, P>),
+    /// The participant waits for the justifications of the other participants
+    Phase3(Phase3Of<C, P>),
+}
+
+/// DKGRunner runs a DKG participant from its first phase up to the final
+/// output. It works with any [`Phase0`] implementation, i.e. both the JF-DKG
+/// (`joint_feldman::DKG`) and the resharing scheme (`resharing::RDKG`).
+///
+/// [`Phase0`]: primitives/phases/trait.Phase0.html
+pub struct DKGRunner<C, B> {
+    board: B,
+    timeouts: PhaseTimeouts,
+    m: PhantomData<C>,
+}
+
+impl<C, B> DKGRunner<C, B>
+where
+    C: Curve,
+    B: BoardPublisher<C> + BoardReader<C>,
+{
+    /// Creates a new runner which communicates with the other participants
+    /// over the provided board.
+    pub fn new(board: B, timeouts: PhaseTimeouts) -> Self {
+        Self {
+            board,
+            timeouts,
+            m: PhantomData,
+        }
+    }
+
+    /// Returns a reference to the board
+    pub fn board(&self) -> &B {
+        &self.board
+    }
+
+    /// Consumes the runner and returns its board
+    pub fn into_board(self) -> B {
+        self.board
+    }
+
+    /// Runs the DKG starting from the provided first phase until the
+    /// protocol either finishes or fails. Phase 3 is only run if some
+    /// justifications are required after processing the responses.
+    pub async fn run<P, R>(&mut self, phase0: P, rng: &mut R) -> NodeResult<DKGOutput<C>>
+    where
+        P: Phase0<C>,
+        R: RngCore,
+    {
+        self.resume(DKGState::Phase0(phase0), rng).await
+    }
+
+    /// Runs the DKG from the provided state until the protocol either finishes
+    /// or fails.
+    pub async fn resume<P, R>(
+        &mut self,
+        state: DKGState<C, P>,
+        rng: &mut R,
+    ) -> NodeResult<DKGOutput<C>>
+    where
+        P: Phase0<C>,
+        R: RngCore,
+    {
+        self.drive(state, rng, |_| Ok(())).await
+    }
+
+    /// Runs the DKG from the provided state and saves the state to the store
+    /// after each phase, so that the participant can resume from the store
+    /// (see [`CheckpointStore::load`]) after a crash. The checkpoint is
+    /// removed once the DKG finishes successfully.
+    ///
+    /// The dealt shares are saved before they are published, so that a
+    /// participant which crashes right after publishing them publishes the
+    /// same bundle again once it resumes.
+    ///
+    /// [`CheckpointStore::load`]: struct.CheckpointStore.html#method.load
+    pub async fn run_with_checkpoints<P, R>(
+        &mut self,
+        state: DKGState<C, P>,
+        store: &CheckpointStore,
+        rng: &mut R,
+    ) -> NodeResult<DKGOutput<C>>
+    where
+        P: Phase0<C>,
+        R: RngCore,
+    {
+        let output = self
+            .drive(state, rng, |state| Ok(store.save(state)?))
+            .await?;
+        store.clear()?;
+        Ok(output)
+    }
+
+    async fn drive<P, R, F>(
+        &mut self,
+        mut state: DKGState<C, P>,
+        rng: &mut R,
+        mut checkpoint: F,
+    ) -> NodeResult<DKGOutput<C>>
+    where
+        P: Phase0<C>,
+        R: RngCore,
+        F: FnMut(&DKGState<C, P>) -> NodeResult<()>,
+    {
+        loop {
+            state = match state {
+                DKGState::Phase0(phase0) => {
+                    let (next, shares) = phase0.encrypt_shares(rng)?;
+                    DKGState::Dealt(next, shares)
+                }
+                DKGState::Dealt(next, shares) => {
+                    for shares in shares {
+                        self.board
+                            .publish_shares(shares)
+                            .await
+                            .map_err(|_| NodeError::PublisherError)?;
+                    }
+                    DKGState::Phase1(next)
+                }
+                DKGState::Phase1(phase1) => {
+                    self.wait_for_deadline(BoardPhase::Shares).await?;
+                    let shares = self
+                        .board
+                        .read_shares()
+                        .await
+                        .map_err(|_| NodeError::ReaderError)?;
+                    DKGState::Phase2(phase1.run(&mut self.board, &shares[..]).await?)
+                }
+                DKGState::Phase2(phase2) => {
+                    self.wait_for_deadline(BoardPhase::Responses).await?;
+                    let responses = self
+                        .board
+                        .read_responses()
+                        .await
+                        .map_err(|_| NodeError::ReaderError)?;
+                    match phase2.run(&mut self.board, &responses[..]).await? {
+                        Phase2Result::Output(output) => return Ok(output),
+                        Phase2Result::GoToPhase3(phase3) => DKGState::Phase3(phase3),
+                    }
+                }
+                DKGState::Phase3(phase3) => {
+                    self.wait_for_deadline(BoardPhase::Justifications).await?;
+                    let justifications = self
+                        .board
+                        .read_justifications()
+                        .await
+                        .map_err(|_| NodeError::ReaderError)?;
+                    return phase3.run(&mut self.board, &justifications[..]).await;
+                }
+            };
+            checkpoint(&state)?;
+        }
+    }
+
+    async fn wait_for_deadline(&mut self, phase: BoardPhase) -> NodeResult<()> {
+        let timeout = match phase {
+            BoardPhase::Shares => self.timeouts.shares,
+            BoardPhase::Responses => self.timeouts.responses,
+            BoardPhase::Justifications => self.timeouts.justifications,
+        };
+        self.board
+            .wait_for_deadline(phase, timeout)
+            .await
+            .map_err(|_| NodeError::ReaderError)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        board::{AuthenticatedBoard, BoardTransport},
+        checkpoint::CheckpointKey,
+        primitives::{
+            group::{Group, Node},
+            joint_feldman, resharing,
+            types::{BundledShares, Equivocation, SessionId},
+            Signed,
+        },
+        test_helpers::{SharedBoard, SharedTransport},
+    };
+
+    use threshold_bls::{
+        curve::bls12377::{G1Curve, PairingCurve as BLS12_377},
+        poly::Idx,
+        sig::{G1Scheme, Scheme},
+    };
+    use tokio::task::LocalSet;
+
+    type C = G1Curve;
+    type S = G1Scheme<BLS12_377>;
+
+    fn setup(n: usize, t: usize) -> (Vec<<S as Scheme>::Private>, Group<C>) {
+        let rng = &mut rand::thread_rng();
+        let keypairs = (0..n).map(|_| S::keypair(rng)).collect::<Vec<_>>();
+        let nodes = keypairs
+            .iter()
+            .enumerate()
+            .map(|(i, (_, public))| Node::<C>::new(i as Idx, public.clone()))
+            .collect::<Vec<_>>();
+        let group = Group::new(nodes, t).unwrap();
+        let privs = keypairs.into_iter().map(|(private, _)| private).collect();
+        (privs, group)
+    }
+
+    // spawns one runner per phase0 on a shared board and returns their outputs
+    async fn run_all<P: Phase0<C> + 'static>(phase0s: Vec<P>) -> Vec<NodeResult<DKGOutput<C>>> {
+        let board = SharedBoard::<C>::new(phase0s.len());
+        let local = LocalSet::new();
+        let handles = phase0s
+            .into_iter()
+            .map(|phase0| {
+                let mut runner = DKGRunner::new(board.clone(), PhaseTimeouts::default());
+                local.spawn_local(async move {
+                    let rng = &mut rand::thread_rng();
+                    runner.run(phase0, rng).await
+                })
+            })
+            .collect::<Vec<_>>();
+
+        local
+            .run_until(async move {
+                let mut outputs = Vec::new();
+                for handle in handles {
+                    outputs.push(handle.await.unwrap());
+                }
+                outputs
+            })
+            .await
+    }
+
+    #[tokio::test]
+    async fn runner_dkg_and_reshare() {
+        let (t, n) = (3, 5);
+        let (privs, group) = setup(n, t);
+        let session = SessionId::new(&group, 0, &[]);
+        let phase0s = privs
+            .iter()
+            .map(|private| joint_feldman::DKG::new(*private, group.clone(), session).unwrap())
+            .collect();
+        let outputs = run_all(phase0s)
+            .await
+            .into_iter()
+            .collect::<NodeResult<Vec<_>>>()
+            .unwrap();
+        let public = outputs[0].public.clone();
+        assert!(outputs.iter().all(|output| output.public == public));
+
+        // reshare to the same group with the runner
+        let reshare_session = SessionId::new(&group, 1, &[]);
+        let phase0s = privs
+            .into_iter()
+            .zip(outputs)
+            .map(|(private, output)| {
+                resharing::RDKG::new_from_share(private, output, group.clone(), reshare_session)
+                    .unwrap()
+            })
+            .collect();
+        let reshared = run_all(phase0s)
+            .await
+            .into_iter()
+            .collect::<NodeResult<Vec<_>>>()
+            .unwrap();
+        for output in reshared {
+            assert_eq!(output.public.public_key(), public.public_key());
+        }
+    }
+
+    // reshares the outputs to the same group over authenticated boards, which
+    // accept the bundles of the provided dealers
+    async fn reshare_authenticated(
+        privs: Vec<<S as Scheme>::Private>,
+        outputs: Vec<DKGOutput<C>>,
+        dealers: Group<C>,
+        session: SessionId,
+    ) -> Vec<NodeResult<DKGOutput<C>>> {
+        let transport = SharedTransport::new(privs.len());
+        let local = LocalSet::new();
+        let handles = privs
+            .into_iter()
+            .zip(outputs)
+            .map(|(private, output)| {
+                let group = output.qual.clone();
+                let board = AuthenticatedBoard::new_resharing(
+                    transport.clone(),
+                    Some(private),
+                    Some(private),
+                    dealers.clone(),
+                    group.clone(),
+                );
+                let mut runner = DKGRunner::new(board, PhaseTimeouts::default());
+                let rdkg =
+                    resharing::RDKG::new_from_share(private, output, group, session).unwrap();
+                local.spawn_local(async move {
+                    let rng = &mut rand::thread_rng();
+                    runner.run(rdkg, rng).await
+                })
+            })
+            .collect::<Vec<_>>();
+
+        local
+            .run_until(async move {
+                let mut outputs = Vec::new();
+                for handle in handles {
+                    outputs.push(handle.await.unwrap());
+                }
+                outputs
+            })
+            .await
+    }
+
+    #[tokio::test]
+    async fn runner_weighted_resharing() {
+        // the nodes have a weight of 1, 2 and 2
+        let rng = &mut rand::thread_rng();
+        let keypairs = (0..3).map(|_| S::keypair(rng)).collect::<Vec<_>>();
+        let list = keypairs
+            .iter()
+            .zip(&[1, 2, 2])
+            .map(|((_, public), weight)| (public.clone(), *weight))
+            .collect();
+        let group = Group::<C>::new_weighted(list, 3).unwrap();
+        let privs = keypairs
+            .into_iter()
+            .map(|(private, _)| private)
+            .collect::<Vec<_>>();
+        let session = SessionId::new(&group, 0, &[]);
+        let phase0s = privs
+            .iter()
+            .map(|private| joint_feldman::DKG::new(*private, group.clone(), session).unwrap())
+            .collect();
+        let outputs = run_all(phase0s)
+            .await
+            .into_iter()
+            .collect::<NodeResult<Vec<_>>>()
+            .unwrap();
+        let public = outputs[0].public.clone();
+
+        // the bundles of the second share of a weighted node are not signed
+        // by the node registered under that index in the unexpanded group
+        let reshare_session = SessionId::new(&group, 1, &[]);
+        let transport = SharedTransport::new(privs.len());
+        for (private, output) in privs.iter().zip(outputs.clone()) {
+            let rdkg =
+                resharing::RDKG::new_from_share(*private, output, group.clone(), reshare_session)
+                    .unwrap();
+            let (_, bundles) = rdkg.encrypt_shares(&mut rand::thread_rng()).unwrap();
+            let mut board = AuthenticatedBoard::new(transport.clone(), *private, group.clone());
+            for bundle in bundles {
+                board.publish_shares(bundle).await.unwrap();
+            }
+        }
+        let mut board = AuthenticatedBoard::new(transport.clone(), privs[0], group.clone());
+        assert_eq!(board.read_shares().await.unwrap().len(), 2);
+        let mut board =
+            AuthenticatedBoard::new_resharing(transport, None, None, group.expand(), group.clone());
+        assert_eq!(board.read_shares().await.unwrap().len(), group.weight());
+
+        // the dealers are the share indices of the previous group, each of
+        // them registered with the key of the node owning it
+        let reshared = reshare_authenticated(privs, outputs, group.expand(), reshare_session).await;
+        for output in reshared {
+            let output = output.unwrap();
+            assert_eq!(output.qual, group);
+            assert_eq!(output.transcript.len(), group.weight());
+            assert_eq!(output.public.public_key(), public.public_key());
+        }
+    }
+
+    #[tokio::test]
+    async fn runner_resume_from_checkpoint() {
+        let (t, n) = (3, 4);
+        let (privs, group) = setup(n, t);
+        let session = SessionId::new(&group, 0, &[]);
+        let board = SharedBoard::<C>::new(n);
+        let dir = std::env::temp_dir();
+        let nonce = rand::random::<u64>();
+
+        // every participant deals its shares, checkpoints and "crashes",
+        // half of them after having published the shares
+        let mut stores = Vec::new();
+        for (i, private) in privs.into_iter().enumerate() {
+            let dkg = joint_feldman::DKG::new(private, group.clone(), session).unwrap();
+            let (next, shares) = dkg.encrypt_shares(&mut rand::thread_rng()).unwrap();
+
+            let path = dir.join(format!("dkg-runner-{}-{}", nonce, i));
+            let store = CheckpointStore::new(path, CheckpointKey::Passphrase(vec![i as u8]));
+            store
+                .save(&DKGState::<C, joint_feldman::DKG<C>>::Dealt(
+                    next,
+                    shares.clone(),
+                ))
+                .unwrap();
+            if i % 2 == 0 {
+                board
+                    .clone()
+                    .publish_shares(shares[0].clone())
+                    .await
+                    .unwrap();
+            }
+            stores.push(store);
+        }
+
+        // after restarting, they resume from their checkpoint
+        let local = LocalSet::new();
+        let handles = stores
+            .into_iter()
+            .map(|store| {
+                let mut runner = DKGRunner::new(board.clone(), PhaseTimeouts::default());
+                local.spawn_local(async move {
+                    let state = store.load::<C, joint_feldman::DKG<C>>().unwrap().unwrap();
+                    let rng = &mut rand::thread_rng();
+                    let output = runner.run_with_checkpoints(state, &store, rng).await;
+                    // the checkpoint is removed once the DKG is done
+                    assert!(store.load::<C, joint_feldman::DKG<C>>().unwrap().is_none());
+                    output
+                })
+            })
+            .collect::<Vec<_>>();
+        let outputs = local
+            .run_until(async move {
+                let mut outputs = Vec::new();
+                for handle in handles {
+                    outputs.push(handle.await.unwrap().unwrap());
+                }
+                outputs
+            })
+            .await;
+
+        // the shares which were published twice are the same, so nobody
+        // equivocated
+        for output in &outputs {
+            assert_eq!(output.qual.len(), n);
+            assert!(output.report.dealers.is_empty());
+            assert_eq!(output.public, outputs[0].public);
+        }
+    }
+
+    #[tokio::test]
+    async fn runner_phase3() {
+        let (t, n) = (3, 5);
+        let (privs, group) = setup(n, t);
+        let session = SessionId::new(&group, 0, &[]);
+        // the first node never runs, so the others must go through phase 3
+        let phase0s = privs
+            .into_iter()
+            .skip(1)
+            .map(|private| joint_feldman::DKG::new(private, group.clone(), session).unwrap())
+            .collect();
+        let outputs = run_all(phase0s)
+            .await
+            .into_iter()
+            .collect::<NodeResult<Vec<_>>>()
+            .unwrap();
+        for output in &outputs {
+            assert_eq!(output.qual.len(), n - 1);
+            assert_eq!(output.public, outputs[0].public);
+        }
+    }
+
+    #[tokio::test]
+    async fn runner_authenticated_board() {
+        let (t, n) = (3, 4);
+        let (privs, group) = setup(n, t);
+        let session = SessionId::new(&group, 0, &[]);
+        let mut transport = SharedTransport::new(n);
+
+        // an attacker impersonates the first dealer by relabeling the shares
+        // of a polynomial it controls, and signs them with its own key
+        let (attacker, _) = S::keypair(&mut rand::thread_rng());
+        let mut attacker = AuthenticatedBoard::new(transport.clone(), attacker, group.clone());
+        let dkg = joint_feldman::DKG::new(privs[1], group.clone(), session).unwrap();
+        let (_, shares) = dkg.encrypt_shares(&mut rand::thread_rng()).unwrap();
+        let mut forged: BundledShares<C> = shares[0].clone();
+        forged.dealer_idx = 0;
+        attacker.publish_shares(forged).await.unwrap();
+        // and also writes bytes which are not a signed bundle
+        transport
+            .broadcast(BoardPhase::Shares, vec![1, 2, 3])
+            .await
+            .unwrap();
+
+        let local = LocalSet::new();
+        let handles = privs
+            .into_iter()
+            .map(|private| {
+                let board = AuthenticatedBoard::new(transport.clone(), private, group.clone());
+                let mut runner = DKGRunner::new(board, PhaseTimeouts::default());
+                let dkg = joint_feldman::DKG::new(private, group.clone(), session).unwrap();
+                local.spawn_local(async move {
+                    let rng = &mut rand::thread_rng();
+                    runner.run(dkg, rng).await
+                })
+            })
+            .collect::<Vec<_>>();
+        let outputs = local
+            .run_until(async move {
+                let mut outputs = Vec::new();
+                for handle in handles {
+                    outputs.push(handle.await.unwrap().unwrap());
+                }
+                outputs
+            })
+            .await;
+
+        // the forged bundle was dropped and everyone used the shares of the
+        // real first dealer
+        for output in &outputs {
+            assert_eq!(output.qual.len(), n);
+            assert_eq!(output.public, outputs[0].public);
+        }
+    }
+
+    #[tokio::test]
+    async fn authenticated_board_equivocations() {
+        let (t, n) = (3, 4);
+        let (privs, group) = setup(n, t);
+        let session = SessionId::new(&group, 0, &[]);
+        let transport = SharedTransport::new(n);
+
+        // the first dealer publishes the shares of two different polynomials
+        let mut dealer = AuthenticatedBoard::new(transport.clone(), privs[0], group.clone());
+        for _ in 0..2 {
+            let dkg = joint_feldman::DKG::new(privs[0], group.clone(), session).unwrap();
+            let (_, shares) = dkg.encrypt_shares(&mut rand::thread_rng()).unwrap();
+            dealer.publish_shares(shares[0].clone()).await.unwrap();
+        }
+
+        // both bundles reach the DKG, which disqualifies the dealer, and the
+        // board keeps a single proof however many times it is read
+        let mut board = AuthenticatedBoard::new(transport.clone(), privs[1], group.clone());
+        assert_eq!(board.read_shares().await.unwrap().len(), 2);
+        board.read_shares().await.unwrap();
+        assert_eq!(board.equivocations().len(), 1);
+        let proof = board.equivocations()[0].clone();
+        assert_eq!(proof.culprit(), 0);
+        assert!(proof.is_dealer());
+        assert!(proof.verify(&session, &group));
+        assert!(!proof.verify(&SessionId::new(&group, 1, &[]), &group));
+
+        // a proof made of bundles which were not signed by the culprit is
+        // rejected
+        let forged = match proof {
+            Equivocation::Shares(first, second) => Equivocation::Shares(
+                first,
+                Signed::sign(second.bundle, &privs[1], &mut rand::thread_rng()),
+            ),
+            _ => unreachable!(),
+        };
+        assert!(!forged.verify(&session, &group));
+    }
+}
@@ -1,6 +1,7 @@
-use super::board::BoardPublisher;
+use super::board::{BoardPhase, BoardPublisher, BoardReader, BoardTransport};
 use super::primitives::types::{BundledJustification, BundledResponses, BundledShares};
 use async_trait::async_trait;
+use std::{cell::RefCell, collections::HashMap, rc::Rc, time::Duration};
 use threshold_bls::group::Curve;
 
 /// An in-memory board used for testing
@@ -52,3 +53,192 @@ impl<C: Curve> BoardPublisher<C> for InMemoryBoard<C> {
         Ok(())
     }
 }
+
+#[async_trait(?Send)]
+impl<C: Curve> BoardReader<C> for InMemoryBoard<C> {
+    type Error = ();
+
+    async fn read_shares(&mut self) -> Result<Vec<BundledShares<C>>, Self::Error>
+    where
+        C: 'async_trait,
+    {
+        Ok(self.shares.clone())
+    }
+
+    async fn read_responses(&mut self) -> Result<Vec<BundledResponses>, Self::Error>
+    where
+        C: 'async_trait,
+    {
+        Ok(self.responses.clone())
+    }
+
+    async fn read_justifications(&mut self) -> Result<Vec<BundledJustification<C>>, Self::Error>
+    where
+        C: 'async_trait,
+    {
+        Ok(self.justifs.clone())
+    }
+
+    // everything published in memory is immediately available
+    async fn wait_for_deadline(&mut self, _: BoardPhase, _: Duration) -> Result<(), Self::Error>
+    where
+        C: 'async_trait,
+    {
+        Ok(())
+    }
+}
+
+/// An in-memory board shared by participants running concurrently on the same
+/// thread. The deadline of a phase is reached once all `n` participants have
+/// started waiting for it.
+pub struct SharedBoard<C: Curve> {
+    board: Rc<RefCell<InMemoryBoard<C>>>,
+    arrivals: Rc<RefCell<HashMap<BoardPhase, usize>>>,
+    n: usize,
+}
+
+impl<C: Curve> Clone for SharedBoard<C> {
+    fn clone(&self) -> Self {
+        Self {
+            board: self.board.clone(),
+            arrivals: self.arrivals.clone(),
+            n: self.n,
+        }
+    }
+}
+
+impl<C: Curve> SharedBoard<C> {
+    #[allow(unused)]
+    pub fn new(n: usize) -> Self {
+        Self {
+            board: Rc::new(RefCell::new(InMemoryBoard::new())),
+            arrivals: Rc::new(RefCell::new(HashMap::new())),
+            n,
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl<C: Curve> BoardPublisher<C> for SharedBoard<C> {
+    type Error = ();
+
+    async fn publish_shares(&mut self, bundle: BundledShares<C>) -> Result<(), Self::Error>
+    where
+        C: 'async_trait,
+    {
+        self.board.borrow_mut().shares.push(bundle);
+        Ok(())
+    }
+
+    async fn publish_responses(&mut self, bundle: BundledResponses) -> Result<(), Self::Error>
+    where
+        C: 'async_trait,
+    {
+        self.board.borrow_mut().responses.push(bundle);
+        Ok(())
+    }
+
+    async fn publish_justifications(
+        &mut self,
+        bundle: BundledJustification<C>,
+    ) -> Result<(), Self::Error>
+    where
+        C: 'async_trait,
+    {
+        self.board.borrow_mut().justifs.push(bundle);
+        Ok(())
+    }
+}
+
+#[async_trait(?Send)]
+impl<C: Curve> BoardReader<C> for SharedBoard<C> {
+    type Error = ();
+
+    async fn read_shares(&mut self) -> Result<Vec<BundledShares<C>>, Self::Error>
+    where
+        C: 'async_trait,
+    {
+        Ok(self.board.borrow().shares.clone())
+    }
+
+    async fn read_responses(&mut self) -> Result<Vec<BundledResponses>, Self::Error>
+    where
+        C: 'async_trait,
+    {
+        Ok(self.board.borrow().responses.clone())
+    }
+
+    async fn read_justifications(&mut self) -> Result<Vec<BundledJustification<C>>, Self::Error>
+    where
+        C: 'async_trait,
+    {
+        Ok(self.board.borrow().justifs.clone())
+    }
+
+    async fn wait_for_deadline(&mut self, phase: BoardPhase, _: Duration) -> Result<(), Self::Error>
+    where
+        C: 'async_trait,
+    {
+        *self.arrivals.borrow_mut().entry(phase).or_insert(0) += 1;
+        while self.arrivals.borrow()[&phase] < self.n {
+            let _ = tokio::task::yield_now().await;
+        }
+        Ok(())
+    }
+}
+
+/// An in-memory transport which relays unauthenticated messages between
+/// participants running concurrently on the same thread. Like `SharedBoard`,
+/// the deadline of a phase is reached once all `n` participants wait for it.
+pub struct SharedTransport {
+    msgs: Rc<RefCell<HashMap<BoardPhase, Vec<Vec<u8>>>>>,
+    arrivals: Rc<RefCell<HashMap<BoardPhase, usize>>>,
+    n: usize,
+}
+
+impl Clone for SharedTransport {
+    fn clone(&self) -> Self {
+        Self {
+            msgs: self.msgs.clone(),
+            arrivals: self.arrivals.clone(),
+            n: self.n,
+        }
+    }
+}
+
+impl SharedTransport {
+    #[allow(unused)]
+    pub fn new(n: usize) -> Self {
+        Self {
+            msgs: Rc::new(RefCell::new(HashMap::new())),
+            arrivals: Rc::new(RefCell::new(HashMap::new())),
+            n,
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl BoardTransport for SharedTransport {
+    type Error = ();
+
+    async fn broadcast(&mut self, phase: BoardPhase, msg: Vec<u8>) -> Result<(), Self::Error> {
+        self.msgs.borrow_mut().entry(phase).or_default().push(msg);
+        Ok(())
+    }
+
+    async fn receive(&mut self, phase: BoardPhase) -> Result<Vec<Vec<u8>>, Self::Error> {
+        Ok(self.msgs.borrow().get(&phase).cloned().unwrap_or_default())
+    }
+
+    async fn wait_for_deadline(
+        &mut self,
+        phase: BoardPhase,
+        _: Duration,
+    ) -> Result<(), Self::Error> {
+        *self.arrivals.borrow_mut().entry(phase).or_insert(0) += 1;
+        while self.arrivals.borrow()[&phase] < self.n {
+            let _ = tokio::task::yield_now().await;
+        }
+        Ok(())
+    }
+}
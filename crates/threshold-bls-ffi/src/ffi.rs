@@ -4,10 +4,12 @@ use rand_core::{RngCore, SeedableRng};
 
 use serde::{de::DeserializeOwned, Serialize};
 use threshold_bls::{
-    poly::{Idx as Index, Poly},
+    group::Element,
+    poly::Poly,
     sig::{
         BlindScheme, BlindThresholdScheme, Scheme, Share, SignatureScheme, ThresholdScheme, Token,
     },
+    vss,
 };
 
 use bls_crypto::ffi::Buffer;
@@ -603,24 +605,29 @@ pub unsafe extern "C" fn destroy_sig(signature: *mut Signature) {
 /// in production, unless you trust the person that generated the keys.
 ///
 /// The seed MUST be at least 32 bytes long
+///
+/// - If NULL pointers are passed, the function will return false
+/// - If `t` is 0 or larger than `n`, the function will return false
+///
+/// Returns true if successful, otherwise false.
 #[no_mangle]
 pub unsafe extern "C" fn threshold_keygen(
     n: usize,
     t: usize,
     seed: *const Buffer,
     keys: *mut *mut Keys,
-) {
+) -> bool {
+    if seed.is_null() || keys.is_null() {
+        return false;
+    }
+
     let seed = <&[u8]>::from(unsafe { &*seed });
     let mut rng = get_rng(seed);
-    let private = Poly::<PrivateKey>::new_from(t - 1, &mut rng);
-    let shares = (0..n)
-        .map(|i| private.eval(i as Index))
-        .map(|e| Share {
-            index: e.index,
-            private: e.value,
-        })
-        .collect();
-    let polynomial: Poly<PublicKey> = private.commit();
+    let secret = PrivateKey::rand(&mut rng);
+    let (shares, polynomial) = match vss::deal::<KeyCurve, _>(secret, t, n, &mut rng) {
+        Ok(dealing) => dealing,
+        Err(_) => return false,
+    };
     let threshold_public_key = polynomial.public_key().clone();
 
     let keys_local = Keys {
@@ -634,6 +641,32 @@ pub unsafe extern "C" fn threshold_keygen(
     unsafe {
         *keys = Box::into_raw(Box::new(keys_local));
     };
+
+    true
+}
+
+/// Verifies a private key share against the polynomial committing to it, e.g. the one returned
+/// by `threshold_keygen` along with the shares.
+///
+/// # Safety
+/// - **This function will dereference the provided pointers. If any invalid pointers are passed
+///     then the software will crash**.
+/// - If NULL pointers are passed, the function will return false
+///
+/// Returns true if the share is correct, otherwise false.
+#[no_mangle]
+pub unsafe extern "C" fn verify_share(
+    polynomial: *const Poly<PublicKey>,
+    share: *const Share<PrivateKey>,
+) -> bool {
+    if polynomial.is_null() || share.is_null() {
+        return false;
+    }
+
+    let polynomial = unsafe { &*polynomial };
+    let share = unsafe { &*share };
+
+    vss::verify_share::<KeyCurve>(share, polynomial)
 }
 
 /// Generates a single private key from the provided seed.
@@ -778,8 +811,12 @@ mod tests {
 
         let (n, t) = (5, 3);
         let mut keys = MaybeUninit::<*mut Keys>::uninit();
-        unsafe { threshold_keygen(n, t, &Buffer::from(&seed[..]), keys.as_mut_ptr()) };
+        assert!(!unsafe { threshold_keygen(t, n, &Buffer::from(&seed[..]), keys.as_mut_ptr()) });
+        assert!(unsafe { threshold_keygen(n, t, &Buffer::from(&seed[..]), keys.as_mut_ptr()) });
         let keys = unsafe { &*keys.assume_init() };
+        for i in 0..n {
+            assert!(unsafe { verify_share(polynomial_ptr(keys), share_ptr(keys, i)) });
+        }
 
         let (message_to_sign, blinding_factor) = if should_blind {
             let mut blinded_message = MaybeUninit::<Buffer>::uninit();
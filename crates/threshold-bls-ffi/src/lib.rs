@@ -14,8 +14,14 @@ cfg_if::cfg_if! {
     }
 }
 
-use threshold_bls::{poly::Idx, schemes::bls12_377::G2Scheme as SigScheme, sig::Scheme};
+use threshold_bls::{
+    poly::Idx,
+    schemes::bls12_377::{G2Curve, G2Scheme as SigScheme},
+    sig::Scheme,
+};
 
+/// The curve of the public keys, over which the shares are committed
+pub(crate) type KeyCurve = G2Curve;
 pub(crate) type PublicKey = <SigScheme as Scheme>::Public;
 pub(crate) type PrivateKey = <SigScheme as Scheme>::Private;
 
@@ -6,10 +6,12 @@ use rand_chacha::ChaChaRng;
 use rand_core::{RngCore, SeedableRng};
 
 use threshold_bls::{
-    poly::{Idx as Index, Poly},
+    group::Element,
+    poly::Poly,
     sig::{
         BlindScheme, BlindThresholdScheme, Scheme, Share, SignatureScheme, ThresholdScheme, Token,
     },
+    vss,
 };
 
 use crate::*;
@@ -267,23 +269,38 @@ pub fn combine(threshold: usize, signatures: Vec<u8>) -> Result<Vec<u8>> {
 /// in production, unless you trust the person that generated the keys.
 ///
 /// The seed MUST be at least 32 bytes long
-pub fn threshold_keygen(n: usize, t: usize, seed: &[u8]) -> Keys {
+///
+/// # Throws
+///
+/// - If `t` is 0 or larger than `n`
+pub fn threshold_keygen(n: usize, t: usize, seed: &[u8]) -> Result<Keys> {
     let mut rng = get_rng(&[seed]);
-    let private = Poly::<PrivateKey>::new_from(t - 1, &mut rng);
-    let shares = (0..n)
-        .map(|i| private.eval(i as Index))
-        .map(|e| Share {
-            index: e.index,
-            private: e.value,
-        })
-        .collect();
-    let polynomial = private.commit();
-    Keys {
+    let secret = PrivateKey::rand(&mut rng);
+    let (shares, polynomial) = vss::deal::<KeyCurve, _>(secret, t, n, &mut rng)
+        .map_err(|err| JsValue::from_str(&format!("could not generate keys: {}", err)))?;
+    Ok(Keys {
         shares,
         polynomial,
         t,
         n,
-    }
+    })
+}
+
+#[wasm_bindgen(js_name = verifyShare)]
+/// Verifies a private key share against the polynomial committing to it, e.g. the one returned
+/// by `thresholdKeygen` along with the shares.
+///
+/// # Throws
+///
+/// - If the polynomial or the share cannot be deserialized
+pub fn verify_share(polynomial_buf: &[u8], share_buf: &[u8]) -> Result<bool> {
+    let polynomial: Poly<PublicKey> = bincode::deserialize(&polynomial_buf)
+        .map_err(|err| JsValue::from_str(&format!("could not deserialize polynomial {}", err)))?;
+    let share: Share<PrivateKey> = bincode::deserialize(&share_buf).map_err(|err| {
+        JsValue::from_str(&format!("could not deserialize private key share {}", err))
+    })?;
+
+    Ok(vss::verify_share::<KeyCurve>(&share, &polynomial))
 }
 
 #[wasm_bindgen(inspectable)]
@@ -454,7 +471,11 @@ mod tests {
     fn threshold_wasm_should_blind(should_blind: bool) {
         let (n, t) = (5, 3);
         let seed = b"aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa";
-        let keys = threshold_keygen(n, t, &seed[..]);
+        assert!(threshold_keygen(t, n, &seed[..]).is_err());
+        let keys = threshold_keygen(n, t, &seed[..]).unwrap();
+        for i in 0..n {
+            assert!(verify_share(&keys.polynomial(), &keys.get_share(i)).unwrap());
+        }
 
         let msg = vec![1, 2, 3, 4, 6];
         let key = b"bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb";
@@ -1,15 +1,16 @@
 use crate::group::PrimeOrder;
-use crate::group::{self, Element, PairingCurve as PC, Point, Scalar as Sc};
+use crate::group::{self, Element, FixedBaseTable, PairingCurve as PC, Point, Scalar as Sc};
 use ark_bls12_377 as bls377;
-use ark_ec::{AffineCurve, PairingEngine, ProjectiveCurve};
-use ark_ff::PrimeField;
-use ark_ff::{Field, One, UniformRand, Zero};
+use ark_ec::{msm::VariableBaseMSM, AffineCurve, PairingEngine, ProjectiveCurve};
+use ark_ff::{BigInteger, PrimeField};
+use ark_ff::{FftField, Field, One, UniformRand, Zero};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use bls_crypto::{
     hash_to_curve::{try_and_increment::TryAndIncrement, HashToCurve},
     hashers::DirectHasher,
     BLSError, SIG_DOMAIN,
 };
+use once_cell::sync::Lazy;
 use rand_core::RngCore;
 use serde::{
     de::{Error as DeserializeError, SeqAccess, Visitor},
@@ -32,6 +33,15 @@ pub enum ZexeError {
     BLSError(#[from] BLSError),
 }
 
+/// The number of bits of the windows of the generator tables
+const GENERATOR_WINDOW: usize = 6;
+
+static G1_TABLE: Lazy<FixedBaseTable<G1>> =
+    Lazy::new(|| FixedBaseTable::new(&G1::one(), GENERATOR_WINDOW));
+
+static G2_TABLE: Lazy<FixedBaseTable<G2>> =
+    Lazy::new(|| FixedBaseTable::new(&G2::one(), GENERATOR_WINDOW));
+
 // TODO(gakonst): Make this work with any PairingEngine.
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Deserialize, Serialize)]
@@ -115,6 +125,17 @@ impl Sc for Scalar {
     fn serialized_size(&self) -> usize {
         self.0.serialized_size()
     }
+
+    fn to_bits_le(&self) -> Vec<bool> {
+        self.0.into_repr().to_bits_le()
+    }
+
+    fn root_of_unity(n: usize) -> Option<Self> {
+        if !n.is_power_of_two() {
+            return None;
+        }
+        Some(Self(bls377::Fr::get_root_of_unity(n)?))
+    }
 }
 
 impl fmt::Display for Scalar {
@@ -146,6 +167,15 @@ impl Element for G1 {
     fn mul(&mut self, mul: &Scalar) {
         self.0.mul_assign(mul.0);
     }
+
+    fn msm(elements: &[Self], scalars: &[Scalar]) -> Self {
+        let points = elements.iter().map(|e| e.0).collect::<Vec<_>>();
+        Self(msm(&points, scalars))
+    }
+
+    fn eval_poly(coeffs: &[Self], x: &Scalar) -> Self {
+        eval_msm(coeffs, x)
+    }
 }
 
 /// Implementation of Point using G1 from BLS12-377
@@ -161,6 +191,10 @@ impl Point for G1 {
 
         Ok(())
     }
+
+    fn generator_table() -> &'static FixedBaseTable<Self> {
+        &G1_TABLE
+    }
 }
 
 impl fmt::Display for G1 {
@@ -192,6 +226,15 @@ impl Element for G2 {
     fn mul(&mut self, mul: &Scalar) {
         self.0.mul_assign(mul.0)
     }
+
+    fn msm(elements: &[Self], scalars: &[Scalar]) -> Self {
+        let points = elements.iter().map(|e| e.0).collect::<Vec<_>>();
+        Self(msm(&points, scalars))
+    }
+
+    fn eval_poly(coeffs: &[Self], x: &Scalar) -> Self {
+        eval_msm(coeffs, x)
+    }
 }
 
 /// Implementation of Point using G2 from BLS12-377
@@ -206,6 +249,10 @@ impl Point for G2 {
 
         Ok(())
     }
+
+    fn generator_table() -> &'static FixedBaseTable<Self> {
+        &G2_TABLE
+    }
 }
 
 impl fmt::Display for G2 {
@@ -214,6 +261,33 @@ impl fmt::Display for G2 {
     }
 }
 
+/// Multi-scalar multiplication with Pippenger's algorithm
+fn msm<G>(points: &[G], scalars: &[Scalar]) -> G
+where
+    G: ProjectiveCurve<ScalarField = bls377::Fr>,
+{
+    assert_eq!(
+        points.len(),
+        scalars.len(),
+        "there should be one scalar per element"
+    );
+    let bases = G::batch_normalization_into_affine(points);
+    let scalars = scalars.iter().map(|s| s.0.into_repr()).collect::<Vec<_>>();
+    VariableBaseMSM::multi_scalar_mul(&bases, &scalars)
+}
+
+// evaluates the polynomial as the multi-scalar multiplication of its
+// coefficients by the powers of x
+fn eval_msm<E: Element<RHS = Scalar>>(coeffs: &[E], x: &Scalar) -> E {
+    let mut powers = Vec::with_capacity(coeffs.len());
+    let mut power = Scalar::one();
+    for _ in 0..coeffs.len() {
+        powers.push(power);
+        power.mul(x);
+    }
+    E::msm(coeffs, &powers)
+}
+
 //TODO (michael) : This interface should be refactored, GT is multiplicative subgroup of extension field
 // so using elliptic curve additive notation for it doesn't make sense
 impl Element for GT {
@@ -271,10 +345,29 @@ impl PC for PairingCurve {
     type G1 = G1;
     type G2 = G2;
     type GT = GT;
+    type G2Prepared = <bls377::Bls12_377 as PairingEngine>::G2Prepared;
 
     fn pair(a: &Self::G1, b: &Self::G2) -> Self::GT {
         GT(<bls377::Bls12_377 as PairingEngine>::pairing(a.0, b.0))
     }
+
+    fn prepare_g2(b: &Self::G2) -> Self::G2Prepared {
+        b.0.into_affine().into()
+    }
+
+    fn pairing_eq(
+        a1: &Self::G1,
+        b1: &Self::G2Prepared,
+        a2: &Self::G1,
+        b2: &Self::G2Prepared,
+    ) -> bool {
+        // e(-a1, b1) * e(a2, b2) == 1
+        let pairs = [
+            (a1.0.neg().into_affine().into(), b1.clone()),
+            (a2.0.into_affine().into(), b2.clone()),
+        ];
+        <bls377::Bls12_377 as PairingEngine>::product_of_pairings(&pairs).is_one()
+    }
 }
 
 // Serde implementations (ideally, these should be upstreamed to Zexe)
@@ -438,6 +531,94 @@ mod tests {
         assert_eq!(de, sig);
     }
 
+    #[test]
+    fn msm() {
+        msm_test::<G1>();
+        msm_test::<G2>();
+    }
+
+    fn msm_test<E: Element<RHS = Scalar>>() {
+        let rng = &mut rand::thread_rng();
+        assert_eq!(E::msm(&[], &[]), E::zero());
+        for n in &[1, 2, 10, 50] {
+            let elements = (0..*n).map(|_| E::rand(rng)).collect::<Vec<_>>();
+            let scalars = (0..*n).map(|_| Scalar::rand(rng)).collect::<Vec<_>>();
+            let expected = elements
+                .iter()
+                .zip(&scalars)
+                .fold(E::zero(), |mut sum, (e, s)| {
+                    let mut term = e.clone();
+                    term.mul(s);
+                    sum.add(&term);
+                    sum
+                });
+            assert_eq!(E::msm(&elements, &scalars), expected);
+        }
+    }
+
+    #[test]
+    fn eval_poly() {
+        eval_poly_test::<G1>();
+        eval_poly_test::<G2>();
+    }
+
+    fn eval_poly_test<E: Element<RHS = Scalar>>() {
+        let rng = &mut rand::thread_rng();
+        let x = Scalar::rand(rng);
+        for n in &[0, 1, 2, 10] {
+            let coeffs = (0..*n).map(|_| E::rand(rng)).collect::<Vec<_>>();
+            // Horner's rule
+            let expected = coeffs.iter().rev().fold(E::zero(), |mut sum, c| {
+                sum.mul(&x);
+                sum.add(c);
+                sum
+            });
+            assert_eq!(E::eval_poly(&coeffs, &x), expected);
+        }
+    }
+
+    #[test]
+    fn generator_table() {
+        generator_table_test::<G1>();
+        generator_table_test::<G2>();
+    }
+
+    fn generator_table_test<P: Point<RHS = Scalar>>() {
+        let rng = &mut rand::thread_rng();
+        let mut minus_one = Scalar::one();
+        minus_one.negate();
+        let scalars = vec![Scalar::zero(), Scalar::one(), minus_one, Scalar::rand(rng)];
+        for scalar in scalars {
+            let mut expected = P::one();
+            expected.mul(&scalar);
+            assert_eq!(P::generator_table().mul(&scalar), expected);
+        }
+
+        // any base and window size
+        let base = P::rand(rng);
+        let scalar = Scalar::rand(rng);
+        let mut expected = base.clone();
+        expected.mul(&scalar);
+        for window in 1..9 {
+            assert_eq!(FixedBaseTable::new(&base, window).mul(&scalar), expected);
+        }
+    }
+
+    #[test]
+    fn pairing_eq() {
+        let rng = &mut rand::thread_rng();
+        let (a, b) = (G1::rand(rng), G2::rand(rng));
+        let x = Scalar::rand(rng);
+        let mut ax = a.clone();
+        ax.mul(&x);
+        let mut bx = b.clone();
+        bx.mul(&x);
+
+        let (b, bx) = (PairingCurve::prepare_g2(&b), PairingCurve::prepare_g2(&bx));
+        assert!(PairingCurve::pairing_eq(&ax, &b, &a, &bx));
+        assert!(!PairingCurve::pairing_eq(&a, &b, &a, &bx));
+    }
+
     #[test]
     fn gt_exp() {
         let rng = &mut rand::thread_rng();
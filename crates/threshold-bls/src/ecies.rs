@@ -33,7 +33,7 @@ use serde::{Deserialize, Serialize};
 
 // crypto imports
 use chacha20poly1305::{
-    aead::{Aead, Error as AError, NewAead},
+    aead::{Aead, Error as AError, NewAead, Payload},
     ChaCha20Poly1305,
 };
 use hkdf::Hkdf;
@@ -66,17 +66,29 @@ pub struct EciesCipher<C: Curve> {
 
 /// Encrypts the message with a public key (curve point) and returns a ciphertext
 pub fn encrypt<C: Curve, R: RngCore>(to: &C::Point, msg: &[u8], rng: &mut R) -> EciesCipher<C> {
+    encrypt_with_ad(to, msg, &[], rng)
+}
+
+/// Encrypts the message with a public key (curve point) and binds the ciphertext
+/// to the associated data, which is mixed in the key derivation and
+/// authenticated by the AEAD. The same associated data must be provided for
+/// decryption.
+pub fn encrypt_with_ad<C: Curve, R: RngCore>(
+    to: &C::Point,
+    msg: &[u8],
+    ad: &[u8],
+    rng: &mut R,
+) -> EciesCipher<C> {
     let eph_secret = C::Scalar::rand(rng);
 
-    let mut ephemeral = C::Point::one();
-    ephemeral.mul(&eph_secret);
+    let ephemeral = C::generator_table().mul(&eph_secret);
 
     // dh = eph(yG) = eph * public
     let mut dh = to.clone();
     dh.mul(&eph_secret);
 
     // derive an ephemeral key from the public key
-    let ephemeral_key = derive::<C>(&dh);
+    let ephemeral_key = derive::<C>(&dh, ad);
 
     // instantiate the AEAD scheme
     let aead = ChaCha20Poly1305::new(&ephemeral_key.into());
@@ -87,7 +99,7 @@ pub fn encrypt<C: Curve, R: RngCore>(to: &C::Point, msg: &[u8], rng: &mut R) ->
 
     // do the encryption
     let aead = aead
-        .encrypt(&nonce.into(), msg)
+        .encrypt(&nonce.into(), Payload { msg, aad: ad })
         .expect("aead should not fail");
 
     EciesCipher {
@@ -99,25 +111,44 @@ pub fn encrypt<C: Curve, R: RngCore>(to: &C::Point, msg: &[u8], rng: &mut R) ->
 
 /// Decrypts the message with a secret key (curve scalar) and returns the cleartext
 pub fn decrypt<C: Curve>(private: &C::Scalar, cipher: &EciesCipher<C>) -> Result<Vec<u8>, AError> {
+    decrypt_with_ad(private, cipher, &[])
+}
+
+/// Decrypts a message which was encrypted with [`encrypt_with_ad`] with a secret
+/// key (curve scalar) and returns the cleartext
+///
+/// [`encrypt_with_ad`]: fn.encrypt_with_ad.html
+pub fn decrypt_with_ad<C: Curve>(
+    private: &C::Scalar,
+    cipher: &EciesCipher<C>,
+    ad: &[u8],
+) -> Result<Vec<u8>, AError> {
     // dh = private * (eph * G) = private * ephPublic
     let mut dh = cipher.ephemeral.clone();
     dh.mul(private);
 
-    let ephemeral_key = derive::<C>(&dh);
+    let ephemeral_key = derive::<C>(&dh, ad);
 
     let aead = ChaCha20Poly1305::new(&ephemeral_key.into());
 
-    aead.decrypt(&cipher.nonce.into(), &cipher.aead[..])
+    aead.decrypt(
+        &cipher.nonce.into(),
+        Payload {
+            msg: &cipher.aead[..],
+            aad: ad,
+        },
+    )
 }
 
-/// Derives an ephemeral key from the provided public key
-fn derive<C: Curve>(dh: &C::Point) -> [u8; KEY_LEN] {
+/// Derives an ephemeral key from the provided public key and associated data
+fn derive<C: Curve>(dh: &C::Point, ad: &[u8]) -> [u8; KEY_LEN] {
     let serialized = bincode::serialize(dh).expect("could not serialize element");
 
     // no salt is fine since we use ephemeral - static DH
     let h = Hkdf::<Sha256>::new(None, &serialized);
     let mut ephemeral_key = [0u8; KEY_LEN];
-    h.expand(&DOMAIN, &mut ephemeral_key)
+    let info = [&DOMAIN[..], ad].concat();
+    h.expand(&info, &mut ephemeral_key)
         .expect("hkdf should not fail");
 
     debug_assert!(ephemeral_key.len() == KEY_LEN);
@@ -156,4 +187,22 @@ mod tests {
         cipher.aead = vec![0; 32];
         decrypt::<Curve>(&s2, &cipher).unwrap_err();
     }
+
+    #[test]
+    fn test_associated_data() {
+        let (s, p) = kp();
+        let data = vec![1, 2, 3, 4];
+
+        let cipher = encrypt_with_ad::<Curve, _>(&p, &data, b"session 1", &mut thread_rng());
+        let deciphered = decrypt_with_ad::<Curve>(&s, &cipher, b"session 1").unwrap();
+        assert_eq!(data, deciphered);
+
+        // decrypting with other or no associated data should fail
+        decrypt_with_ad::<Curve>(&s, &cipher, b"session 2").unwrap_err();
+        decrypt::<Curve>(&s, &cipher).unwrap_err();
+
+        // no associated data is the same as plain encryption
+        let cipher = encrypt::<Curve, _>(&p, &data, &mut thread_rng());
+        assert_eq!(decrypt_with_ad::<Curve>(&s, &cipher, &[]).unwrap(), data);
+    }
 }
@@ -34,6 +34,45 @@ pub trait Element:
     fn zero() -> Self {
         Self::new()
     }
+
+    /// Returns the linear combination `sum_i scalars[i] * elements[i]`, which
+    /// is zero for empty slices. It panics if the slices have different
+    /// lengths.
+    ///
+    /// The default implementation multiplies each element separately. Points
+    /// override it with a multi-scalar multiplication (e.g. Pippenger's
+    /// algorithm), which is much faster for many elements. It is defined on
+    /// `Element` so that polynomials can use it whatever their coefficients.
+    fn msm(elements: &[Self], scalars: &[Self::RHS]) -> Self {
+        assert_eq!(
+            elements.len(),
+            scalars.len(),
+            "there should be one scalar per element"
+        );
+        elements
+            .iter()
+            .zip(scalars)
+            .fold(Self::zero(), |mut sum, (element, scalar)| {
+                let mut term = element.clone();
+                term.mul(scalar);
+                sum.add(&term);
+                sum
+            })
+    }
+
+    /// Evaluates the polynomial with the given coefficients, starting from the
+    /// constant term, at `x`.
+    ///
+    /// The default implementation uses Horner's rule. Points override it with
+    /// `msm` of the coefficients by the powers of `x`, which is faster for
+    /// many coefficients.
+    fn eval_poly(coeffs: &[Self], x: &Self::RHS) -> Self {
+        coeffs.iter().rev().fold(Self::zero(), |mut sum, coeff| {
+            sum.mul(x);
+            sum.add(coeff);
+            sum
+        })
+    }
 }
 
 /// Checks inclusion in prime order subgroup. Only needed when underlying trait
@@ -51,15 +90,84 @@ pub trait Scalar: Element {
     fn sub(&mut self, other: &Self);
     fn from_random_bytes(bytes: &[u8]) -> Option<Self>;
     fn serialized_size(&self) -> usize;
+    /// Returns the bits of the canonical representation of the scalar, least
+    /// significant first. All the scalars have the same number of bits.
+    fn to_bits_le(&self) -> Vec<bool>;
+    /// Returns a primitive n-th root of unity, for n a power of two, if the
+    /// field has one.
+    fn root_of_unity(n: usize) -> Option<Self>;
 }
 
-/// Basic point functionality that can be multiplied by a scalar
-pub trait Point: Element {
+/// Basic point functionality that can be multiplied by a scalar. Points
+/// should implement `Element::msm` with a multi-scalar multiplication.
+pub trait Point: Element + 'static {
     /// Error which may occur while mapping to the group
     type Error: Debug;
 
     /// Maps the provided data to a group element
     fn map(&mut self, data: &[u8]) -> Result<(), <Self as Point>::Error>;
+
+    /// Returns the precomputed table of the generator, built the first time
+    /// it is used.
+    fn generator_table() -> &'static FixedBaseTable<Self>;
+}
+
+/// FixedBaseTable holds the multiples of a base point for each window of bits
+/// of a scalar, so that multiplying the base by a scalar only takes one
+/// addition per window instead of a full double-and-add.
+#[derive(Debug, Clone)]
+pub struct FixedBaseTable<P: Point> {
+    window: usize,
+    // multiples[k][d] = d * 2^(k * window) * base
+    multiples: Vec<Vec<P>>,
+}
+
+impl<P> FixedBaseTable<P>
+where
+    P: Point,
+    P::RHS: Scalar,
+{
+    /// Precomputes the multiples of the base for windows of the given number
+    /// of bits. The table holds `2^window` points per window.
+    pub fn new(base: &P, window: usize) -> Self {
+        assert!(
+            window > 0 && window < 16,
+            "the window should be of 1 to 15 bits"
+        );
+        let windows = P::RHS::one().to_bits_le().chunks(window).count();
+
+        let mut multiples = Vec::with_capacity(windows);
+        let mut outer = base.clone();
+        for _ in 0..windows {
+            let mut row = Vec::with_capacity(1 << window);
+            let mut inner = P::zero();
+            for _ in 0..1 << window {
+                row.push(inner.clone());
+                inner.add(&outer);
+            }
+            // the last multiple is the base of the next window
+            outer = inner;
+            multiples.push(row);
+        }
+
+        Self { window, multiples }
+    }
+
+    /// Multiplies the base by the scalar
+    pub fn mul(&self, scalar: &P::RHS) -> P {
+        let bits = scalar.to_bits_le();
+        debug_assert!(bits.len() <= self.window * self.multiples.len());
+        bits.chunks(self.window)
+            .zip(&self.multiples)
+            .fold(P::zero(), |mut sum, (chunk, row)| {
+                let digit = chunk
+                    .iter()
+                    .rev()
+                    .fold(0, |digit, bit| (digit << 1) | *bit as usize);
+                sum.add(&row[digit]);
+                sum
+            })
+    }
 }
 
 /// A group holds functionalities to create scalar and points related; it is
@@ -80,6 +188,12 @@ pub trait Curve: Clone + Debug + Send + Sync {
     fn point() -> Self::Point {
         Self::Point::one()
     }
+
+    /// Returns the precomputed table of the generator, to multiply it by
+    /// scalars faster than with `Element::mul`.
+    fn generator_table() -> &'static FixedBaseTable<Self::Point> {
+        Self::Point::generator_table()
+    }
 }
 
 /// A curve equipped with a bilinear pairing operation.
@@ -92,8 +206,24 @@ pub trait PairingCurve: Debug {
 
     type GT: Element<RHS = Self::Scalar>;
 
+    /// A G2 element prepared for the pairing, which is worth caching for the
+    /// elements paired many times (e.g. public keys)
+    type G2Prepared: Clone + Debug + Send + Sync;
+
     /// Perfors a pairing operation between the 2 group elements
     fn pair(a: &Self::G1, b: &Self::G2) -> Self::GT;
+
+    /// Prepares the G2 element for the pairing
+    fn prepare_g2(b: &Self::G2) -> Self::G2Prepared;
+
+    /// Checks that `e(a1, b1) == e(a2, b2)` with a single final
+    /// exponentiation
+    fn pairing_eq(
+        a1: &Self::G1,
+        b1: &Self::G2Prepared,
+        a2: &Self::G1,
+        b2: &Self::G2Prepared,
+    ) -> bool;
 }
 
 #[derive(Debug, Clone, PartialEq)]
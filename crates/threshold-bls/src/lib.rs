@@ -189,6 +189,10 @@ pub mod poly;
 /// BLS Signature implementations. Supports blind and threshold signatures.
 pub mod sig;
 
+/// Feldman and Pedersen verifiable secret sharing, for a dealer to split a
+/// secret into shares which can be checked by their holders.
+pub mod vss;
+
 /// Pre-instantiated signature schemes for each curve
 pub mod schemes {
     use crate::sig::{G1Scheme, G2Scheme};
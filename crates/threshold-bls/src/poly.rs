@@ -1,7 +1,11 @@
 use crate::group::{Curve, Element, Point, Scalar};
 use rand_core::RngCore;
 use serde::{Deserialize, Serialize};
-use std::{collections::BTreeMap, fmt};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    sync::{Arc, RwLock},
+};
 use thiserror::Error;
 
 pub type PrivatePoly<C> = Poly<<C as Curve>::Scalar>;
@@ -101,6 +105,14 @@ pub enum PolyError {
     InvalidRecovery(usize, usize),
     #[error("Could not invert scalar")]
     NoInverse,
+    #[error("Could not correct the errors: at most {0} can be corrected")]
+    TooManyErrors(usize),
+    #[error("Invalid hierarchy: {0}")]
+    InvalidHierarchy(&'static str),
+    #[error("The shares do not reach the thresholds of the hierarchy")]
+    Unauthorized,
+    #[error("The interpolation problem has no solution")]
+    Singular,
 }
 
 impl<C> Poly<C>
@@ -108,95 +120,136 @@ where
     C: Element,
     C::RHS: Scalar<RHS = C::RHS>,
 {
-    /// Evaluates the polynomial at the specified value.
+    /// Evaluates the polynomial at the x coordinate of the given index, i.e.
+    /// `index + 1`.
     pub fn eval(&self, i: Idx) -> Eval<C> {
-        let mut xi = C::RHS::new();
-        // +1 because we must never evaluate the polynomial at its first point
-        // otherwise it reveals the "secret" value !
-        // TODO: maybe move that a layer above, to not mix ss scheme with poly.
-        xi.set_int((i + 1).into());
-
-        let res = self.0.iter().rev().fold(C::zero(), |mut sum, coeff| {
-            sum.mul(&xi);
-            sum.add(coeff);
-            sum
-        });
-
         Eval {
-            value: res,
+            value: self.eval_at(&x_coordinate(i)),
             index: i,
         }
     }
 
+    /// Evaluates the polynomial at the given x coordinate
+    pub fn eval_at(&self, x: &C::RHS) -> C {
+        C::eval_poly(&self.0, x)
+    }
+
+    /// Returns the derivative of the given order of the polynomial
+    pub fn derivative(&self, order: usize) -> Self {
+        if order >= self.0.len() {
+            return Self::zero();
+        }
+        // the j-th coefficient is c_{j+order} * (j+order)! / j!
+        let coeffs: Vec<C> = self.0[order..]
+            .iter()
+            .enumerate()
+            .map(|(j, c)| {
+                let mut c = c.clone();
+                c.mul(&falling_factorial(j + order, order));
+                c
+            })
+            .collect();
+        Self::from(coeffs)
+    }
+
+    /// Evaluates the derivative of the given order of the polynomial at the x
+    /// coordinate of the given index.
+    pub fn eval_derivative(&self, order: usize, i: Idx) -> Eval<C> {
+        self.derivative(order).eval(i)
+    }
+
     /// Given at least `t` polynomial evaluations, it will recover the polynomial's
     /// constant term
     pub fn recover(t: usize, shares: Vec<Eval<C>>) -> Result<C, PolyError> {
         let xs = Self::share_map(t, shares)?;
 
-        // iterate over all indices and for each multiply the lagrange basis
-        // with the value of the share
-        let mut acc = C::zero();
-        for (i, xi) in &xs {
-            let mut yi = xi.1.clone();
-            let mut num = C::RHS::one();
-            let mut den = C::RHS::one();
+        // compute the lagrange basis at 0 for each index, and combine them
+        // with the values of the shares
+        let indices = xs.keys().cloned().collect::<Vec<_>>();
+        let coeffs = LagrangeCoefficients::<C::RHS>::for_indices(&indices)?;
+        let values = xs.into_values().map(|xi| xi.1).collect::<Vec<_>>();
 
-            for (j, xj) in &xs {
-                if i == j {
-                    continue;
-                }
+        Ok(C::msm(&values, &coeffs.coeffs))
+    }
 
-                // xj - 0
-                num.mul(&xj.0);
+    /// Recovers the polynomial's constant term from the evaluations at the
+    /// indices of the precomputed Lagrange coefficients. The evaluations at
+    /// other indices are ignored.
+    pub fn recover_with(
+        coeffs: &LagrangeCoefficients<C::RHS>,
+        shares: Vec<Eval<C>>,
+    ) -> Result<C, PolyError> {
+        let mut values = shares
+            .into_iter()
+            .map(|sh| (sh.index, sh.value))
+            .collect::<HashMap<_, _>>();
+        let values = coeffs
+            .indices
+            .iter()
+            .filter_map(|i| values.remove(i))
+            .collect::<Vec<_>>();
+        if values.len() < coeffs.indices.len() {
+            return Err(PolyError::InvalidRecovery(
+                values.len(),
+                coeffs.indices.len(),
+            ));
+        }
 
-                // 1 / (xj - xi)
-                let mut tmp = xj.0.clone();
-                tmp.sub(&xi.0);
-                den.mul(&tmp);
-            }
+        Ok(C::msm(&values, &coeffs.coeffs))
+    }
 
-            let inv = den.inverse().ok_or(PolyError::NoInverse)?;
-            num.mul(&inv);
-            yi.mul(&num);
-            acc.add(&yi);
-        }
+    /// Recovers the polynomial's constant term from the shares of a
+    /// hierarchical sharing with Birkhoff interpolation. The shares must reach
+    /// the thresholds of the hierarchy.
+    pub fn recover_hierarchical(
+        hierarchy: &Hierarchy,
+        shares: Vec<Eval<C>>,
+    ) -> Result<C, PolyError> {
+        let indices = shares.iter().map(|sh| sh.index).collect::<Vec<_>>();
+        let coeffs = hierarchy.coefficients(&indices)?;
+        Self::recover_with(&coeffs, shares)
+    }
 
-        Ok(acc)
+    /// Given at least `t` polynomial evaluations, recovers the value of the
+    /// polynomial at the given x coordinate
+    pub fn recover_at(t: usize, shares: Vec<Eval<C>>, x: &C::RHS) -> Result<C, PolyError> {
+        let xs = Self::share_map(t, shares)?;
+        Self::interpolate_at(&xs.into_values().collect::<Vec<_>>(), x)
     }
 
     /// Given at least `t` polynomial evaluations, it will recover the entire polynomial
     pub fn full_recover(t: usize, shares: Vec<Eval<C>>) -> Result<Self, PolyError> {
         let xs = Self::share_map(t, shares)?;
+        Self::interpolate(xs.into_values().collect())
+    }
 
-        // iterate over all indices and for each multiply the lagrange basis
-        // with the value of the share
-        let res = xs
-            .iter()
-            // get the share and the lagrange basis
-            .map(|(i, share)| (share, Poly::<C::RHS>::lagrange_basis(*i, &xs)))
-            // get the linear combination poly
-            .map(|(share, basis)| {
-                // calculate the linear combination coefficients
-                let linear_coeffs = basis
-                    .0
-                    .into_iter()
-                    .map(move |c| {
-                        // y_j * L_y
-                        // TODO: Can we avoid allocating here?
-                        let mut s = share.1.clone();
-                        s.mul(&c);
-                        s
-                    })
-                    .collect::<Vec<_>>();
-
-                Self::from(linear_coeffs)
+    /// Returns the value at `x` of the polynomial of degree `points.len() - 1`
+    /// going through the given points `(x_j, y_j)`
+    pub fn interpolate_at(points: &[(C::RHS, C)], x: &C::RHS) -> Result<C, PolyError> {
+        let (xs, values): (Vec<_>, Vec<_>) = points.iter().cloned().unzip();
+        let coeffs = lagrange_at(&xs, x)?;
+        Ok(C::msm(&values, &coeffs))
+    }
+
+    /// Returns the polynomial of degree `points.len() - 1` going through the
+    /// given points `(x_j, y_j)`
+    pub fn interpolate(points: Vec<(C::RHS, C)>) -> Result<Self, PolyError> {
+        if points.is_empty() {
+            return Ok(Self::zero());
+        }
+        let (xs, values): (Vec<_>, Vec<_>) = points.into_iter().unzip();
+
+        // each coefficient is the linear combination of the values with the
+        // coefficients of the lagrange basis polynomials
+        let bases = Poly::<C::RHS>::lagrange_bases(&xs)?;
+        let coeffs = (0..xs.len())
+            .map(|k| {
+                let weights = bases.iter().map(|b| b.0[k].clone()).collect::<Vec<_>>();
+                C::msm(&values, &weights)
             })
-            .fold(Self::zero(), |mut acc, poly| {
-                acc.add(&poly);
-                acc
-            });
+            .collect::<Vec<_>>();
 
-        Ok(res)
+        Ok(Self::from(coeffs))
     }
 
     fn share_map(
@@ -216,9 +269,7 @@ where
             .into_iter()
             .take(t)
             .fold(BTreeMap::new(), |mut m, sh| {
-                let mut xi = C::RHS::new();
-                xi.set_int((sh.index + 1).into());
-                m.insert(sh.index, (xi, sh.value));
+                m.insert(sh.index, (x_coordinate(sh.index), sh.value));
                 m
             });
 
@@ -234,6 +285,508 @@ where
     }
 }
 
+/// LagrangeCoefficients holds the Lagrange coefficients at 0 of a set of
+/// indices, i.e. the weights of the evaluations at these indices which
+/// recover the constant term of a polynomial. They only depend on the indices,
+/// so they can be reused for every recovery from the same set of signers.
+/// They are not deserializable, since a coefficient per index is required:
+/// they are computed with `for_indices` or `Hierarchy::coefficients`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LagrangeCoefficients<X> {
+    indices: Vec<Idx>,
+    coeffs: Vec<X>,
+    orders: Vec<usize>,
+}
+
+impl<X: Scalar<RHS = X>> LagrangeCoefficients<X> {
+    /// Computes the coefficients of the given indices, with a single field
+    /// inversion. It fails if an index is repeated.
+    pub fn for_indices(indices: &[Idx]) -> Result<Self, PolyError> {
+        let mut indices = indices.to_vec();
+        indices.sort_unstable();
+
+        let xs = indices.iter().map(|i| x_coordinate(*i)).collect::<Vec<_>>();
+        let coeffs = lagrange_at(&xs, &X::zero())?;
+        let orders = vec![0; indices.len()];
+
+        Ok(Self {
+            indices,
+            coeffs,
+            orders,
+        })
+    }
+
+    /// Returns the sorted indices
+    pub fn indices(&self) -> &[Idx] {
+        &self.indices
+    }
+
+    /// Returns the coefficient of each index, in the same order
+    pub fn coefficients(&self) -> &[X] {
+        &self.coeffs
+    }
+
+    /// Returns the order of the derivative evaluated for the share of the
+    /// index, which is 0 unless the coefficients are the Birkhoff ones of a
+    /// hierarchy, or None if the index is not one of the coefficients.
+    pub fn order(&self, idx: Idx) -> Option<usize> {
+        self.indices
+            .binary_search(&idx)
+            .ok()
+            .map(|i| self.orders[i])
+    }
+}
+
+/// Hierarchy describes a hierarchical threshold sharing (Tassa's scheme). The
+/// indices are split in levels, level 0 being the most privileged one, and
+/// each level has a cumulative threshold: a set of shares recovers the secret
+/// if, for every level `l`, it holds at least `thresholds[l]` shares of level
+/// `l` or lower. The threshold of the last level is the number of shares
+/// required in total, e.g. "3 shares, at least 1 of level 0" is `[1, 3]`.
+///
+/// The share of an index of level `l` is the derivative of order
+/// `thresholds[l - 1]` (0 for level 0) of a polynomial of degree
+/// `threshold() - 1`, evaluated at its x coordinate. The shares have the same
+/// format as flat ones, but their partial signatures are created with
+/// `ThresholdScheme::partial_sign_derivative`, so that they carry the order.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Hierarchy {
+    thresholds: Vec<usize>,
+    levels: BTreeMap<Idx, usize>,
+}
+
+impl Hierarchy {
+    /// Creates a hierarchy from the strictly increasing cumulative threshold
+    /// of each level and from the level of each index. The indices must be
+    /// able to reach the thresholds.
+    pub fn new(thresholds: Vec<usize>, levels: BTreeMap<Idx, usize>) -> Result<Self, PolyError> {
+        if thresholds.is_empty() || thresholds[0] == 0 {
+            return Err(PolyError::InvalidHierarchy("thresholds must be positive"));
+        }
+        if thresholds.windows(2).any(|w| w[0] >= w[1]) {
+            return Err(PolyError::InvalidHierarchy(
+                "thresholds must be strictly increasing",
+            ));
+        }
+        if levels.values().any(|l| *l >= thresholds.len()) {
+            return Err(PolyError::InvalidHierarchy("unknown level"));
+        }
+
+        let hierarchy = Self { thresholds, levels };
+        let indices = hierarchy.levels.keys().cloned().collect::<Vec<_>>();
+        if !hierarchy.is_authorized(&indices) {
+            return Err(PolyError::InvalidHierarchy("thresholds cannot be reached"));
+        }
+        Ok(hierarchy)
+    }
+
+    /// Returns the number of shares required to recover the secret
+    pub fn threshold(&self) -> usize {
+        *self.thresholds.last().expect("hierarchy without levels")
+    }
+
+    /// Returns the level of the index, if it is part of the hierarchy
+    pub fn level(&self, idx: Idx) -> Option<usize> {
+        self.levels.get(&idx).cloned()
+    }
+
+    /// Returns the order of the derivative evaluated for the share of the
+    /// index, if it is part of the hierarchy
+    pub fn order(&self, idx: Idx) -> Option<usize> {
+        self.level(idx)
+            .map(|l| if l == 0 { 0 } else { self.thresholds[l - 1] })
+    }
+
+    /// Evaluates the share of the index on the polynomial, which can be the
+    /// private or the public one.
+    pub fn eval<C>(&self, poly: &Poly<C>, idx: Idx) -> Option<Eval<C>>
+    where
+        C: Element,
+        C::RHS: Scalar<RHS = C::RHS>,
+    {
+        self.order(idx)
+            .map(|order| poly.eval_derivative(order, idx))
+    }
+
+    /// Returns true if the shares of the indices can recover the secret.
+    /// Unknown and repeated indices are ignored.
+    pub fn is_authorized(&self, indices: &[Idx]) -> bool {
+        let mut counts = vec![0; self.thresholds.len()];
+        let mut indices = indices.to_vec();
+        indices.sort_unstable();
+        indices.dedup();
+        indices
+            .iter()
+            .filter_map(|i| self.level(*i))
+            .for_each(|l| counts[l] += 1);
+
+        let mut total = 0;
+        counts.iter().zip(&self.thresholds).all(|(count, t)| {
+            total += count;
+            total >= *t
+        })
+    }
+
+    /// Computes the Birkhoff coefficients of the indices, i.e. the weights of
+    /// their shares which recover the secret. Like Lagrange coefficients, they
+    /// only depend on the indices, and can be used with `Poly::recover_with`
+    /// and `ThresholdScheme::aggregate_with`. Only the `threshold()` most
+    /// privileged indices are used.
+    pub fn coefficients<X: Scalar<RHS = X>>(
+        &self,
+        indices: &[Idx],
+    ) -> Result<LagrangeCoefficients<X>, PolyError> {
+        if !self.is_authorized(indices) {
+            return Err(PolyError::Unauthorized);
+        }
+        // the most privileged shares still reach every threshold
+        let mut selected = indices
+            .iter()
+            .filter_map(|i| self.level(*i).map(|l| (l, *i)))
+            .collect::<Vec<_>>();
+        selected.sort_unstable();
+        selected.dedup();
+        selected.truncate(self.threshold());
+        let mut indices = selected.into_iter().map(|(_, i)| i).collect::<Vec<_>>();
+        indices.sort_unstable();
+
+        // the weighted sum of the shares must map the coefficients of the
+        // polynomial to its constant term: the k-th equation sums the
+        // derivatives of x^k at the x coordinate of each share
+        let t = self.threshold();
+        let mut matrix = vec![Vec::with_capacity(indices.len()); t];
+        for i in &indices {
+            let order = self.order(*i).expect("selected index");
+            let x: X = x_coordinate(*i);
+            let mut power = X::one();
+            for (k, row) in matrix.iter_mut().enumerate() {
+                if k < order {
+                    row.push(X::zero());
+                    continue;
+                }
+                let mut c = falling_factorial::<X>(k, order);
+                c.mul(&power);
+                row.push(c);
+                power.mul(&x);
+            }
+        }
+        let mut rhs = vec![X::zero(); t];
+        rhs[0] = X::one();
+        let coeffs =
+            solve_linear(&mut matrix, &mut rhs, indices.len()).ok_or(PolyError::Singular)?;
+        let orders = indices
+            .iter()
+            .map(|i| self.order(*i).expect("selected index"))
+            .collect();
+
+        Ok(LagrangeCoefficients {
+            indices,
+            coeffs,
+            orders,
+        })
+    }
+}
+
+/// Returns `n! / (n - k)!`, i.e. the factor of the derivative of order `k` of
+/// `x^n`.
+fn falling_factorial<X: Scalar<RHS = X>>(n: usize, k: usize) -> X {
+    let mut res = X::one();
+    for i in n + 1 - k..=n {
+        let mut factor = X::new();
+        factor.set_int(i as u64);
+        res.mul(&factor);
+    }
+    res
+}
+
+/// Returns the x coordinate of the evaluation at the given index. It is
+/// `index + 1`, since evaluating the polynomial at 0 reveals its constant term,
+/// i.e. the secret.
+pub fn x_coordinate<X: Scalar>(i: Idx) -> X {
+    let mut x = X::new();
+    x.set_int(u64::from(i) + 1);
+    x
+}
+
+/// Returns the lagrange basis polynomials of the given points evaluated at
+/// `x`, i.e. `L_i(x) = prod_{j != i} (x - x_j) / (x_i - x_j)`, with a single
+/// field inversion. It fails if a point is repeated.
+fn lagrange_at<X: Scalar<RHS = X>>(xs: &[X], x: &X) -> Result<Vec<X>, PolyError> {
+    let diffs = xs
+        .iter()
+        .map(|xj| {
+            let mut diff = x.clone();
+            diff.sub(xj);
+            diff
+        })
+        .collect::<Vec<_>>();
+    // suffixes[i] = prod_{j >= i} (x - x_j)
+    let mut suffixes = vec![X::one(); xs.len() + 1];
+    for (j, diff) in diffs.iter().enumerate().rev() {
+        let mut suffix = suffixes[j + 1].clone();
+        suffix.mul(diff);
+        suffixes[j] = suffix;
+    }
+
+    let mut dens = xs
+        .iter()
+        .enumerate()
+        .map(|(i, xi)| {
+            let mut den = X::one();
+            for (j, xj) in xs.iter().enumerate() {
+                if i != j {
+                    let mut tmp = xi.clone();
+                    tmp.sub(xj);
+                    den.mul(&tmp);
+                }
+            }
+            den
+        })
+        .collect::<Vec<_>>();
+    batch_inverse(&mut dens)?;
+
+    // the numerators are the products of the differences before and after i
+    let mut prefix = X::one();
+    Ok(dens
+        .into_iter()
+        .zip(diffs)
+        .zip(&suffixes[1..])
+        .map(|((inv, diff), suffix)| {
+            let mut coeff = prefix.clone();
+            coeff.mul(suffix);
+            coeff.mul(&inv);
+            prefix.mul(&diff);
+            coeff
+        })
+        .collect())
+}
+
+/// Inverts all the elements in place with Montgomery's trick, i.e. a single
+/// inversion and three multiplications per element.
+fn batch_inverse<X: Scalar<RHS = X>>(elements: &mut [X]) -> Result<(), PolyError> {
+    // prefix[i] = e_0 * ... * e_{i-1}
+    let mut prefix = Vec::with_capacity(elements.len());
+    let mut acc = X::one();
+    for e in elements.iter() {
+        prefix.push(acc.clone());
+        acc.mul(e);
+    }
+
+    let mut inv = acc.inverse().ok_or(PolyError::NoInverse)?;
+    for (e, prefix) in elements.iter_mut().zip(prefix).rev() {
+        // inv = 1 / (e_0 * ... * e_i)
+        let mut e_inv = inv.clone();
+        e_inv.mul(&prefix);
+        inv.mul(e);
+        *e = e_inv;
+    }
+    Ok(())
+}
+
+/// Solves the linear system `matrix * x = rhs` with `unknowns` columns by
+/// Gaussian elimination. The free variables are set to zero, and None is
+/// returned if the system has no solution.
+fn solve_linear<X: Scalar<RHS = X>>(
+    matrix: &mut [Vec<X>],
+    rhs: &mut [X],
+    unknowns: usize,
+) -> Option<Vec<X>> {
+    let mut pivots = Vec::new();
+    for col in 0..unknowns {
+        let row = pivots.len();
+        let pivot = match (row..matrix.len()).find(|r| matrix[*r][col] != X::zero()) {
+            Some(pivot) => pivot,
+            None => continue,
+        };
+        matrix.swap(row, pivot);
+        rhs.swap(row, pivot);
+
+        // normalize the pivot row, then eliminate the column from the others
+        let inv = matrix[row][col].inverse()?;
+        matrix[row].iter_mut().for_each(|c| c.mul(&inv));
+        rhs[row].mul(&inv);
+        let pivot_row = matrix[row].clone();
+        let pivot_rhs = rhs[row].clone();
+        for (r, (equation, value)) in matrix.iter_mut().zip(rhs.iter_mut()).enumerate() {
+            if r == row || equation[col] == X::zero() {
+                continue;
+            }
+            let factor = equation[col].clone();
+            for (c, p) in equation[col..].iter_mut().zip(&pivot_row[col..]) {
+                let mut tmp = p.clone();
+                tmp.mul(&factor);
+                c.sub(&tmp);
+            }
+            let mut tmp = pivot_rhs.clone();
+            tmp.mul(&factor);
+            value.sub(&tmp);
+        }
+        pivots.push(col);
+    }
+
+    // the remaining equations must be 0 = 0
+    if rhs[pivots.len()..].iter().any(|c| c != &X::zero()) {
+        return None;
+    }
+    let mut solution = vec![X::zero(); unknowns];
+    for (row, col) in pivots.into_iter().enumerate() {
+        solution[col] = rhs[row].clone();
+    }
+    Some(solution)
+}
+
+/// Divides the polynomial by a monic polynomial, and returns the quotient and
+/// the remainder.
+fn div_rem_monic<X: Scalar<RHS = X>>(num: &[X], den: &[X]) -> (Vec<X>, Vec<X>) {
+    let mut rem = num.to_vec();
+    if num.len() < den.len() {
+        return (vec![X::zero()], rem);
+    }
+    let mut quotient = vec![X::zero(); num.len() - den.len() + 1];
+    for k in (0..quotient.len()).rev() {
+        let c = rem[k + den.len() - 1].clone();
+        for (j, d) in den.iter().enumerate() {
+            let mut tmp = d.clone();
+            tmp.mul(&c);
+            rem[k + j].sub(&tmp);
+        }
+        quotient[k] = c;
+    }
+    rem.truncate(den.len() - 1);
+    (quotient, rem)
+}
+
+/// Below this number of coefficients, polynomials are multiplied with the
+/// schoolbook algorithm
+const NTT_THRESHOLD: usize = 64;
+
+fn schoolbook_mul<X: Scalar<RHS = X>>(a: &[X], b: &[X]) -> Vec<X> {
+    let mut coeffs = vec![X::zero(); a.len() + b.len() - 1];
+    for (i, c1) in a.iter().enumerate() {
+        for (j, c2) in b.iter().enumerate() {
+            // c_ij += c1 * c2
+            let mut tmp = c1.clone();
+            tmp.mul(c2);
+            coeffs[i + j].add(&tmp);
+        }
+    }
+    coeffs
+}
+
+/// Multiplies the polynomials by evaluating them at the n-th roots of unity,
+/// with n the smallest power of two above the number of coefficients of the
+/// product. Returns None if the field has no such root of unity.
+fn ntt_mul<X: Scalar<RHS = X>>(a: &[X], b: &[X]) -> Option<Vec<X>> {
+    let len = a.len() + b.len() - 1;
+    let n = len.next_power_of_two();
+    let omega = X::root_of_unity(n)?;
+    let omega_inv = omega.inverse()?;
+    let mut n_inv = X::new();
+    n_inv.set_int(n as u64);
+    let n_inv = n_inv.inverse()?;
+
+    let mut fa = a.to_vec();
+    fa.resize(n, X::zero());
+    let mut fb = b.to_vec();
+    fb.resize(n, X::zero());
+    ntt(&mut fa, &omega);
+    ntt(&mut fb, &omega);
+    fa.iter_mut().zip(&fb).for_each(|(x, y)| x.mul(y));
+
+    // the inverse transform is the transform with the inverse root, scaled
+    ntt(&mut fa, &omega_inv);
+    fa.truncate(len);
+    fa.iter_mut().for_each(|c| c.mul(&n_inv));
+    Some(fa)
+}
+
+/// Evaluates in place the polynomial at the powers of omega, a primitive n-th
+/// root of unity with n the number of coefficients, a power of two
+/// (iterative Cooley-Tukey).
+fn ntt<X: Scalar<RHS = X>>(a: &mut [X], omega: &X) {
+    let n = a.len();
+    debug_assert!(n.is_power_of_two());
+    if n == 1 {
+        return;
+    }
+
+    // bit-reversal permutation
+    let bits = n.trailing_zeros();
+    for i in 0..n {
+        let j = i.reverse_bits() >> (usize::BITS - bits);
+        if i < j {
+            a.swap(i, j);
+        }
+    }
+
+    let mut len = 2;
+    while len <= n {
+        // w_len = omega^(n / len) is a primitive len-th root of unity
+        let mut w_len = omega.clone();
+        let mut k = n / len;
+        while k > 1 {
+            let tmp = w_len.clone();
+            w_len.mul(&tmp);
+            k /= 2;
+        }
+
+        let half = len / 2;
+        for chunk in a.chunks_mut(len) {
+            let mut w = X::one();
+            let (lo, hi) = chunk.split_at_mut(half);
+            for (u, v) in lo.iter_mut().zip(hi.iter_mut()) {
+                // (u, v) = (u + w * v, u - w * v)
+                v.mul(&w);
+                let mut diff = u.clone();
+                diff.sub(v);
+                u.add(v);
+                *v = diff;
+                w.mul(&w_len);
+            }
+        }
+        len *= 2;
+    }
+}
+
+/// LagrangeCache keeps the Lagrange coefficients of the sets of indices which
+/// were already used. It never evicts them, so it is meant for committees
+/// whose signer sets rarely change.
+#[derive(Debug)]
+pub struct LagrangeCache<X> {
+    cache: RwLock<HashMap<Vec<Idx>, Arc<LagrangeCoefficients<X>>>>,
+}
+
+impl<X> Default for LagrangeCache<X> {
+    fn default() -> Self {
+        Self {
+            cache: RwLock::new(HashMap::new()),
+        }
+    }
+}
+
+impl<X: Scalar<RHS = X>> LagrangeCache<X> {
+    /// Creates an empty cache
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the coefficients of the indices, computing them the first time
+    pub fn get(&self, indices: &[Idx]) -> Result<Arc<LagrangeCoefficients<X>>, PolyError> {
+        let mut key = indices.to_vec();
+        key.sort_unstable();
+        if let Some(coeffs) = self.cache.read().expect("lock poisoned").get(&key) {
+            return Ok(coeffs.clone());
+        }
+
+        let coeffs = Arc::new(LagrangeCoefficients::for_indices(&key)?);
+        self.cache
+            .write()
+            .expect("lock poisoned")
+            .insert(key, coeffs.clone());
+        Ok(coeffs)
+    }
+}
+
 impl<C: Element> From<Vec<C>> for Poly<C> {
     fn from(c: Vec<C>) -> Self {
         Self(c)
@@ -249,33 +802,23 @@ impl<C: Element> From<Poly<C>> for Vec<C> {
 impl<X: Scalar<RHS = X>> Poly<X> {
     /// Performs the multiplication operation.
     ///
-    /// Note this is a simple implementation that is suitable for secret sharing schemes,
-    /// but may be inneficient for other purposes: the degree of the returned polynomial
-    /// is always the greatest possible, regardless of the actual coefficients
-    /// given.
-    // TODO: Implement divide and conquer algorithm
+    /// Large polynomials are multiplied in quasi-linear time with a number
+    /// theoretic transform when the field has the required roots of unity,
+    /// and with the schoolbook algorithm otherwise. The degree of the returned
+    /// polynomial is always the greatest possible, regardless of the actual
+    /// coefficients given.
     fn mul(&mut self, other: &Self) {
         if self.is_zero() || other.is_zero() {
             *self = Self::zero();
             return;
         }
 
-        let d3 = self.degree() + other.degree();
-
-        // need to initializes every coeff to zero first
-        let mut coeffs = (0..=d3).map(|_| X::zero()).collect::<Vec<X>>();
-
-        for (i, c1) in self.0.iter().enumerate() {
-            for (j, c2) in other.0.iter().enumerate() {
-                // c_ij += c1 * c2
-                let mut tmp = X::one();
-                tmp.mul(c1);
-                tmp.mul(c2);
-                coeffs[i + j].add(&tmp);
-            }
-        }
-
-        self.0 = coeffs;
+        let coeffs = if self.0.len().min(other.0.len()) >= NTT_THRESHOLD {
+            ntt_mul(&self.0, &other.0)
+        } else {
+            None
+        };
+        self.0 = coeffs.unwrap_or_else(|| schoolbook_mul(&self.0, &other.0));
     }
 
     /// Returns the scalar polynomial f(x) = x - c
@@ -284,56 +827,135 @@ impl<X: Scalar<RHS = X>> Poly<X> {
         Poly::from(vec![c, X::one()])
     }
 
-    /// Computes the lagrange basis polynomial of index i
-    fn lagrange_basis<E: Element<RHS = X>>(i: Idx, xs: &BTreeMap<Idx, (X, E)>) -> Poly<X> {
-        let mut basis = Poly::<X>::from(vec![X::one()]);
+    /// Returns the polynomial prod_i (x - roots[i]). The halves are multiplied
+    /// recursively (i.e. along a subproduct tree), so that the large products
+    /// use the fast multiplication.
+    fn from_roots(roots: &[X]) -> Poly<X> {
+        match roots {
+            [] => Poly::from(vec![X::one()]),
+            [root] => Poly::<X>::new_neg_constant(root.clone()),
+            _ => {
+                let (left, right) = roots.split_at(roots.len() / 2);
+                let mut poly = Self::from_roots(left);
+                poly.mul(&Self::from_roots(right));
+                poly
+            }
+        }
+    }
 
-        // accumulator of the denominator values
-        let mut acc = X::one();
+    /// Divides the polynomial by (x - root), dropping the remainder
+    fn div_by_root(&self, root: &X) -> Poly<X> {
+        let mut quotient = vec![X::zero(); self.0.len() - 1];
+        let mut acc = X::zero();
+        for (q, c) in quotient.iter_mut().zip(&self.0[1..]).rev() {
+            acc.mul(root);
+            acc.add(c);
+            *q = acc.clone();
+        }
+        Poly::from(quotient)
+    }
 
-        // TODO remove that cloning due to borrowing issue with the map
-        let xi = xs.get(&i).unwrap().clone().0;
-        for (idx, sc) in xs.iter() {
-            if *idx == i {
-                continue;
-            }
+    /// Computes the lagrange basis polynomials of the given points. Each of
+    /// them is the quotient of prod_j (x - x_j) by (x - x_i), divided by its
+    /// value at x_i, so that all of them only take a quadratic time overall.
+    fn lagrange_bases(xs: &[X]) -> Result<Vec<Poly<X>>, PolyError> {
+        let master = Self::from_roots(xs);
+        let quotients = xs
+            .iter()
+            .map(|xi| master.div_by_root(xi))
+            .collect::<Vec<_>>();
 
-            // basis * (x - sc)
-            let minus_sc = Poly::<X>::new_neg_constant(sc.0.clone());
-            basis.mul(&minus_sc);
+        let mut dens = quotients
+            .iter()
+            .zip(xs)
+            .map(|(q, xi)| q.eval_at(xi))
+            .collect::<Vec<_>>();
+        batch_inverse(&mut dens)?;
 
-            // den = xi - sc
-            let mut den = X::zero();
-            den.add(&xi);
-            den.sub(&sc.0);
+        Ok(quotients
+            .into_iter()
+            .zip(dens)
+            .map(|(mut q, den)| {
+                q.0.iter_mut().for_each(|c| c.mul(&den));
+                q
+            })
+            .collect())
+    }
 
-            // den = 1 / den
-            den = den.inverse().unwrap();
+    /// Recovers the polynomial of degree `t - 1` from `n >= t + 2e` evaluations
+    /// of which at most `e` are incorrect, with the Berlekamp-Welch decoder of
+    /// Reed-Solomon codes, and returns it along with the indices of the
+    /// incorrect evaluations.
+    ///
+    /// Evaluations on points can not be decoded this way: the incorrect ones
+    /// must be identified with their commitments or signatures instead.
+    pub fn robust_recover(
+        t: usize,
+        mut shares: Vec<Eval<X>>,
+    ) -> Result<(Self, Vec<Idx>), PolyError> {
+        shares.sort_by_key(|sh| sh.index);
+        shares.dedup_by_key(|sh| sh.index);
+        if t == 0 || shares.len() < t {
+            return Err(PolyError::InvalidRecovery(shares.len(), t));
+        }
+        let n = shares.len();
+        let e = (n - t) / 2;
 
-            // acc = acc * den
-            acc.mul(&den);
+        // find the error locator E (monic, of degree e) and Q = P * E (of
+        // degree e + t - 1) such that Q(x_i) = y_i * E(x_i) for every share
+        let unknowns = 2 * e + t;
+        let (mut matrix, mut rhs): (Vec<_>, Vec<_>) = shares
+            .iter()
+            .map(|sh| {
+                let xi = x_coordinate::<X>(sh.index);
+                let mut row = Vec::with_capacity(unknowns);
+                let mut power = X::one();
+                for _ in 0..e + t {
+                    row.push(power.clone());
+                    power.mul(&xi);
+                }
+                // - y_i * x_i^j for the coefficients of E
+                let mut power = sh.value.clone();
+                power.negate();
+                for _ in 0..e {
+                    row.push(power.clone());
+                    power.mul(&xi);
+                }
+                // y_i * x_i^e
+                power.negate();
+                (row, power)
+            })
+            .unzip();
+        let solution =
+            solve_linear(&mut matrix, &mut rhs, unknowns).ok_or(PolyError::TooManyErrors(e))?;
+
+        let mut locator = solution[e + t..].to_vec();
+        locator.push(X::one());
+        let (quotient, remainder) = div_rem_monic(&solution[..e + t], &locator);
+        if remainder.iter().any(|c| c != &X::zero()) {
+            return Err(PolyError::TooManyErrors(e));
         }
 
-        // multiply all coefficients by the denominator
-        basis.mul(&Poly::from(vec![acc]));
-        basis
+        let poly = Self::from(quotient);
+        let bad = shares
+            .iter()
+            .filter(|sh| poly.eval(sh.index).value != sh.value)
+            .map(|sh| sh.index)
+            .collect::<Vec<_>>();
+        if bad.len() > e {
+            return Err(PolyError::TooManyErrors(e));
+        }
+        Ok((poly, bad))
     }
 
     /// Commits the scalar polynomial to the group and returns a polynomial over
     /// the group
     ///
     /// This is done by multiplying each coefficient of the polynomial with the
-    /// group's generator.
+    /// group's generator, using its precomputed table.
     pub fn commit<P: Point<RHS = X>>(&self) -> Poly<P> {
-        let commits = self
-            .0
-            .iter()
-            .map(|c| {
-                let mut commitment = P::one();
-                commitment.mul(c);
-                commitment
-            })
-            .collect::<Vec<P>>();
+        let table = P::generator_table();
+        let commits = self.0.iter().map(|c| table.mul(c)).collect::<Vec<P>>();
 
         Poly::<P>::from(commits)
     }
@@ -491,6 +1113,54 @@ pub mod tests {
 
     }
 
+    #[test]
+    fn lagrange_coefficients() {
+        let poly = Poly::<Sc>::new(4);
+        let shares = [7, 2, 11, 0, 5]
+            .iter()
+            .map(|i| poly.eval(*i))
+            .collect::<Vec<_>>();
+        let coeffs = LagrangeCoefficients::<Sc>::for_indices(&[7, 2, 11, 0, 5]).unwrap();
+        assert_eq!(coeffs.indices(), &[0, 2, 5, 7, 11]);
+
+        let expected = Poly::recover(5, shares.clone()).unwrap();
+        assert_eq!(&expected, poly.public_key());
+        assert_eq!(
+            Poly::recover_with(&coeffs, shares.clone()).unwrap(),
+            expected
+        );
+
+        // the other shares are ignored, and missing ones are reported
+        let mut more = shares.clone();
+        more.push(poly.eval(3));
+        assert_eq!(Poly::recover_with(&coeffs, more).unwrap(), expected);
+        let err = Poly::recover_with(&coeffs, shares[1..].to_vec()).unwrap_err();
+        assert!(matches!(err, PolyError::InvalidRecovery(4, 5)));
+
+        // on points too
+        let public = poly.commit::<G1>();
+        let shares = [0, 2, 5, 7, 11]
+            .iter()
+            .map(|i| public.eval(*i))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            &Poly::recover_with(&coeffs, shares).unwrap(),
+            public.public_key()
+        );
+
+        let err = LagrangeCoefficients::<Sc>::for_indices(&[1, 2, 1]).unwrap_err();
+        assert!(matches!(err, PolyError::NoInverse));
+    }
+
+    #[test]
+    fn lagrange_cache() {
+        let cache = LagrangeCache::<Sc>::new();
+        let coeffs = cache.get(&[3, 1, 2]).unwrap();
+        assert_eq!(coeffs.indices(), &[1, 2, 3]);
+        assert!(Arc::ptr_eq(&coeffs, &cache.get(&[1, 2, 3]).unwrap()));
+        assert!(!Arc::ptr_eq(&coeffs, &cache.get(&[1, 2, 4]).unwrap()));
+    }
+
     #[test]
     fn interpolation_insufficient_shares() {
         let degree = 4;
@@ -571,6 +1241,178 @@ pub mod tests {
         assert_eq!(total, res.value);
     }
 
+    #[test]
+    fn ntt_mul() {
+        for (d1, d2) in &[(63, 63), (64, 100), (200, 130), (255, 256)] {
+            let p1 = Poly::<Sc>::new(*d1);
+            let p2 = Poly::<Sc>::new(*d2);
+            let mut p3 = p1.clone();
+            p3.mul(&p2);
+            assert_eq!(p3.degree(), d1 + d2);
+            assert_eq!(p3.0, schoolbook_mul(&p1.0, &p2.0));
+        }
+    }
+
+    #[test]
+    fn from_roots() {
+        let roots = (0..200)
+            .map(|_| Sc::rand(&mut thread_rng()))
+            .collect::<Vec<_>>();
+        let p = Poly::<Sc>::from_roots(&roots);
+        assert_eq!(p.degree(), 200);
+        for root in &roots {
+            assert_eq!(p.eval_at(root), Sc::zero());
+        }
+
+        // removing a root
+        let q = p.div_by_root(&roots[7]);
+        assert_eq!(q.degree(), 199);
+        assert_eq!(q.eval_at(&roots[0]), Sc::zero());
+        assert_ne!(q.eval_at(&roots[7]), Sc::zero());
+    }
+
+    #[test]
+    fn full_recover_large() {
+        let poly = Poly::<Sc>::new(300);
+        let shares = (0..400).rev().map(|i| poly.eval(i)).collect::<Vec<_>>();
+        assert_eq!(Poly::full_recover(301, shares).unwrap(), poly);
+
+        let public = poly.commit::<G1>();
+        let shares = (0..301).map(|i| public.eval(i)).collect::<Vec<_>>();
+        assert_eq!(Poly::full_recover(301, shares).unwrap(), public);
+    }
+
+    #[test]
+    fn robust_recover() {
+        let rng = &mut thread_rng();
+        let poly = Poly::<Sc>::new(4);
+        let mut shares = (0..11).map(|i| poly.eval(i)).collect::<Vec<_>>();
+
+        // without errors
+        let (recovered, bad) = Poly::robust_recover(5, shares.clone()).unwrap();
+        assert_eq!(recovered, poly);
+        assert!(bad.is_empty());
+
+        // up to (11 - 5) / 2 = 3 errors are corrected
+        for i in &[9, 2, 5] {
+            shares[*i].value = Sc::rand(rng);
+        }
+        shares.reverse();
+        let (recovered, bad) = Poly::robust_recover(5, shares.clone()).unwrap();
+        assert_eq!(recovered, poly);
+        assert_eq!(bad, vec![2, 5, 9]);
+
+        // but not more
+        shares[0].value = Sc::rand(rng);
+        let err = Poly::robust_recover(5, shares.clone()).unwrap_err();
+        assert!(matches!(err, PolyError::TooManyErrors(3)));
+        // nor any error with t + 1 shares
+        let err = Poly::robust_recover(5, shares[4..10].to_vec()).unwrap_err();
+        assert!(matches!(err, PolyError::TooManyErrors(0)));
+        let err = Poly::robust_recover(5, shares[..4].to_vec()).unwrap_err();
+        assert!(matches!(err, PolyError::InvalidRecovery(4, 5)));
+    }
+
+    #[test]
+    fn explicit_points() {
+        let rng = &mut thread_rng();
+        let poly = Poly::<Sc>::new(4);
+        let public = poly.commit::<G1>();
+        assert_eq!(poly.eval_at(&x_coordinate(3)), poly.eval(3).value);
+        assert_eq!(&poly.eval_at(&Sc::zero()), poly.public_key());
+
+        // interpolation from arbitrary points, at arbitrary points
+        let xs = (0..5).map(|_| Sc::rand(rng)).collect::<Vec<_>>();
+        let points = xs.iter().map(|x| (*x, poly.eval_at(x))).collect::<Vec<_>>();
+        let x = Sc::rand(rng);
+        assert_eq!(Poly::interpolate_at(&points, &x).unwrap(), poly.eval_at(&x));
+        assert_eq!(Poly::interpolate_at(&points, &xs[2]).unwrap(), points[2].1);
+        assert_eq!(Poly::interpolate(points).unwrap(), poly);
+
+        let points = xs
+            .iter()
+            .map(|x| (*x, public.eval_at(x)))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            Poly::interpolate_at(&points, &x).unwrap(),
+            public.eval_at(&x)
+        );
+        assert_eq!(Poly::interpolate(points).unwrap(), public);
+
+        // recovery from the evaluations at indices
+        let shares = (0..5).map(|i| poly.eval(i * 2)).collect::<Vec<_>>();
+        assert_eq!(Poly::recover_at(5, shares, &x).unwrap(), poly.eval_at(&x));
+
+        let points = vec![(xs[0], Sc::one()), (xs[0], Sc::zero())];
+        let err = Poly::interpolate_at(&points, &x).unwrap_err();
+        assert!(matches!(err, PolyError::NoInverse));
+    }
+
+    #[test]
+    fn hierarchical() {
+        // 3 of 7, at least 1 of the first 2 indices
+        let levels = (0..7).map(|i| (i, if i < 2 { 0 } else { 1 })).collect();
+        let hierarchy = Hierarchy::new(vec![1, 3], levels).unwrap();
+        assert_eq!(hierarchy.order(1), Some(0));
+        assert_eq!(hierarchy.order(4), Some(1));
+        assert_eq!(hierarchy.order(7), None);
+
+        let poly = Poly::<Sc>::new(hierarchy.threshold() - 1);
+        let public = poly.commit::<G1>();
+        let shares = (0..7)
+            .map(|i| hierarchy.eval(&poly, i).unwrap())
+            .collect::<Vec<_>>();
+        let public_shares = (0..7)
+            .map(|i| hierarchy.eval(&public, i).unwrap())
+            .collect::<Vec<_>>();
+        for (share, public_share) in shares.iter().zip(&public_shares) {
+            let mut commitment = G1::one();
+            commitment.mul(&share.value);
+            assert_eq!(commitment, public_share.value);
+        }
+
+        for set in &[
+            vec![0, 3, 5],
+            vec![1, 2, 6],
+            vec![0, 1, 4],
+            vec![0, 1, 2, 3, 4],
+        ] {
+            let subset = set.iter().map(|i| shares[*i].clone()).collect();
+            let secret = Poly::recover_hierarchical(&hierarchy, subset).unwrap();
+            assert_eq!(&secret, poly.public_key());
+
+            let subset = set.iter().map(|i| public_shares[*i].clone()).collect();
+            let key = Poly::recover_hierarchical(&hierarchy, subset).unwrap();
+            assert_eq!(&key, public.public_key());
+        }
+
+        // enough shares, but none of the first level
+        let subset = shares[2..].to_vec();
+        let err = Poly::recover_hierarchical(&hierarchy, subset).unwrap_err();
+        assert!(matches!(err, PolyError::Unauthorized));
+        let subset = shares[..2].to_vec();
+        let err = Poly::recover_hierarchical(&hierarchy, subset).unwrap_err();
+        assert!(matches!(err, PolyError::Unauthorized));
+
+        // a single level is a flat sharing
+        let flat = Hierarchy::new(vec![3], (0..5).map(|i| (i, 0)).collect()).unwrap();
+        assert_eq!(
+            flat.coefficients::<Sc>(&[1, 3, 4]).unwrap(),
+            LagrangeCoefficients::for_indices(&[1, 3, 4]).unwrap()
+        );
+
+        for (thresholds, levels) in &[
+            (vec![0, 3], vec![0, 1, 1]),
+            (vec![2, 2], vec![0, 0, 1]),
+            (vec![1, 3], vec![1, 1, 1]),
+            (vec![1, 3], vec![0, 1, 2]),
+        ] {
+            let levels = levels.iter().enumerate().map(|(i, l)| (i as Idx, *l));
+            let err = Hierarchy::new(thresholds.clone(), levels.collect()).unwrap_err();
+            assert!(matches!(err, PolyError::InvalidHierarchy(_)));
+        }
+    }
+
     #[test]
     fn new_neg_constant() {
         let mut constant = Sc::rand(&mut thread_rng());
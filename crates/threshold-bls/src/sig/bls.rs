@@ -28,6 +28,9 @@ pub mod common {
     /// BLSScheme is an internal trait that encompasses the common work between a
     /// BLS signature over G1 or G2.
     pub trait BLSScheme: Scheme {
+        /// Public key prepared for the pairing
+        type Prepared: Clone + Debug + Send + Sync;
+
         /// Returns sig = msg^{private}. The message MUST be hashed before this call.
         fn internal_sign(
             private: &Self::Private,
@@ -54,6 +57,41 @@ pub mod common {
             sig_bytes: &[u8],
             should_hash: bool,
         ) -> Result<(), BLSError> {
+            let (sig, h) = Self::parse(msg, sig_bytes, should_hash)?;
+
+            let success = Self::final_exp(public, &sig, &h);
+            if !success {
+                return Err(BLSError::InvalidSig);
+            }
+
+            Ok(())
+        }
+
+        /// Verifies the signature with the prepared generator and public key
+        fn internal_verify_prepared(
+            generator: &Self::Prepared,
+            public: &Self::Prepared,
+            msg: &[u8],
+            sig_bytes: &[u8],
+            should_hash: bool,
+        ) -> Result<(), BLSError> {
+            let (sig, h) = Self::parse(msg, sig_bytes, should_hash)?;
+
+            let success = Self::final_exp_prepared(generator, public, &sig, &h);
+            if !success {
+                return Err(BLSError::InvalidSig);
+            }
+
+            Ok(())
+        }
+
+        /// Returns the signature and the message point, hashing the message
+        /// if required.
+        fn parse(
+            msg: &[u8],
+            sig_bytes: &[u8],
+            should_hash: bool,
+        ) -> Result<(Self::Signature, Self::Signature), BLSError> {
             let sig: Self::Signature = bincode::deserialize_from(sig_bytes)?;
 
             let h = if should_hash {
@@ -64,16 +102,23 @@ pub mod common {
                 bincode::deserialize_from(msg)?
             };
 
-            let success = Self::final_exp(public, &sig, &h);
-            if !success {
-                return Err(BLSError::InvalidSig);
-            }
-
-            Ok(())
+            Ok((sig, h))
         }
 
         /// Performs the final exponentiation for the BLS sig scheme
         fn final_exp(p: &Self::Public, sig: &Self::Signature, hm: &Self::Signature) -> bool;
+
+        /// Prepares the public key for the pairing
+        fn prepare(p: &Self::Public) -> Self::Prepared;
+
+        /// Performs the final exponentiation for the BLS sig scheme with the
+        /// prepared generator and public key
+        fn final_exp_prepared(
+            generator: &Self::Prepared,
+            p: &Self::Prepared,
+            sig: &Self::Signature,
+            hm: &Self::Signature,
+        ) -> bool;
     }
 
     impl<T> SignatureScheme for T
@@ -117,6 +162,9 @@ impl<C> common::BLSScheme for G1Scheme<C>
 where
     C: PairingCurve,
 {
+    // G1 keys do not need any preparation, the signatures are prepared instead
+    type Prepared = C::G1;
+
     fn final_exp(p: &Self::Public, sig: &Self::Signature, hm: &Self::Signature) -> bool {
         // e(g1,sig) == e(pub, H(m))
         // e(g1,H(m))^x == e(g1,H(m))^x
@@ -124,6 +172,19 @@ where
         let right = C::pair(p, hm);
         left == right
     }
+
+    fn prepare(p: &Self::Public) -> Self::Prepared {
+        p.clone()
+    }
+
+    fn final_exp_prepared(
+        generator: &Self::Prepared,
+        p: &Self::Prepared,
+        sig: &Self::Signature,
+        hm: &Self::Signature,
+    ) -> bool {
+        C::pairing_eq(generator, &C::prepare_g2(sig), p, &C::prepare_g2(hm))
+    }
 }
 
 /// G2Scheme implements the BLS signature scheme with G2 as private / public
@@ -146,6 +207,8 @@ impl<C> common::BLSScheme for G2Scheme<C>
 where
     C: PairingCurve,
 {
+    type Prepared = C::G2Prepared;
+
     fn final_exp(p: &Self::Public, sig: &Self::Signature, hm: &Self::Signature) -> bool {
         // e(sig,g2) == e(H(m),pub)
         // e(H(m),g2)^x == e(H(m),g2)^x
@@ -153,6 +216,19 @@ where
         let right = C::pair(hm, p);
         left == right
     }
+
+    fn prepare(p: &Self::Public) -> Self::Prepared {
+        C::prepare_g2(p)
+    }
+
+    fn final_exp_prepared(
+        generator: &Self::Prepared,
+        p: &Self::Prepared,
+        sig: &Self::Signature,
+        hm: &Self::Signature,
+    ) -> bool {
+        C::pairing_eq(sig, generator, hm, p)
+    }
 }
 
 #[cfg(test)]
@@ -10,6 +10,9 @@ pub use tblind::BlindThresholdError;
 mod tbls;
 pub use tbls::{Share, ThresholdError};
 
+mod verifier;
+pub use verifier::Verifier;
+
 #[allow(clippy::module_inception)]
 mod sig;
 pub use sig::*;
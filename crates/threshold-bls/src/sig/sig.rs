@@ -1,7 +1,7 @@
 pub use super::tbls::Share; // import and re-export it for easier access
 use crate::{
     group::{Element, Point, Scalar},
-    poly::Poly,
+    poly::{LagrangeCoefficients, Poly},
 };
 use rand_core::RngCore;
 use serde::{de::DeserializeOwned, Serialize};
@@ -26,8 +26,7 @@ pub trait Scheme: Debug {
     fn keypair<R: RngCore>(rng: &mut R) -> (Self::Private, Self::Public) {
         let private = Self::Private::rand(rng);
 
-        let mut public = Self::Public::one();
-        public.mul(&private);
+        let public = Self::Public::generator_table().mul(&private);
 
         (private, public)
     }
@@ -147,7 +146,20 @@ pub trait ThresholdScheme: Scheme {
     /// Partially signs a message with a share of the private key
     fn partial_sign(private: &Share<Self::Private>, msg: &[u8]) -> Result<Partial, Self::Error>;
 
-    /// Verifies a partial signature on a message against the public polynomial
+    /// Partially signs a message with a share of a hierarchical sharing, i.e.
+    /// the evaluation of the derivative of the given order. The partial
+    /// signature carries the order, so that it cannot be aggregated with
+    /// Lagrange interpolation. It is verified with `Verifier::with_hierarchy`,
+    /// which checks the order against the one of the hierarchy.
+    fn partial_sign_derivative(
+        private: &Share<Self::Private>,
+        order: usize,
+        msg: &[u8],
+    ) -> Result<Partial, Self::Error>;
+
+    /// Verifies a partial signature on a message against the public polynomial.
+    /// Only the flat partial signatures of `partial_sign` are accepted, and
+    /// identity signatures or keys are rejected.
     fn partial_verify(
         public: &Poly<Self::Public>,
         msg: &[u8],
@@ -156,8 +168,18 @@ pub trait ThresholdScheme: Scheme {
 
     /// Aggregates all partials signature together. Note that this method does
     /// not verify if the partial signatures are correct or not; it only
-    /// aggregates them.
+    /// aggregates them. The partial signatures of the lower levels of a
+    /// hierarchical sharing are rejected, they require `aggregate_with`.
     fn aggregate(threshold: usize, partials: &[Partial]) -> Result<Vec<u8>, Self::Error>;
+
+    /// Aggregates the partial signatures of the indices of the precomputed
+    /// Lagrange coefficients, ignoring the other ones. The order carried by
+    /// each partial signature must be the one of its coefficient. Like
+    /// `aggregate`, it does not verify the partial signatures.
+    fn aggregate_with(
+        coeffs: &LagrangeCoefficients<Self::Private>,
+        partials: &[Partial],
+    ) -> Result<Vec<u8>, Self::Error>;
 }
 
 /// BlindThreshold is ThresholdScheme that allows to verify a partially blinded
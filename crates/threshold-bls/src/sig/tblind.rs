@@ -1,4 +1,4 @@
-use crate::poly::{Eval, Poly};
+use crate::poly::{Eval, Idx, Poly};
 use crate::sig::tbls::Share;
 use crate::sig::{BlindScheme, BlindThresholdScheme, Partial, ThresholdScheme};
 use thiserror::Error;
@@ -14,6 +14,11 @@ pub enum BlindThresholdError<E: 'static + std::error::Error> {
     /// Raised when (de)serialization fails
     #[error(transparent)]
     BincodeError(#[from] bincode::Error),
+
+    /// Raised when the verifier has no share at the index of the partial
+    /// signature
+    #[error("no share at index {0}")]
+    UnknownShare(Idx),
 }
 
 impl<T> BlindThresholdScheme for T
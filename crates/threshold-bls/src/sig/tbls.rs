@@ -1,7 +1,9 @@
 //! Threshold Signatures implementation for any type which implements
 //! [`SignatureScheme`](../trait.SignatureScheme.html)
-use crate::poly::{Eval, Idx, Poly, PolyError};
+use crate::group::Element;
+use crate::poly::{Eval, Idx, LagrangeCoefficients, Poly, PolyError};
 use crate::sig::{Partial, SignatureScheme, ThresholdScheme};
+use bincode::Options;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
@@ -33,6 +35,28 @@ pub enum ThresholdError<I: SignatureScheme> {
     /// were fewer than the threshold
     #[error("not enough partial signatures: {0}/{1}")]
     NotEnoughPartialSignatures(usize, usize),
+
+    /// UnknownShare is raised when a partial signature is verified against
+    /// the keys of a `Verifier` which has no share at its index
+    #[error("no share at index {0}")]
+    UnknownShare(Idx),
+
+    /// HierarchicalPartial is raised when the partial signature of a share of
+    /// a hierarchical sharing is verified against the public polynomial or
+    /// aggregated with Lagrange interpolation, which only work for flat shares
+    #[error("the partial signature of index {0} is hierarchical")]
+    HierarchicalPartial(Idx),
+
+    /// InvalidOrder is raised when the order of the derivative of a partial
+    /// signature is not the one of its share index
+    #[error("invalid derivative order {1} for index {0}")]
+    InvalidOrder(Idx, usize),
+
+    /// IdentityPartial is raised when a partial signature or the key of its
+    /// share is the identity, since such a partial signature verifies without
+    /// the share
+    #[error("the partial signature of index {0} or its key is the identity")]
+    IdentityPartial(Idx),
 }
 
 impl<I: SignatureScheme> ThresholdScheme for I {
@@ -51,16 +75,42 @@ impl<I: SignatureScheme> ThresholdScheme for I {
         Ok(ret)
     }
 
+    fn partial_sign_derivative(
+        private: &Share<Self::Private>,
+        order: usize,
+        msg: &[u8],
+    ) -> Result<Vec<u8>, <Self as ThresholdScheme>::Error> {
+        if order == 0 {
+            return Self::partial_sign(private, msg);
+        }
+        let sig = Self::sign(&private.private, msg).map_err(ThresholdError::SignatureError)?;
+        let partial = Eval {
+            value: sig,
+            index: private.index,
+        };
+        let ret = bincode::serialize(&(partial, order as u32))?;
+        Ok(ret)
+    }
+
     fn partial_verify(
         public: &Poly<Self::Public>,
         msg: &[u8],
         partial: &[u8],
     ) -> Result<(), <Self as ThresholdScheme>::Error> {
-        let partial: Eval<Vec<u8>> = bincode::deserialize(partial)?;
+        // the order of a hierarchical share is only known from the hierarchy,
+        // so such partials are verified with `Verifier::with_hierarchy`
+        let (partial, order) = parse_partial::<Self>(partial)?;
+        if order != 0 {
+            return Err(ThresholdError::HierarchicalPartial(partial.index));
+        }
 
-        let public_i = public.eval(partial.index);
+        let public_i = public.eval(partial.index).value;
+        let sig: Self::Signature = bincode::deserialize(&partial.value)?;
+        if public_i == Self::Public::zero() || sig == Self::Signature::zero() {
+            return Err(ThresholdError::IdentityPartial(partial.index));
+        }
 
-        Self::verify(&public_i.value, msg, &partial.value).map_err(ThresholdError::SignatureError)
+        Self::verify(&public_i, msg, &partial.value).map_err(ThresholdError::SignatureError)
     }
 
     fn aggregate(
@@ -74,22 +124,71 @@ impl<I: SignatureScheme> ThresholdScheme for I {
             ));
         }
 
-        let valid_partials: Vec<Eval<Self::Signature>> = partials
-            .iter()
-            .map(|partial| {
-                let eval: Eval<Vec<u8>> = bincode::deserialize(partial)?;
-                let sig = bincode::deserialize(&eval.value)?;
-                Ok(Eval {
-                    index: eval.index,
-                    value: sig,
-                })
-            })
-            .collect::<Result<_, <Self as ThresholdScheme>::Error>>()?;
-
+        let valid_partials = parse_partials::<Self>(partials, |_| Some(0))?;
         let recovered_sig = Poly::<Self::Signature>::recover(threshold, valid_partials)
             .map_err(ThresholdError::PolyError)?;
         Ok(bincode::serialize(&recovered_sig).expect("could not serialize"))
     }
+
+    fn aggregate_with(
+        coeffs: &LagrangeCoefficients<Self::Private>,
+        partials: &[Partial],
+    ) -> Result<Vec<u8>, <Self as ThresholdScheme>::Error> {
+        let valid_partials = parse_partials::<Self>(partials, |idx| coeffs.order(idx))?;
+
+        let recovered_sig = Poly::<Self::Signature>::recover_with(coeffs, valid_partials)
+            .map_err(ThresholdError::PolyError)?;
+        Ok(bincode::serialize(&recovered_sig).expect("could not serialize"))
+    }
+}
+
+// deserializes a partial signature along with the order of the derivative
+// evaluated for its share, which is only serialized for the lower levels of a
+// hierarchical sharing. Any other trailing bytes are rejected.
+pub(crate) fn parse_partial<I: SignatureScheme>(
+    partial: &[u8],
+) -> Result<(Eval<Vec<u8>>, usize), ThresholdError<I>> {
+    let mut reader = partial;
+    let eval: Eval<Vec<u8>> = bincode::deserialize_from(&mut reader)?;
+    if reader.is_empty() {
+        return Ok((eval, 0));
+    }
+    let order = bincode::options()
+        .with_fixint_encoding()
+        .deserialize::<u32>(reader)? as usize;
+    if order == 0 {
+        return Err(ThresholdError::InvalidOrder(eval.index, order));
+    }
+    Ok((eval, order))
+}
+
+// deserializes the signatures of the partials along with their index. The
+// order of each partial must be the one returned for its index, partials of
+// unknown indices are kept with any order.
+fn parse_partials<I: SignatureScheme>(
+    partials: &[Partial],
+    expected: impl Fn(Idx) -> Option<usize>,
+) -> Result<Vec<Eval<I::Signature>>, ThresholdError<I>> {
+    partials
+        .iter()
+        .map(|partial| {
+            let (eval, order) = parse_partial(partial)?;
+            match expected(eval.index) {
+                Some(0) if order != 0 => {
+                    return Err(ThresholdError::HierarchicalPartial(eval.index))
+                }
+                Some(e) if e != order => {
+                    return Err(ThresholdError::InvalidOrder(eval.index, order))
+                }
+                _ => {}
+            }
+            let sig = bincode::deserialize(&eval.value)?;
+            Ok(Eval {
+                index: eval.index,
+                value: sig,
+            })
+        })
+        .collect()
 }
 
 #[cfg(test)]
@@ -123,7 +222,10 @@ mod tests {
         (shares, private.commit())
     }
 
-    fn test_threshold_scheme<T: ThresholdScheme + SignatureScheme>(creator: ShareCreator<T>) {
+    fn test_threshold_scheme<T>(creator: ShareCreator<T>)
+    where
+        T: ThresholdScheme<Error = ThresholdError<T>> + SignatureScheme,
+    {
         let threshold = 4;
         let (shares, public) = creator(5, threshold);
         let msg = vec![1, 9, 6, 9];
@@ -141,7 +243,40 @@ mod tests {
         );
         let final_sig = T::aggregate(threshold, &partials).unwrap();
 
+        // the order of a hierarchical share is not accepted from the partial
+        let mut partial = partials[0].clone();
+        partial.extend_from_slice(&(threshold as u32).to_le_bytes());
+        let err = T::partial_verify(&public, &msg, &partial).unwrap_err();
+        assert!(matches!(err, ThresholdError::HierarchicalPartial(0)));
+        let err = T::aggregate(1, &[partial.clone()]).unwrap_err();
+        assert!(matches!(err, ThresholdError::HierarchicalPartial(0)));
+        // nor any other trailing bytes
+        partial.push(0);
+        T::partial_verify(&public, &msg, &partial).unwrap_err();
+        let mut partial = partials[0].clone();
+        partial.extend_from_slice(&0u32.to_le_bytes());
+        let err = T::partial_verify(&public, &msg, &partial).unwrap_err();
+        assert!(matches!(err, ThresholdError::InvalidOrder(0, 0)));
+        // the identity does not hold a share
+        let identity = bincode::serialize(&Eval {
+            index: 0,
+            value: bincode::serialize(&T::Signature::zero()).unwrap(),
+        })
+        .unwrap();
+        let err = T::partial_verify(&public, &msg, &identity).unwrap_err();
+        assert!(matches!(err, ThresholdError::IdentityPartial(0)));
+
         T::verify(public.public_key(), &msg, &final_sig).unwrap();
+
+        // with the coefficients of the signers, ignoring the others
+        let coeffs = LagrangeCoefficients::for_indices(&[4, 1, 2, 0]).unwrap();
+        let sig = T::aggregate_with(&coeffs, &partials).unwrap();
+        assert_eq!(sig, final_sig);
+        let err = T::aggregate_with(&coeffs, &partials[1..]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "could not recover public key: Invalid recovery: only has 3/4 shares"
+        );
     }
 
     #[test]
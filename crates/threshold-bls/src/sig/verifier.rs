@@ -0,0 +1,307 @@
+//! Verifier for the (partial) signatures of a public key or public polynomial
+//! which does not change, such as the distributed key of a DKG.
+use crate::group::Element;
+use crate::poly::{Eval, Hierarchy, Idx, Poly};
+use crate::sig::bls::{common::BLSScheme, BLSError};
+use crate::sig::{tbls::parse_partial, BlindError, BlindThresholdError, ThresholdError};
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+/// Verifier caches the prepared pairing inputs of a public polynomial: the
+/// generator, the public key and the key of each valid share index once it has
+/// been evaluated. Creating it is more expensive than a single verification,
+/// but verifying many signatures against the same keys is much faster. Partial
+/// signatures of other indices are rejected, so the cache stays bounded.
+///
+/// The verifier can be shared between threads.
+#[derive(Debug)]
+pub struct Verifier<S: BLSScheme> {
+    generator: S::Prepared,
+    key: S::Prepared,
+    public: Poly<S::Public>,
+    n: usize,
+    hierarchy: Option<Hierarchy>,
+    shares: RwLock<HashMap<Idx, S::Prepared>>,
+}
+
+impl<S: BLSScheme> Verifier<S> {
+    /// Creates a verifier for the signatures of the public key
+    pub fn new(public: &S::Public) -> Self {
+        Self::from_poly(&Poly::from(vec![public.clone()]), 0)
+    }
+
+    /// Creates a verifier for the signatures of the public key of the
+    /// polynomial, and for the partial signatures of its `n` shares, at the
+    /// indices `0..n`.
+    pub fn from_poly(public: &Poly<S::Public>, n: usize) -> Self {
+        Self {
+            generator: S::prepare(&S::Public::one()),
+            key: S::prepare(public.public_key()),
+            public: public.clone(),
+            n,
+            hierarchy: None,
+            shares: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Verifies the partial signatures of the shares of a hierarchical
+    /// sharing instead of a flat one. The valid indices are the ones of the
+    /// hierarchy.
+    pub fn with_hierarchy(mut self, hierarchy: Hierarchy) -> Self {
+        self.hierarchy = Some(hierarchy);
+        self.shares = RwLock::new(HashMap::new());
+        self
+    }
+
+    /// Returns the public polynomial
+    pub fn public(&self) -> &Poly<S::Public> {
+        &self.public
+    }
+
+    /// Verifies the signature of the message by the public key
+    pub fn verify(&self, msg: &[u8], sig: &[u8]) -> Result<(), BLSError> {
+        S::internal_verify_prepared(&self.generator, &self.key, msg, sig, true)
+    }
+
+    /// Verifies the partial signature of the message by its share. The order
+    /// of the derivative evaluated for the share is the one of the hierarchy,
+    /// the partial signature must carry the same one.
+    pub fn partial_verify(&self, msg: &[u8], partial: &[u8]) -> Result<(), ThresholdError<S>> {
+        let (partial, order) = parse_partial::<S>(partial)?;
+        let expected = self
+            .order(partial.index)
+            .ok_or(ThresholdError::UnknownShare(partial.index))?;
+        if expected != order {
+            return Err(ThresholdError::InvalidOrder(partial.index, order));
+        }
+        let sig: S::Signature = bincode::deserialize(&partial.value)?;
+        if sig == S::Signature::zero() {
+            return Err(ThresholdError::IdentityPartial(partial.index));
+        }
+        let public_i = self
+            .share_key(partial.index)
+            .ok_or(ThresholdError::IdentityPartial(partial.index))?;
+        S::internal_verify_prepared(&self.generator, &public_i, msg, &partial.value, true)
+            .map_err(ThresholdError::SignatureError)
+    }
+
+    /// Verifies the blinded partial signature of the blinded message by its
+    /// share
+    pub fn verify_blind_partial(
+        &self,
+        blind_msg: &[u8],
+        blind_partial: &[u8],
+    ) -> Result<(), BlindThresholdError<BlindError>> {
+        let blinded_partial: Eval<Vec<u8>> = bincode::deserialize(blind_partial)?;
+        let public_i = self
+            .share_key(blinded_partial.index)
+            .ok_or(BlindThresholdError::UnknownShare(blinded_partial.index))?;
+        S::internal_verify_prepared(
+            &self.generator,
+            &public_i,
+            blind_msg,
+            &blinded_partial.value,
+            false,
+        )
+        .map_err(|err| BlindThresholdError::BlindError(err.into()))
+    }
+
+    // returns the prepared key of the share, evaluating it the first time, or
+    // None if the index is not one of the valid ones or if its key is the
+    // identity, e.g. a derivative of an order larger than the degree
+    fn share_key(&self, idx: Idx) -> Option<S::Prepared> {
+        if let Some(key) = self.shares.read().expect("lock poisoned").get(&idx) {
+            return Some(key.clone());
+        }
+        let order = self.order(idx)?;
+        let key = self.public.eval_derivative(order, idx).value;
+        if key == S::Public::zero() {
+            return None;
+        }
+        let key = S::prepare(&key);
+        self.shares
+            .write()
+            .expect("lock poisoned")
+            .insert(idx, key.clone());
+        Some(key)
+    }
+
+    // returns the order of the derivative evaluated for the share of the
+    // index, or None if the index is not one of the valid ones
+    fn order(&self, idx: Idx) -> Option<usize> {
+        match &self.hierarchy {
+            Some(hierarchy) => hierarchy.order(idx),
+            None if (idx as usize) < self.n => Some(0),
+            None => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curve::bls12377::PairingCurve as PCurve;
+    use crate::poly::LagrangeCoefficients;
+    use crate::sig::{
+        bls::{G1Scheme, G2Scheme},
+        tbls::Share,
+        BlindScheme, BlindThresholdScheme, SignatureScheme, ThresholdScheme,
+    };
+    use rand::thread_rng;
+
+    fn shares<S: BLSScheme>(n: usize, t: usize) -> (Vec<Share<S::Private>>, Poly<S::Public>) {
+        let private = Poly::<S::Private>::new(t - 1);
+        let shares = (0..n)
+            .map(|i| private.eval(i as Idx))
+            .map(|e| Share {
+                index: e.index,
+                private: e.value,
+            })
+            .collect();
+        (shares, private.commit())
+    }
+
+    #[test]
+    fn verify_g1() {
+        verify_test::<G1Scheme<PCurve>>();
+    }
+
+    #[test]
+    fn verify_g2() {
+        verify_test::<G2Scheme<PCurve>>();
+    }
+
+    fn verify_test<S: BLSScheme + 'static>() {
+        let (private, public) = S::keypair(&mut thread_rng());
+        let verifier = Verifier::<S>::new(&public);
+        let msg = vec![1, 9, 6, 9];
+        let sig = S::sign(&private, &msg).unwrap();
+        verifier.verify(&msg, &sig).unwrap();
+        verifier.verify(&[1, 9, 6, 8], &sig).unwrap_err();
+
+        let (shares, public) = shares::<S>(5, 3);
+        let verifier = Verifier::<S>::from_poly(&public, 5);
+        let partials = shares
+            .iter()
+            .map(|s| S::partial_sign(s, &msg).unwrap())
+            .collect::<Vec<_>>();
+        // twice, the second time with the cached keys
+        for _ in 0..2 {
+            for partial in &partials {
+                verifier.partial_verify(&msg, partial).unwrap();
+                S::partial_verify(&public, &msg, partial).unwrap();
+            }
+        }
+        // a partial signature of another share
+        let mut eval: Eval<Vec<u8>> = bincode::deserialize(&partials[0]).unwrap();
+        eval.index = 1;
+        let forged = bincode::serialize(&eval).unwrap();
+        verifier.partial_verify(&msg, &forged).unwrap_err();
+        // a partial signature of an index without a share is not evaluated
+        eval.index = 5;
+        let unknown = bincode::serialize(&eval).unwrap();
+        let err = verifier.partial_verify(&msg, &unknown).unwrap_err();
+        assert!(matches!(err, ThresholdError::UnknownShare(5)));
+        assert_eq!(verifier.shares.read().unwrap().len(), 5);
+
+        let sig = S::aggregate(3, &partials).unwrap();
+        verifier.verify(&msg, &sig).unwrap();
+
+        // blinded partial signatures
+        let (_, blinded) = S::blind_msg(&msg, &mut thread_rng());
+        for share in &shares {
+            let partial = S::sign_blind_partial(share, &blinded).unwrap();
+            verifier.verify_blind_partial(&blinded, &partial).unwrap();
+            verifier.verify_blind_partial(&msg, &partial).unwrap_err();
+        }
+    }
+
+    #[test]
+    fn hierarchical_g1() {
+        hierarchical_test::<G1Scheme<PCurve>>();
+    }
+
+    #[test]
+    fn hierarchical_g2() {
+        hierarchical_test::<G2Scheme<PCurve>>();
+    }
+
+    fn hierarchical_test<S: BLSScheme + 'static>() {
+        // 3 of 7, at least 1 of the first 2 indices
+        let levels = (0..7).map(|i| (i, if i < 2 { 0 } else { 1 })).collect();
+        let hierarchy = Hierarchy::new(vec![1, 3], levels).unwrap();
+        let private = Poly::<S::Private>::new(hierarchy.threshold() - 1);
+        let public = private.commit();
+        let verifier = Verifier::<S>::from_poly(&public, 0).with_hierarchy(hierarchy.clone());
+
+        let msg = vec![1, 9, 6, 9];
+        let shares = (0..7)
+            .map(|i| hierarchy.eval(&private, i).unwrap())
+            .map(|e| Share {
+                index: e.index,
+                private: e.value,
+            })
+            .collect::<Vec<_>>();
+        let partials = shares
+            .iter()
+            .map(|s| {
+                let order = hierarchy.order(s.index).unwrap();
+                S::partial_sign_derivative(s, order, &msg).unwrap()
+            })
+            .collect::<Vec<_>>();
+        for partial in &partials {
+            verifier.partial_verify(&msg, partial).unwrap();
+        }
+        // the order of the partials of the second level is only known from
+        // the hierarchy
+        S::partial_verify(&public, &msg, &partials[1]).unwrap();
+        let err = S::partial_verify(&public, &msg, &partials[2]).unwrap_err();
+        assert!(matches!(err, ThresholdError::HierarchicalPartial(2)));
+        // the identity verifies against a derivative larger than the degree
+        let identity = |index| {
+            let eval = Eval {
+                index,
+                value: bincode::serialize(&S::Signature::zero()).unwrap(),
+            };
+            bincode::serialize(&(eval, hierarchy.threshold() as u32)).unwrap()
+        };
+        let err = verifier.partial_verify(&msg, &identity(2)).unwrap_err();
+        assert!(matches!(err, ThresholdError::InvalidOrder(2, 3)));
+        let err = S::partial_verify(&public, &msg, &identity(2)).unwrap_err();
+        assert!(matches!(err, ThresholdError::HierarchicalPartial(2)));
+        // the first level holds flat shares
+        assert_eq!(partials[1], S::partial_sign(&shares[1], &msg).unwrap());
+        // a share of the second level signed as a flat one
+        let partial = S::partial_sign(&shares[3], &msg).unwrap();
+        let err = verifier.partial_verify(&msg, &partial).unwrap_err();
+        assert!(matches!(err, ThresholdError::InvalidOrder(3, 0)));
+        S::partial_verify(&public, &msg, &partial).unwrap_err();
+        // an index outside of the hierarchy
+        let share = Share {
+            index: 7,
+            private: private.eval(7).value,
+        };
+        let partial = S::partial_sign(&share, &msg).unwrap();
+        let err = verifier.partial_verify(&msg, &partial).unwrap_err();
+        assert!(matches!(err, ThresholdError::UnknownShare(7)));
+
+        // Lagrange interpolation does not work for the second level
+        let err = S::aggregate(3, &partials).unwrap_err();
+        assert!(matches!(err, ThresholdError::HierarchicalPartial(2)));
+
+        let coeffs = hierarchy.coefficients(&[0, 4, 6]).unwrap();
+        let sig = S::aggregate_with(&coeffs, &partials).unwrap();
+        verifier.verify(&msg, &sig).unwrap();
+        // the orders must be the ones of the coefficients
+        let mut forged = partials.clone();
+        forged[4] = identity(4);
+        let err = S::aggregate_with(&coeffs, &forged).unwrap_err();
+        assert!(matches!(err, ThresholdError::InvalidOrder(4, 3)));
+        let coeffs = LagrangeCoefficients::for_indices(&[0, 4, 6]).unwrap();
+        let err = S::aggregate_with(&coeffs, &partials).unwrap_err();
+        assert!(matches!(err, ThresholdError::HierarchicalPartial(4)));
+        hierarchy
+            .coefficients::<S::Private>(&[2, 4, 6])
+            .unwrap_err();
+    }
+}
@@ -0,0 +1,308 @@
+//! Verifiable secret sharing: a dealer splits a secret into shares along with a
+//! commitment to its polynomial, which lets each share holder check its share
+//! without trusting the dealer.
+//!
+//! With Feldman's scheme, the commitment is the public polynomial, i.e. the
+//! coefficients multiplied by the generator, so it reveals the public key of the
+//! secret. Pedersen's scheme also commits with a second generator and a random
+//! blinding polynomial, so the commitment reveals nothing about the secret.
+use crate::{
+    group::{Curve, Element, Point, Scalar},
+    poly::{x_coordinate, Idx, Poly, PrivatePoly, PublicPoly},
+    sig::Share,
+};
+use rand_core::RngCore;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// The message hashed to the curve to derive the second generator of
+/// Pedersen's scheme, so that its discrete logarithm is unknown.
+const PEDERSEN_DOMAIN: &[u8] = b"threshold-bls pedersen vss generator";
+
+/// The shares of a dealing, along with the commitment to them
+pub type Dealing<C> = (Vec<Share<<C as Curve>::Scalar>>, PublicPoly<C>);
+/// The shares of a dealing of Pedersen's scheme, along with the commitment to
+/// them
+pub type PedersenDealing<C> = (Vec<PedersenShare<<C as Curve>::Scalar>>, PublicPoly<C>);
+
+/// Errors associated with dealing shares
+#[derive(Debug, Error)]
+pub enum VSSError {
+    /// InvalidThreshold is raised when the threshold is 0 or larger than the
+    /// number of shares
+    #[error("invalid threshold {0} for {1} shares")]
+    InvalidThreshold(usize, usize),
+}
+
+/// Splits the secret into `n` shares at the indices `0..n`, `t` of which are
+/// required to recover it, and returns them along with the public polynomial
+/// committing to them.
+pub fn deal<C: Curve, R: RngCore>(
+    secret: C::Scalar,
+    t: usize,
+    n: usize,
+    rng: &mut R,
+) -> Result<Dealing<C>, VSSError> {
+    let private = sharing_poly::<C::Scalar, _>(secret, t, n, rng)?;
+    let shares = (0..n as Idx)
+        .map(|i| {
+            let eval = private.eval(i);
+            Share {
+                index: eval.index,
+                private: eval.value,
+            }
+        })
+        .collect();
+    Ok((shares, private.commit()))
+}
+
+/// Checks the share against the public polynomial committing to it
+pub fn verify_share<C: Curve>(share: &Share<C::Scalar>, commitment: &PublicPoly<C>) -> bool {
+    C::generator_table().mul(&share.private) == commitment.eval(share.index).value
+}
+
+/// Checks many shares of the same public polynomial at once. The shares are
+/// combined with random coefficients `r_i`, so that a single multi-scalar
+/// multiplication checks `sum r_i s_i * G == sum_j (sum_i r_i x_i^j) * C_j`,
+/// instead of evaluating the public polynomial for each share. An incorrect
+/// share passes the check with negligible probability only.
+pub fn verify_shares<C: Curve, R: RngCore>(
+    shares: &[Share<C::Scalar>],
+    commitment: &PublicPoly<C>,
+    rng: &mut R,
+) -> bool {
+    match shares {
+        [] => return true,
+        [share] => return verify_share::<C>(share, commitment),
+        _ => {}
+    }
+
+    // the combined share, and for each coefficient the combination of the
+    // powers of the evaluation points
+    let mut combined = C::Scalar::zero();
+    let mut weights = vec![C::Scalar::zero(); commitment.degree() + 1];
+    for share in shares {
+        let r = C::Scalar::rand(rng);
+        let mut term = share.private.clone();
+        term.mul(&r);
+        combined.add(&term);
+
+        let x = x_coordinate::<C::Scalar>(share.index);
+        let mut power = r;
+        for weight in weights.iter_mut() {
+            weight.add(&power);
+            power.mul(&x);
+        }
+    }
+
+    let commit = C::generator_table().mul(&combined);
+    let coeffs = (0..weights.len())
+        .map(|j| commitment.get(j as Idx))
+        .collect::<Vec<_>>();
+    commit == C::Point::msm(&coeffs, &weights)
+}
+
+/// A share of Pedersen's scheme: the evaluations of the secret and of the
+/// blinding polynomials
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PedersenShare<S> {
+    /// The share's index in the polynomials
+    pub index: Idx,
+    /// The scalar corresponding to the share's secret
+    pub private: S,
+    /// The evaluation of the blinding polynomial
+    pub blinding: S,
+}
+
+impl<S> From<PedersenShare<S>> for Share<S> {
+    fn from(share: PedersenShare<S>) -> Self {
+        Share {
+            index: share.index,
+            private: share.private,
+        }
+    }
+}
+
+/// Pedersen's verifiable secret sharing. The commitment to a share is
+/// `s_i * G + b_i * H`, where `H` is a second generator derived by hashing to
+/// the curve, so that nobody knows its discrete logarithm in base `G`.
+#[derive(Debug, Clone)]
+pub struct Pedersen<C: Curve> {
+    h: C::Point,
+}
+
+impl<C: Curve> Default for Pedersen<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C: Curve> Pedersen<C> {
+    /// Derives the second generator
+    pub fn new() -> Self {
+        let mut h = C::Point::new();
+        h.map(PEDERSEN_DOMAIN)
+            .expect("could not hash the generator to the curve");
+        Self { h }
+    }
+
+    /// Returns the second generator
+    pub fn generator(&self) -> &C::Point {
+        &self.h
+    }
+
+    /// Splits the secret into `n` shares at the indices `0..n`, `t` of which
+    /// are required to recover it, and returns them along with the hiding
+    /// commitment to the secret and blinding polynomials.
+    pub fn deal<R: RngCore>(
+        &self,
+        secret: C::Scalar,
+        t: usize,
+        n: usize,
+        rng: &mut R,
+    ) -> Result<PedersenDealing<C>, VSSError> {
+        let private = sharing_poly::<C::Scalar, _>(secret, t, n, rng)?;
+        let blinding = PrivatePoly::<C>::new_from(t - 1, rng);
+
+        let mut commitment = private.commit::<C::Point>();
+        let coeffs = (0..t)
+            .map(|j| {
+                let mut c = self.h.clone();
+                c.mul(&blinding.get(j as Idx));
+                c
+            })
+            .collect::<Vec<_>>();
+        commitment.add(&Poly::from(coeffs));
+
+        let shares = (0..n as Idx)
+            .map(|i| PedersenShare {
+                index: i,
+                private: private.eval(i).value,
+                blinding: blinding.eval(i).value,
+            })
+            .collect();
+        Ok((shares, commitment))
+    }
+
+    /// Checks the share against the commitment of the polynomials
+    pub fn verify_share(
+        &self,
+        share: &PedersenShare<C::Scalar>,
+        commitment: &PublicPoly<C>,
+    ) -> bool {
+        let commit = C::Point::msm(
+            &[C::point(), self.h.clone()],
+            &[share.private.clone(), share.blinding.clone()],
+        );
+        commit == commitment.eval(share.index).value
+    }
+}
+
+// returns a random polynomial of degree `t - 1` whose constant term is the
+// secret
+fn sharing_poly<X: Scalar<RHS = X>, R: RngCore>(
+    secret: X,
+    t: usize,
+    n: usize,
+    rng: &mut R,
+) -> Result<Poly<X>, VSSError> {
+    if t == 0 || t > n {
+        return Err(VSSError::InvalidThreshold(t, n));
+    }
+    let coeffs = std::iter::once(secret)
+        .chain((1..t).map(|_| X::rand(rng)))
+        .collect::<Vec<_>>();
+    Ok(Poly::from(coeffs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curve::bls12377::{G1Curve, G2Curve};
+    use crate::poly::Eval;
+    use rand::prelude::*;
+
+    #[test]
+    fn feldman_g1() {
+        feldman_test::<G1Curve>();
+    }
+
+    #[test]
+    fn feldman_g2() {
+        feldman_test::<G2Curve>();
+    }
+
+    fn feldman_test<C: Curve>() {
+        let rng = &mut thread_rng();
+        let secret = C::Scalar::rand(rng);
+        let (mut shares, commitment) = deal::<C, _>(secret.clone(), 3, 7, rng).unwrap();
+        assert_eq!(shares.len(), 7);
+        assert_eq!(commitment.degree(), 2);
+        assert_eq!(commitment.public_key(), &C::generator_table().mul(&secret));
+        assert!(shares.iter().all(|s| verify_share::<C>(s, &commitment)));
+        assert!(verify_shares::<C, _>(&shares, &commitment, rng));
+        assert!(verify_shares::<C, _>(&shares[..1], &commitment, rng));
+        assert!(verify_shares::<C, _>(&[], &commitment, rng));
+
+        let evals = shares
+            .iter()
+            .map(|s| Eval {
+                index: s.index,
+                value: s.private.clone(),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(Poly::recover(3, evals).unwrap(), secret);
+
+        // a single incorrect share fails the whole batch
+        shares[5].private.add(&C::Scalar::one());
+        assert!(!verify_share::<C>(&shares[5], &commitment));
+        assert!(!verify_shares::<C, _>(&shares, &commitment, rng));
+        assert!(!verify_shares::<C, _>(&shares[5..6], &commitment, rng));
+        // swapping two correct shares does too
+        shares[5].private.sub(&C::Scalar::one());
+        let share = shares[2].private.clone();
+        shares[2].private = shares[3].private.clone();
+        shares[3].private = share;
+        assert!(!verify_shares::<C, _>(&shares, &commitment, rng));
+
+        let err = deal::<C, _>(secret.clone(), 0, 7, rng).unwrap_err();
+        assert!(matches!(err, VSSError::InvalidThreshold(0, 7)));
+        let err = deal::<C, _>(secret, 8, 7, rng).unwrap_err();
+        assert!(matches!(err, VSSError::InvalidThreshold(8, 7)));
+    }
+
+    #[test]
+    fn pedersen() {
+        let rng = &mut thread_rng();
+        let pedersen = Pedersen::<G1Curve>::new();
+        assert_ne!(pedersen.generator(), &G1Curve::point());
+
+        let secret = <G1Curve as Curve>::Scalar::rand(rng);
+        let (shares, commitment) = pedersen.deal(secret, 3, 5, rng).unwrap();
+        // the commitment hides the public key
+        assert_ne!(
+            commitment.public_key(),
+            &G1Curve::generator_table().mul(&secret)
+        );
+        assert!(shares.iter().all(|s| pedersen.verify_share(s, &commitment)));
+
+        let mut share = shares[1].clone();
+        share.blinding.add(&<G1Curve as Curve>::Scalar::one());
+        assert!(!pedersen.verify_share(&share, &commitment));
+        let share = PedersenShare {
+            index: 2,
+            ..shares[1].clone()
+        };
+        assert!(!pedersen.verify_share(&share, &commitment));
+
+        let evals = shares
+            .into_iter()
+            .map(Share::from)
+            .map(|s| Eval {
+                index: s.index,
+                value: s.private,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(Poly::recover(3, evals).unwrap(), secret);
+    }
+}